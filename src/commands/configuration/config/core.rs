@@ -1,12 +1,14 @@
 use std::sync::Arc;
 use diesel::prelude::*;
 use diesel_async::RunQueryDsl;
-use poise::serenity_prelude::{ComponentInteraction, CreateEmbed, CreateInteractionResponse, CreateInteractionResponseMessage, CreateSelectMenuKind};
+use chrono_tz::Tz;
+use poise::serenity_prelude::{ComponentInteraction, CreateActionRow, CreateEmbed, CreateInputText, CreateInteractionResponse, CreateInteractionResponseMessage, CreateModal, CreateSelectMenuKind, InputTextStyle};
+use poise::serenity_prelude::CreateInteractionResponse::Modal;
 use crate::{BotError, Context};
 use crate::database::models::GuildSettings;
 use crate::localization::manager::{Language, LocalizationManager, TranslationParam};
 use crate::util::color::BotColors;
-use crate::util::interaction::{await_interaction, create_select_menu, create_select_menu_with_default, get_selected_value};
+use crate::util::interaction::{await_interaction, await_modal_interaction, create_select_menu, create_select_menu_with_default, get_modal_value, get_selected_value};
 
 pub async fn handle_core_config(ctx: Context<'_>, interaction: ComponentInteraction) -> Result<(), BotError> {
     let guild_table = fetch_guild_settings(ctx).await;
@@ -16,15 +18,18 @@ pub async fn handle_core_config(ctx: Context<'_>, interaction: ComponentInteract
     
     interaction.create_response(ctx.http(), CreateInteractionResponse::UpdateMessage(
         CreateInteractionResponseMessage::default()
-            .embed(create_core_config_embed(lang, locales.clone()))
+            .embed(create_core_config_embed(lang, locales.clone(), &guild_table))
             .components(vec![create_select_menu("core_config", vec![
                 (locales.get("commands.configuration.core.bot_language.display_name", lang, &[]), "bot_language".parse().unwrap()),
+                (locales.get("commands.configuration.core.timezone.display_name", lang, &[]), "timezone".parse().unwrap()),
             ], &locales.get("commands.configuration.config.placeholder.option", lang, &[]), CreateSelectMenuKind::String { options: Default::default() })])
     )).await?;
 
     if let Some(interaction) = await_interaction(&ctx, &interaction.message, "core_config").await {
         if get_selected_value(&interaction)? == "bot_language" {
             edit_bot_language(ctx, locales, interaction, guild_table).await?;
+        } else if get_selected_value(&interaction)? == "timezone" {
+            edit_timezone(ctx, locales, interaction, lang).await?;
         }
     }
 
@@ -41,11 +46,12 @@ async fn fetch_guild_settings(ctx: Context<'_>) -> GuildSettings {
     }).await.unwrap()
 }
 
-fn create_core_config_embed(lang: Language, locales: Arc<LocalizationManager>) -> CreateEmbed<'static> {
+fn create_core_config_embed(lang: Language, locales: Arc<LocalizationManager>, guild_settings: &GuildSettings) -> CreateEmbed<'static> {
     CreateEmbed::new()
         .title(locales.get("commands.configuration.config.embed_title", lang, &[]))
         .color(BotColors::Default.color())
         .field(locales.get("commands.configuration.core.bot_language.display_name", lang, &[]), locales.get_translated_lang_name(lang), false)
+        .field(locales.get("commands.configuration.core.timezone.display_name", lang, &[]), guild_settings.timezone.clone(), false)
 }
 
 
@@ -90,4 +96,56 @@ async fn update_bot_language(ctx: &Context<'_>, new_lang: String) -> Result<(),
     }).await?;
 
     Ok(())
-}
\ No newline at end of file
+}
+
+async fn edit_timezone(ctx: Context<'_>, locales: Arc<LocalizationManager>, interaction: ComponentInteraction, lang: Language) -> Result<(), BotError> {
+    let input_text = CreateInputText::new(InputTextStyle::Short, locales.get("commands.configuration.core.timezone.set.label", lang, &[]), "timezone")
+        .placeholder("Europe/Warsaw")
+        .min_length(1)
+        .max_length(64);
+
+    let modal = CreateModal::new("timezone_modal", locales.get("commands.configuration.core.timezone.set.title", lang, &[]))
+        .components(vec![CreateActionRow::InputText(input_text)]);
+
+    interaction.create_response(ctx.http(), Modal(modal)).await?;
+
+    let Some(interaction) = await_modal_interaction(&ctx, &interaction.message, "timezone_modal").await else {
+        return Ok(());
+    };
+
+    let Ok(new_timezone) = get_modal_value(&interaction)?.trim().parse::<Tz>() else {
+        interaction.create_response(ctx.http(), CreateInteractionResponse::Message(
+            CreateInteractionResponseMessage::default()
+                .content(locales.get("commands.configuration.core.timezone.set.error_invalid", lang, &[]))
+        )).await?;
+        return Ok(());
+    };
+
+    update_timezone(&ctx, new_timezone.name().to_string()).await?;
+
+    interaction.create_response(ctx.http(), CreateInteractionResponse::UpdateMessage(
+        CreateInteractionResponseMessage::default()
+            .embed(CreateEmbed::new()
+                .title(locales.get("commands.configuration.core.timezone.done.title", lang, &[]))
+                .description(locales.get("commands.configuration.core.timezone.done.description", lang, &[
+                    TranslationParam::from(new_timezone.name().to_string())
+                ]))
+                .color(BotColors::Default.color())
+            )
+            .components(vec![])
+    )).await?;
+
+    Ok(())
+}
+
+async fn update_timezone(ctx: &Context<'_>, new_timezone: String) -> Result<(), BotError> {
+    use crate::database::schema::guild_settings::dsl::*;
+
+    ctx.data().db.run(move |conn| {
+        diesel::update(guild_settings.filter(guild_id.eq(ctx.guild_id().unwrap().get() as i64)))
+            .set(timezone.eq(new_timezone.clone()))
+            .execute(conn)
+    }).await?;
+
+    Ok(())
+}
@@ -2,15 +2,22 @@ use std::borrow::Cow;
 use std::sync::Arc;
 use diesel::prelude::*;
 use diesel_async::RunQueryDsl;
-use poise::serenity_prelude::{ChannelId, ChannelType, ComponentInteraction, CreateActionRow, CreateEmbed, CreateInputText, CreateInteractionResponse, CreateInteractionResponseMessage, CreateModal, CreateSelectMenuKind, InputTextStyle};
+use poise::serenity_prelude::{ChannelId, ChannelType, RoleId, ComponentInteraction, CreateActionRow, CreateEmbed, CreateInputText, CreateInteractionResponse, CreateInteractionResponseMessage, CreateModal, CreateSelectMenuKind, InputTextStyle};
 use poise::serenity_prelude::CreateInteractionResponse::Modal;
+use regex::Regex;
 use strum::IntoEnumIterator;
 use crate::{BotError, Context};
-use crate::database::models::{ModerationSettings};
+use crate::database::models::{CaseTypes, ModerationSettings, ReasonPresets};
 use crate::localization::manager::{Language, LocalizationManager, TranslationParam};
+use crate::modules::moderation::case_types::{case_type_key, fetch_case_types, BUILTIN_CASE_TYPES};
+use crate::modules::moderation::cases::NEVER_EXPIRE;
 use crate::modules::moderation::logs::{get_active_log_types, string_to_log_type, LogType};
+use crate::modules::moderation::nicknames::DEFAULT_NICKNAME_TEMPLATE;
+use crate::modules::moderation::presets::{fetch_presets, MAX_PRESET_LENGTH};
+use crate::modules::moderation::quarantine::apply_quarantine_overwrites;
 use crate::util::color::BotColors;
-use crate::util::interaction::{await_interaction, await_modal_interaction, create_select_menu, create_select_menu_with_default, get_modal_value, get_selected_value, get_selected_values};
+use crate::util::time::{format_duration, parse_duration};
+use crate::util::interaction::{await_interaction, await_modal_interaction, create_select_menu, create_select_menu_with_default, get_modal_value, get_modal_values, get_selected_value, get_selected_values, MAX_SELECT_OPTIONS};
 
 pub async fn handle_moderation_config(ctx: Context<'_>, interaction: ComponentInteraction) -> Result<(), BotError> {
     let locales = ctx.data().localization_manager.clone();
@@ -23,18 +30,48 @@ pub async fn handle_moderation_config(ctx: Context<'_>, interaction: ComponentIn
             .embed(create_moderation_config_embed(&moderation_table, lang, locales.clone()))
             .components(vec![create_select_menu("core_config", vec![
                 (locales.get("commands.configuration.moderation.warn_expire_time.display_name", lang, &[]), "warn_expire".parse().unwrap()),
+                (locales.get("commands.configuration.moderation.warn_decay.display_name", lang, &[]), "warn_decay".parse().unwrap()),
                 (locales.get("commands.configuration.moderation.default_log_channel.display_name", lang, &[]), "default_log_channel".parse().unwrap()),
                 (locales.get("commands.configuration.moderation.log_types.display_name", lang, &[]), "log_types".parse().unwrap()),
+                (locales.get("commands.configuration.moderation.lockdown_channels.display_name", lang, &[]), "lockdown_channels".parse().unwrap()),
+                (locales.get("commands.configuration.moderation.auto_dehoist.display_name", lang, &[]), "auto_dehoist".parse().unwrap()),
+                (locales.get("commands.configuration.moderation.nickname_patterns.display_name", lang, &[]), "nickname_patterns".parse().unwrap()),
+                (locales.get("commands.configuration.moderation.nickname_template.display_name", lang, &[]), "nickname_template".parse().unwrap()),
+                (locales.get("commands.configuration.moderation.quarantine.display_name", lang, &[]), "quarantine".parse().unwrap()),
+                (locales.get("commands.configuration.moderation.quarantine_automod_rules.display_name", lang, &[]), "quarantine_automod_rules".parse().unwrap()),
+                (locales.get("commands.configuration.moderation.reason_presets.display_name", lang, &[]), "reason_presets".parse().unwrap()),
+                (locales.get("commands.configuration.moderation.case_types.display_name", lang, &[]), "case_types".parse().unwrap()),
+                (locales.get("commands.configuration.moderation.evidence_channel.display_name", lang, &[]), "evidence_channel".parse().unwrap()),
             ], &locales.get("commands.configuration.config.placeholder.option", lang, &[]), CreateSelectMenuKind::String { options: Default::default() })])
     )).await?;
 
     if let Some(interaction) = await_interaction(&ctx, &interaction.message, "core_config").await {
         if get_selected_value(&interaction)? == "warn_expire" {
             edit_warn_expire(ctx, locales, interaction, moderation_table, lang).await?;
+        } else if get_selected_value(&interaction)? == "warn_decay" {
+            edit_warn_decay(ctx, interaction, moderation_table).await?;
         } else if get_selected_value(&interaction)? == "default_log_channel" {
             edit_default_log_channel(ctx, interaction, moderation_table).await?;
         } else if get_selected_value(&interaction)? == "log_types" {
             edit_log_types(ctx, interaction, moderation_table).await?;
+        } else if get_selected_value(&interaction)? == "lockdown_channels" {
+            edit_lockdown_channels(ctx, interaction, moderation_table).await?;
+        } else if get_selected_value(&interaction)? == "auto_dehoist" {
+            edit_auto_dehoist(ctx, interaction, moderation_table).await?;
+        } else if get_selected_value(&interaction)? == "nickname_patterns" {
+            edit_nickname_patterns(ctx, interaction).await?;
+        } else if get_selected_value(&interaction)? == "nickname_template" {
+            edit_nickname_template(ctx, interaction).await?;
+        } else if get_selected_value(&interaction)? == "quarantine" {
+            edit_quarantine(ctx, interaction, moderation_table).await?;
+        } else if get_selected_value(&interaction)? == "quarantine_automod_rules" {
+            edit_quarantine_automod_rules(ctx, interaction, moderation_table).await?;
+        } else if get_selected_value(&interaction)? == "reason_presets" {
+            edit_reason_presets(ctx, interaction).await?;
+        } else if get_selected_value(&interaction)? == "case_types" {
+            edit_case_types(ctx, interaction).await?;
+        } else if get_selected_value(&interaction)? == "evidence_channel" {
+            edit_evidence_channel(ctx, interaction, moderation_table).await?;
         }
     }
 
@@ -60,11 +97,32 @@ fn create_moderation_config_embed(
     CreateEmbed::new()
         .title(locales.get("commands.configuration.config.embed_title", lang, &[]))
         .color(BotColors::Default.color())
-        .field(locales.get("commands.configuration.moderation.warn_expire_time.display_name", lang, &[]), locales.get("commands.configuration.moderation.warn_expire_time.value", lang, &[
-            TranslationParam::from(moderation_table.warn_expire_time.to_string())
-        ]), false)
+        .field(locales.get("commands.configuration.moderation.warn_expire_time.display_name", lang, &[]), format_warn_expire_time(moderation_table.warn_expire_time, lang, &locales), false)
+        .field(locales.get("commands.configuration.moderation.warn_decay.display_name", lang, &[]), format_warn_decay(moderation_table, lang, &locales), false)
         .field(locales.get("commands.configuration.moderation.default_log_channel.display_name", lang, &[]), moderation_table.default_log_channel.map_or(locales.get("commands.configuration.config.none", lang, &[]), |id| format!("<#{}>", id)), false)
         .field(locales.get("commands.configuration.moderation.log_types.display_name", lang, &[]), get_active_log_types(moderation_table.log_types as u32, &locales.clone(), lang).iter().map(|log_type| log_type.to_string()).collect::<Vec<_>>().join(", "), false)
+        .field(locales.get("commands.configuration.moderation.lockdown_channels.display_name", lang, &[]), format_lockdown_channels(&moderation_table.lockdown_channels, lang, &locales), false)
+        .field(locales.get("commands.configuration.moderation.auto_dehoist.display_name", lang, &[]), if moderation_table.auto_dehoist {
+            locales.get("commands.configuration.config.enabled", lang, &[])
+        } else {
+            locales.get("commands.configuration.config.disabled", lang, &[])
+        }, false)
+        .field(locales.get("commands.configuration.moderation.nickname_patterns.display_name", lang, &[]), if moderation_table.nickname_patterns.is_empty() {
+            locales.get("commands.configuration.config.none", lang, &[])
+        } else {
+            moderation_table.nickname_patterns.iter().map(|p| format!("`{}`", p)).collect::<Vec<_>>().join(", ")
+        }, false)
+        .field(locales.get("commands.configuration.moderation.nickname_template.display_name", lang, &[]), format!("`{}`", moderation_table.nickname_template), false)
+        .field(locales.get("commands.configuration.moderation.quarantine.display_name", lang, &[]), match (moderation_table.quarantine_role, moderation_table.quarantine_channel) {
+            (Some(role), Some(channel)) => format!("<@&{}> (<#{}>)", role, channel),
+            _ => locales.get("commands.configuration.config.none", lang, &[]),
+        }, false)
+        .field(locales.get("commands.configuration.moderation.quarantine_automod_rules.display_name", lang, &[]), if moderation_table.quarantine_automod_rules.is_empty() {
+            locales.get("commands.configuration.config.none", lang, &[])
+        } else {
+            moderation_table.quarantine_automod_rules.iter().map(|id| format!("`{}`", id)).collect::<Vec<_>>().join(", ")
+        }, false)
+        .field(locales.get("commands.configuration.moderation.evidence_channel.display_name", lang, &[]), moderation_table.evidence_channel.map_or(locales.get("commands.configuration.config.none", lang, &[]), |id| format!("<#{}>", id)), false)
 }
 
 
@@ -94,7 +152,7 @@ async fn edit_warn_expire(ctx: Context<'_>, locales: Arc<LocalizationManager>, i
     ];
 
 
-    let custom_label = format!("{} days", mod_table.warn_expire_time);
+    let custom_label = format_warn_expire_time(mod_table.warn_expire_time, lang, &locales);
 
     if mod_table.warn_expire_time != 3 && mod_table.warn_expire_time != 7 && mod_table.warn_expire_time != 14 && mod_table.warn_expire_time != 30 {
         options.insert(5, (
@@ -124,7 +182,7 @@ async fn edit_warn_expire(ctx: Context<'_>, locales: Arc<LocalizationManager>, i
             let input_text = CreateInputText::new(InputTextStyle::Short, locales.get("commands.configuration.moderation.warn_expire_time.set.custom.placeholder", lang, &[]), "days")
                 .placeholder(locales.get("commands.configuration.moderation.warn_expire_time.set.custom.placeholder", lang, &[]))
                 .min_length(1)
-                .max_length(3);
+                .max_length(32);
 
             let action_row = CreateActionRow::InputText(input_text);
 
@@ -134,36 +192,51 @@ async fn edit_warn_expire(ctx: Context<'_>, locales: Arc<LocalizationManager>, i
             interaction.create_response(ctx.http(), Modal(modal)).await?;
 
             if let Some(interaction) = await_modal_interaction(&ctx, &interaction.message, "custom_warn_expire_time").await {
-                let selected_days = get_modal_value(&interaction)?;
+                let selected_duration = get_modal_value(&interaction)?;
 
-                if let Ok(days) = selected_days.parse::<i64>() {
-                    if days >= 3 {
-                        update_warn_expire_time(&ctx, days).await?;
-                    } else {
+                // Takes the same syntax as `/warn expire`, a bare number still means days
+                let selected_duration = if selected_duration.trim().chars().all(|c| c.is_ascii_digit()) {
+                    format!("{}d", selected_duration.trim())
+                } else {
+                    selected_duration
+                };
+
+                let days = match parse_duration(&selected_duration, lang) {
+                    // Stored in whole days, partial days are rounded up
+                    Ok(Some(seconds)) if seconds >= 3 * 86400 => seconds.div_ceil(86400) as i64,
+                    Ok(Some(_)) => {
                         interaction.create_response(ctx.http(), CreateInteractionResponse::Message(
                             CreateInteractionResponseMessage::default()
                                 .content(locales.get("commands.configuration.moderation.warn_expire_time.set.custom.error_too_low", lang, &[]))
                         )).await?;
                         return Ok(());
+                    },
+                    Ok(None) => NEVER_EXPIRE,
+                    Err(why) => {
+                        interaction.create_response(ctx.http(), CreateInteractionResponse::Message(
+                            CreateInteractionResponseMessage::default()
+                                .content(why.localize(lang, &locales))
+                        )).await?;
+                        return Ok(());
                     }
-                } else {
-                    interaction.create_response(ctx.http(), CreateInteractionResponse::Message(
-                        CreateInteractionResponseMessage::default()
-                            .content(locales.get("commands.configuration.moderation.warn_expire_time.set.custom.error_invalid", lang, &[]))
-                    )).await?;
-                    return Ok(());
-                }
+                };
+
+                update_warn_expire_time(&ctx, days).await?;
 
-                update_warn_expire_time(&ctx, selected_days.parse().unwrap()).await?;
+                let description = if days == NEVER_EXPIRE {
+                    locales.get("commands.configuration.moderation.warn_expire_time.done.description_never", lang, &[])
+                } else {
+                    locales.get("commands.configuration.moderation.warn_expire_time.done.description", lang, &[
+                        TranslationParam::from(days.to_string())
+                    ])
+                };
 
 
                 interaction.create_response(ctx.http(), CreateInteractionResponse::UpdateMessage(
                     CreateInteractionResponseMessage::default()
                         .embed(CreateEmbed::new()
                             .title(locales.get("commands.configuration.moderation.warn_expire_time.done.title", lang, &[]))
-                            .description(ctx.data().localization_manager.get("commands.configuration.moderation.warn_expire_time.done.description", lang, &[
-                                TranslationParam::from(selected_days)
-                            ]))
+                            .description(description)
                             .color(BotColors::Default.color())
                         )
                         .components(vec![])
@@ -189,6 +262,16 @@ async fn edit_warn_expire(ctx: Context<'_>, locales: Arc<LocalizationManager>, i
     Ok(())
 }
 
+fn format_warn_expire_time(days: i64, lang: Language, locales: &LocalizationManager) -> String {
+    if days == NEVER_EXPIRE {
+        locales.get("commands.configuration.moderation.warn_expire_time.never", lang, &[])
+    } else {
+        locales.get("commands.configuration.moderation.warn_expire_time.value", lang, &[
+            TranslationParam::from(days.to_string())
+        ])
+    }
+}
+
 async fn update_warn_expire_time(ctx: &Context<'_>, warn_time: i64) -> Result<(), BotError> {
     use crate::database::schema::moderation_settings::dsl::*;
 
@@ -202,6 +285,104 @@ async fn update_warn_expire_time(ctx: &Context<'_>, warn_time: i64) -> Result<()
 }
 
 
+fn format_warn_decay(moderation_table: &ModerationSettings, lang: Language, locales: &LocalizationManager) -> String {
+    match moderation_table.warn_decay_period {
+        Some(period) => locales.get("commands.configuration.moderation.warn_decay.value", lang, &[
+            TranslationParam::from(moderation_table.warn_decay_points.to_string()),
+            TranslationParam::from(format_duration(period as u64, lang)),
+        ]),
+        None => locales.get("commands.configuration.config.disabled", lang, &[]),
+    }
+}
+
+async fn edit_warn_decay(ctx: Context<'_>, interaction: ComponentInteraction, mut mod_table: ModerationSettings) -> Result<(), BotError> {
+    let locales = ctx.data().localization_manager.clone();
+    let lang = locales.get_guild_language(ctx.data().db.clone(), ctx.guild_id().unwrap()).await.unwrap();
+
+    let modal = CreateModal::new("warn_decay_modal", locales.get("commands.configuration.moderation.warn_decay.set.title", lang, &[]))
+        .components(vec![
+            CreateActionRow::InputText(CreateInputText::new(InputTextStyle::Short, locales.get("commands.configuration.moderation.warn_decay.set.points", lang, &[]), "points")
+                .value(mod_table.warn_decay_points.to_string())
+                .max_length(3)
+                .required(true)),
+            CreateActionRow::InputText(CreateInputText::new(InputTextStyle::Short, locales.get("commands.configuration.moderation.warn_decay.set.period", lang, &[]), "period")
+                .placeholder("7d")
+                .max_length(32)
+                .required(false)),
+        ]);
+
+    interaction.create_response(ctx.http(), Modal(modal)).await?;
+
+    let Some(interaction) = await_modal_interaction(&ctx, &interaction.message, "warn_decay_modal").await else {
+        return Ok(());
+    };
+
+    let values = get_modal_values(&interaction)?;
+    let decay_period = values[1].trim();
+
+    let decay_points = match values[0].trim().parse::<i32>() {
+        Ok(p) if (1..=100).contains(&p) => p,
+        _ => {
+            interaction.create_response(ctx.http(), CreateInteractionResponse::Message(
+                CreateInteractionResponseMessage::default()
+                    .content(locales.get("commands.configuration.moderation.warn_decay.set.error_invalid_points", lang, &[]))
+            )).await?;
+            return Ok(());
+        }
+    };
+
+    // An empty period turns decay off
+    let decay_period = if decay_period.is_empty() {
+        None
+    } else {
+        match parse_duration(decay_period, lang) {
+            Ok(Some(seconds)) => Some(seconds as i64),
+            Ok(None) => None,
+            Err(why) => {
+                interaction.create_response(ctx.http(), CreateInteractionResponse::Message(
+                    CreateInteractionResponseMessage::default()
+                        .content(why.localize(lang, &locales))
+                )).await?;
+                return Ok(());
+            }
+        }
+    };
+
+    update_warn_decay(&ctx, decay_points, decay_period).await?;
+
+    mod_table.warn_decay_points = decay_points;
+    mod_table.warn_decay_period = decay_period;
+
+    interaction.create_response(ctx.http(), CreateInteractionResponse::UpdateMessage(
+        CreateInteractionResponseMessage::default()
+            .embed(CreateEmbed::new()
+                .title(locales.get("commands.configuration.moderation.warn_decay.done.title", lang, &[]))
+                .description(locales.get("commands.configuration.moderation.warn_decay.done.description", lang, &[
+                    TranslationParam::from(format_warn_decay(&mod_table, lang, &locales))
+                ]))
+                .color(BotColors::Default.color())
+            )
+            .components(vec![])
+    )).await?;
+
+    Ok(())
+}
+
+async fn update_warn_decay(ctx: &Context<'_>, decay_points: i32, decay_period: Option<i64>) -> Result<(), BotError> {
+    use crate::database::schema::moderation_settings::dsl::*;
+
+    ctx.data().db.run(move |conn| {
+        diesel::update(moderation_settings.filter(guild_id.eq(ctx.guild_id().unwrap().get() as i64)))
+            .set((
+                warn_decay_points.eq(decay_points),
+                warn_decay_period.eq(decay_period),
+            ))
+            .execute(conn)
+    }).await?;
+
+    Ok(())
+}
+
 async fn edit_default_log_channel(ctx: Context<'_>, interaction: ComponentInteraction, mod_table: ModerationSettings) -> Result<(), BotError> {
     let locales = ctx.data().localization_manager.clone();
     let lang = locales.get_guild_language(ctx.data().db.clone(), ctx.guild_id().unwrap()).await.unwrap();
@@ -264,19 +445,46 @@ async fn edit_log_types(ctx: Context<'_>, interaction: ComponentInteraction, mod
     let locales = ctx.data().localization_manager.clone();
     let lang = locales.get_guild_language(ctx.data().db.clone(), ctx.guild_id().unwrap()).await.unwrap();
 
-    let mut options: Vec<(String, String, bool)> = vec![];
+    let all_log_types: Vec<LogType> = LogType::iter().collect();
+    let pages: Vec<&[LogType]> = all_log_types.chunks(MAX_SELECT_OPTIONS).collect();
+
+    // Discord caps select menus at 25 options, with more log types than that a page is picked first
+    let (interaction, page) = if pages.len() > 1 {
+        let page_options = pages.iter().enumerate().map(|(index, page)| (
+            format!("{} – {}", page[0].to_string(&locales, lang), page[page.len() - 1].to_string(&locales, lang)),
+            index.to_string()
+        )).collect();
 
-    let active_log_types = get_active_log_types(mod_table.log_types as u32, &locales.clone(), lang);
+        interaction.create_response(ctx.http(), CreateInteractionResponse::UpdateMessage(
+            CreateInteractionResponseMessage::default()
+                .embed(CreateEmbed::new()
+                    .title(locales.get("commands.configuration.moderation.log_types.set.title", lang, &[]))
+                    .description(locales.get("commands.configuration.moderation.log_types.set.page_description", lang, &[]))
+                    .color(BotColors::Default.color()))
+                .components(vec![create_select_menu(
+                    "log_types_page",
+                    page_options,
+                    &locales.get("commands.configuration.moderation.log_types.set.page_placeholder", lang, &[]),
+                    CreateSelectMenuKind::String { options: Default::default() }
+                )])
+        )).await?;
 
-    for log_type in LogType::iter() {
-        let log_type_string = log_type.to_string(&locales.clone(), lang);
+        let Some(interaction) = await_interaction(&ctx, &interaction.message, "log_types_page").await else {
+            return Ok(());
+        };
 
-        options.push((
-            log_type_string.clone(),
-            log_type_string.clone(),
-            active_log_types.contains(&log_type_string)
-        ));
-    }
+        let page = get_selected_value(&interaction)?.parse::<usize>().unwrap_or(0).min(pages.len() - 1);
+        (interaction, page)
+    } else {
+        (interaction, 0)
+    };
+
+    let page_log_types = pages[page];
+
+    let options: Vec<(String, String, bool)> = page_log_types.iter().map(|log_type| {
+        let log_type_string = log_type.to_string(&locales, lang);
+        (log_type_string.clone(), log_type_string, mod_table.log_types as u32 & log_type.as_bit() != 0)
+    }).collect();
 
     let length = options.len() as u8;
 
@@ -298,8 +506,10 @@ async fn edit_log_types(ctx: Context<'_>, interaction: ComponentInteraction, mod
     if let Some(interaction) = await_interaction(&ctx, &interaction.message, "log_types").await {
         let selected_log_types = get_selected_values(&interaction)?;
 
-        let mut log_types = 0u32;
-        for log_type in selected_log_types.clone() {
+        // Only the shown page is replaced, log types on the other pages keep their state
+        let page_mask = page_log_types.iter().fold(0u32, |mask, log_type| mask | log_type.as_bit());
+        let mut log_types = mod_table.log_types as u32 & !page_mask;
+        for log_type in selected_log_types {
             if let Some(log_type_enum) = string_to_log_type(&log_type, &locales.clone(), lang) {
                 log_types |= log_type_enum.as_bit();
             }
@@ -312,7 +522,7 @@ async fn edit_log_types(ctx: Context<'_>, interaction: ComponentInteraction, mod
                 .embed(CreateEmbed::new()
                     .title(locales.get("commands.configuration.moderation.log_types.done.title", lang, &[]))
                     .description(locales.get("commands.configuration.moderation.log_types.done.description", lang, &[
-                        TranslationParam::from(selected_log_types.join(", "))
+                        TranslationParam::from(get_active_log_types(log_types, &locales, lang).join(", "))
                     ]))
                     .color(BotColors::Default.color())
                 )
@@ -333,4 +543,886 @@ async fn update_log_types(ctx: &Context<'_>, types: i32) -> Result<(), BotError>
     }).await?;
 
     Ok(())
-}
\ No newline at end of file
+}
+
+fn format_lockdown_channels(channels: &[i64], lang: Language, locales: &LocalizationManager) -> String {
+    if channels.is_empty() {
+        locales.get("commands.configuration.moderation.lockdown_channels.all", lang, &[])
+    } else {
+        channels.iter().map(|id| format!("<#{}>", id)).collect::<Vec<_>>().join(", ")
+    }
+}
+
+async fn edit_lockdown_channels(ctx: Context<'_>, interaction: ComponentInteraction, mod_table: ModerationSettings) -> Result<(), BotError> {
+    let locales = ctx.data().localization_manager.clone();
+    let lang = locales.get_guild_language(ctx.data().db.clone(), ctx.guild_id().unwrap()).await.unwrap();
+
+    let default_channels = if mod_table.lockdown_channels.is_empty() {
+        None
+    } else {
+        Some(Cow::Owned(mod_table.lockdown_channels.iter().map(|id| ChannelId::new(*id as u64)).collect::<Vec<_>>()))
+    };
+
+    interaction.create_response(ctx.http(), CreateInteractionResponse::UpdateMessage(
+        CreateInteractionResponseMessage::default()
+            .embed(CreateEmbed::new()
+                .title(locales.get("commands.configuration.moderation.lockdown_channels.set.title", lang, &[]))
+                .description(locales.get("commands.configuration.moderation.lockdown_channels.set.description", lang, &[])).color(BotColors::Default.color()))
+            .components(vec![create_select_menu_with_default(
+                "lockdown_channels",
+                vec![],
+                &locales.get("commands.configuration.moderation.lockdown_channels.set.placeholder", lang, &[]),
+                CreateSelectMenuKind::Channel {
+                    channel_types: Some(Cow::from(vec![ChannelType::Text])),
+                    default_channels
+                }, Some(25)
+            )])
+    )).await?;
+    if let Some(interaction) = await_interaction(&ctx, &interaction.message, "lockdown_channels").await {
+        let selected_channels: Vec<i64> = get_selected_values(&interaction)?
+            .iter()
+            .map(|id| id.parse::<i64>().unwrap())
+            .collect();
+
+        update_lockdown_channels(&ctx, selected_channels.clone()).await?;
+
+        interaction.create_response(ctx.http(), CreateInteractionResponse::UpdateMessage(
+            CreateInteractionResponseMessage::default()
+                .embed(CreateEmbed::new()
+                    .title(locales.get("commands.configuration.moderation.lockdown_channels.done.title", lang, &[]))
+                    .description(locales.get("commands.configuration.moderation.lockdown_channels.done.description", lang, &[
+                        TranslationParam::from(format_lockdown_channels(&selected_channels, lang, &locales))
+                    ]))
+                    .color(BotColors::Default.color())
+                )
+                .components(vec![])
+        )).await?;
+    }
+
+    Ok(())
+}
+
+async fn update_lockdown_channels(ctx: &Context<'_>, channels: Vec<i64>) -> Result<(), BotError> {
+    use crate::database::schema::moderation_settings::dsl::*;
+
+    ctx.data().db.run(move |conn| {
+        diesel::update(moderation_settings.filter(guild_id.eq(ctx.guild_id().unwrap().get() as i64)))
+            .set(lockdown_channels.eq(channels))
+            .execute(conn)
+    }).await?;
+
+    Ok(())
+}
+
+async fn edit_auto_dehoist(ctx: Context<'_>, interaction: ComponentInteraction, mod_table: ModerationSettings) -> Result<(), BotError> {
+    let locales = ctx.data().localization_manager.clone();
+    let lang = locales.get_guild_language(ctx.data().db.clone(), ctx.guild_id().unwrap()).await.unwrap();
+
+    interaction.create_response(ctx.http(), CreateInteractionResponse::UpdateMessage(
+        CreateInteractionResponseMessage::default()
+            .embed(CreateEmbed::new()
+                .title(locales.get("commands.configuration.moderation.auto_dehoist.set.title", lang, &[]))
+                .description(locales.get("commands.configuration.moderation.auto_dehoist.set.description", lang, &[])).color(BotColors::Default.color()))
+            .components(vec![create_select_menu_with_default(
+                "auto_dehoist",
+                vec![
+                    (locales.get("commands.configuration.config.enabled", lang, &[]), "enabled".to_string(), mod_table.auto_dehoist),
+                    (locales.get("commands.configuration.config.disabled", lang, &[]), "disabled".to_string(), !mod_table.auto_dehoist),
+                ],
+                &locales.get("commands.configuration.moderation.auto_dehoist.set.placeholder", lang, &[]),
+                CreateSelectMenuKind::String { options: Default::default() }, None
+            )])
+    )).await?;
+    if let Some(interaction) = await_interaction(&ctx, &interaction.message, "auto_dehoist").await {
+        let enabled = get_selected_value(&interaction)? == "enabled";
+
+        update_auto_dehoist(&ctx, enabled).await?;
+
+        interaction.create_response(ctx.http(), CreateInteractionResponse::UpdateMessage(
+            CreateInteractionResponseMessage::default()
+                .embed(CreateEmbed::new()
+                    .title(locales.get("commands.configuration.moderation.auto_dehoist.done.title", lang, &[]))
+                    .description(locales.get(if enabled {
+                        "commands.configuration.moderation.auto_dehoist.done.enabled"
+                    } else {
+                        "commands.configuration.moderation.auto_dehoist.done.disabled"
+                    }, lang, &[]))
+                    .color(BotColors::Default.color())
+                )
+                .components(vec![])
+        )).await?;
+    }
+
+    Ok(())
+}
+
+async fn update_auto_dehoist(ctx: &Context<'_>, enabled: bool) -> Result<(), BotError> {
+    use crate::database::schema::moderation_settings::dsl::*;
+
+    ctx.data().db.run(move |conn| {
+        diesel::update(moderation_settings.filter(guild_id.eq(ctx.guild_id().unwrap().get() as i64)))
+            .set(auto_dehoist.eq(enabled))
+            .execute(conn)
+    }).await?;
+
+    Ok(())
+}
+
+async fn edit_nickname_patterns(ctx: Context<'_>, interaction: ComponentInteraction) -> Result<(), BotError> {
+    let locales = ctx.data().localization_manager.clone();
+    let lang = locales.get_guild_language(ctx.data().db.clone(), ctx.guild_id().unwrap()).await.unwrap();
+
+    let input_text = CreateInputText::new(InputTextStyle::Paragraph, locales.get("commands.configuration.moderation.nickname_patterns.set.label", lang, &[]), "patterns")
+        .placeholder(locales.get("commands.configuration.moderation.nickname_patterns.set.placeholder", lang, &[]))
+        .required(false);
+
+    let modal = CreateModal::new("nickname_patterns_modal", locales.get("commands.configuration.moderation.nickname_patterns.set.title", lang, &[]))
+        .components(vec![CreateActionRow::InputText(input_text)]);
+
+    interaction.create_response(ctx.http(), Modal(modal)).await?;
+
+    if let Some(interaction) = await_modal_interaction(&ctx, &interaction.message, "nickname_patterns_modal").await {
+        let patterns: Vec<String> = get_modal_value(&interaction)?
+            .lines()
+            .map(|line| line.trim().to_string())
+            .filter(|line| !line.is_empty())
+            .collect();
+
+        if let Some(invalid) = patterns.iter().find(|p| Regex::new(p).is_err()) {
+            interaction.create_response(ctx.http(), CreateInteractionResponse::Message(
+                CreateInteractionResponseMessage::default()
+                    .content(locales.get("commands.configuration.moderation.nickname_patterns.set.error_invalid", lang, &[
+                        TranslationParam::from(invalid)
+                    ]))
+            )).await?;
+            return Ok(());
+        }
+
+        update_nickname_patterns(&ctx, patterns.clone()).await?;
+
+        interaction.create_response(ctx.http(), CreateInteractionResponse::UpdateMessage(
+            CreateInteractionResponseMessage::default()
+                .embed(CreateEmbed::new()
+                    .title(locales.get("commands.configuration.moderation.nickname_patterns.done.title", lang, &[]))
+                    .description(locales.get("commands.configuration.moderation.nickname_patterns.done.description", lang, &[
+                        TranslationParam::from(patterns.len().to_string())
+                    ]))
+                    .color(BotColors::Default.color())
+                )
+                .components(vec![])
+        )).await?;
+    }
+
+    Ok(())
+}
+
+async fn update_nickname_patterns(ctx: &Context<'_>, patterns: Vec<String>) -> Result<(), BotError> {
+    use crate::database::schema::moderation_settings::dsl::*;
+
+    ctx.data().db.run(move |conn| {
+        diesel::update(moderation_settings.filter(guild_id.eq(ctx.guild_id().unwrap().get() as i64)))
+            .set(nickname_patterns.eq(patterns))
+            .execute(conn)
+    }).await?;
+
+    Ok(())
+}
+
+async fn edit_nickname_template(ctx: Context<'_>, interaction: ComponentInteraction) -> Result<(), BotError> {
+    let locales = ctx.data().localization_manager.clone();
+    let lang = locales.get_guild_language(ctx.data().db.clone(), ctx.guild_id().unwrap()).await.unwrap();
+
+    let input_text = CreateInputText::new(InputTextStyle::Short, locales.get("commands.configuration.moderation.nickname_template.set.label", lang, &[]), "template")
+        .placeholder(DEFAULT_NICKNAME_TEMPLATE)
+        .min_length(1)
+        .max_length(32);
+
+    let modal = CreateModal::new("nickname_template_modal", locales.get("commands.configuration.moderation.nickname_template.set.title", lang, &[]))
+        .components(vec![CreateActionRow::InputText(input_text)]);
+
+    interaction.create_response(ctx.http(), Modal(modal)).await?;
+
+    if let Some(interaction) = await_modal_interaction(&ctx, &interaction.message, "nickname_template_modal").await {
+        let template = get_modal_value(&interaction)?;
+
+        update_nickname_template(&ctx, template.clone()).await?;
+
+        interaction.create_response(ctx.http(), CreateInteractionResponse::UpdateMessage(
+            CreateInteractionResponseMessage::default()
+                .embed(CreateEmbed::new()
+                    .title(locales.get("commands.configuration.moderation.nickname_template.done.title", lang, &[]))
+                    .description(locales.get("commands.configuration.moderation.nickname_template.done.description", lang, &[
+                        TranslationParam::from(template)
+                    ]))
+                    .color(BotColors::Default.color())
+                )
+                .components(vec![])
+        )).await?;
+    }
+
+    Ok(())
+}
+
+async fn update_nickname_template(ctx: &Context<'_>, template: String) -> Result<(), BotError> {
+    use crate::database::schema::moderation_settings::dsl::*;
+
+    ctx.data().db.run(move |conn| {
+        diesel::update(moderation_settings.filter(guild_id.eq(ctx.guild_id().unwrap().get() as i64)))
+            .set(nickname_template.eq(template))
+            .execute(conn)
+    }).await?;
+
+    Ok(())
+}
+
+async fn edit_quarantine(ctx: Context<'_>, interaction: ComponentInteraction, mod_table: ModerationSettings) -> Result<(), BotError> {
+    let locales = ctx.data().localization_manager.clone();
+    let lang = locales.get_guild_language(ctx.data().db.clone(), ctx.guild_id().unwrap()).await.unwrap();
+
+    interaction.create_response(ctx.http(), CreateInteractionResponse::UpdateMessage(
+        CreateInteractionResponseMessage::default()
+            .embed(CreateEmbed::new()
+                .title(locales.get("commands.configuration.moderation.quarantine.set.title", lang, &[]))
+                .description(locales.get("commands.configuration.moderation.quarantine.set.role_description", lang, &[])).color(BotColors::Default.color()))
+            .components(vec![create_select_menu_with_default(
+                "quarantine_role",
+                vec![],
+                &locales.get("commands.configuration.moderation.quarantine.set.role_placeholder", lang, &[]),
+                CreateSelectMenuKind::Role {
+                    default_roles: mod_table.quarantine_role.map(|id| Cow::Owned(vec![RoleId::new(id as u64)]))
+                }, None
+            )])
+    )).await?;
+
+    let Some(interaction) = await_interaction(&ctx, &interaction.message, "quarantine_role").await else {
+        return Ok(());
+    };
+
+    let role = get_selected_value(&interaction)?.parse::<i64>().unwrap();
+
+    interaction.create_response(ctx.http(), CreateInteractionResponse::UpdateMessage(
+        CreateInteractionResponseMessage::default()
+            .embed(CreateEmbed::new()
+                .title(locales.get("commands.configuration.moderation.quarantine.set.title", lang, &[]))
+                .description(locales.get("commands.configuration.moderation.quarantine.set.channel_description", lang, &[])).color(BotColors::Default.color()))
+            .components(vec![create_select_menu_with_default(
+                "quarantine_channel",
+                vec![],
+                &locales.get("commands.configuration.moderation.quarantine.set.channel_placeholder", lang, &[]),
+                CreateSelectMenuKind::Channel {
+                    channel_types: Some(Cow::from(vec![ChannelType::Text])),
+                    default_channels: mod_table.quarantine_channel.map(|id| Cow::Owned(vec![ChannelId::new(id as u64)]))
+                }, None
+            )])
+    )).await?;
+
+    if let Some(interaction) = await_interaction(&ctx, &interaction.message, "quarantine_channel").await {
+        let channel = get_selected_value(&interaction)?.parse::<i64>().unwrap();
+
+        update_quarantine(&ctx, role, channel).await?;
+
+        interaction.create_response(ctx.http(), CreateInteractionResponse::UpdateMessage(
+            CreateInteractionResponseMessage::default()
+                .embed(CreateEmbed::new()
+                    .title(locales.get("commands.configuration.moderation.quarantine.done.title", lang, &[]))
+                    .description(locales.get("commands.configuration.moderation.quarantine.done.description", lang, &[
+                        TranslationParam::from(format!("<@&{}>", role)),
+                        TranslationParam::from(format!("<#{}>", channel)),
+                    ]))
+                    .color(BotColors::Default.color())
+                )
+                .components(vec![])
+        )).await?;
+
+        // Editing every channel can take a while, so it happens after answering the interaction
+        apply_quarantine_overwrites(ctx.serenity_context(), ctx.guild_id().unwrap(), RoleId::new(role as u64), ChannelId::new(channel as u64)).await?;
+    }
+
+    Ok(())
+}
+
+async fn update_quarantine(ctx: &Context<'_>, role: i64, channel: i64) -> Result<(), BotError> {
+    use crate::database::schema::moderation_settings::dsl::*;
+
+    ctx.data().db.run(move |conn| {
+        diesel::update(moderation_settings.filter(guild_id.eq(ctx.guild_id().unwrap().get() as i64)))
+            .set((quarantine_role.eq(Some(role)), quarantine_channel.eq(Some(channel))))
+            .execute(conn)
+    }).await?;
+
+    Ok(())
+}
+
+async fn edit_quarantine_automod_rules(ctx: Context<'_>, interaction: ComponentInteraction, mod_table: ModerationSettings) -> Result<(), BotError> {
+    let locales = ctx.data().localization_manager.clone();
+    let lang = locales.get_guild_language(ctx.data().db.clone(), ctx.guild_id().unwrap()).await.unwrap();
+
+    let rules = ctx.guild_id().unwrap().automod_rules(ctx.http()).await?;
+
+    let mut options: Vec<(String, String, bool)> = vec![
+        (locales.get("commands.configuration.config.none", lang, &[]), "none".to_string(), mod_table.quarantine_automod_rules.is_empty()),
+    ];
+
+    options.extend(rules.iter().take(24).map(|rule| (
+        rule.name.to_string(),
+        rule.id.to_string(),
+        mod_table.quarantine_automod_rules.contains(&(rule.id.get() as i64))
+    )));
+
+    let max_values = options.len() as u8;
+
+    interaction.create_response(ctx.http(), CreateInteractionResponse::UpdateMessage(
+        CreateInteractionResponseMessage::default()
+            .embed(CreateEmbed::new()
+                .title(locales.get("commands.configuration.moderation.quarantine_automod_rules.set.title", lang, &[]))
+                .description(locales.get("commands.configuration.moderation.quarantine_automod_rules.set.description", lang, &[])).color(BotColors::Default.color()))
+            .components(vec![create_select_menu_with_default(
+                "quarantine_automod_rules",
+                options,
+                &locales.get("commands.configuration.moderation.quarantine_automod_rules.set.placeholder", lang, &[]),
+                CreateSelectMenuKind::String { options: Default::default() }, Some(max_values)
+            )])
+    )).await?;
+
+    if let Some(interaction) = await_interaction(&ctx, &interaction.message, "quarantine_automod_rules").await {
+        let selected = get_selected_values(&interaction)?;
+
+        let selected_rules: Vec<i64> = if selected.iter().any(|value| value == "none") {
+            Vec::new()
+        } else {
+            selected.iter().map(|id| id.parse::<i64>().unwrap()).collect()
+        };
+
+        update_quarantine_automod_rules(&ctx, selected_rules.clone()).await?;
+
+        interaction.create_response(ctx.http(), CreateInteractionResponse::UpdateMessage(
+            CreateInteractionResponseMessage::default()
+                .embed(CreateEmbed::new()
+                    .title(locales.get("commands.configuration.moderation.quarantine_automod_rules.done.title", lang, &[]))
+                    .description(locales.get("commands.configuration.moderation.quarantine_automod_rules.done.description", lang, &[
+                        TranslationParam::from(selected_rules.len().to_string())
+                    ]))
+                    .color(BotColors::Default.color())
+                )
+                .components(vec![])
+        )).await?;
+    }
+
+    Ok(())
+}
+
+async fn update_quarantine_automod_rules(ctx: &Context<'_>, rules: Vec<i64>) -> Result<(), BotError> {
+    use crate::database::schema::moderation_settings::dsl::*;
+
+    ctx.data().db.run(move |conn| {
+        diesel::update(moderation_settings.filter(guild_id.eq(ctx.guild_id().unwrap().get() as i64)))
+            .set(quarantine_automod_rules.eq(rules))
+            .execute(conn)
+    }).await?;
+
+    Ok(())
+}
+
+fn format_reason_presets(presets: &[ReasonPresets], lang: Language, locales: &LocalizationManager) -> String {
+    if presets.is_empty() {
+        return locales.get("commands.configuration.config.none", lang, &[]);
+    }
+
+    presets.iter().map(|preset| {
+        let mut line = format!("• {}", preset.reason);
+        if let Some(preset_points) = preset.points {
+            line += &format!(" — {} pts", preset_points);
+        }
+        if let Some(ref preset_duration) = preset.duration {
+            line += &format!(" — {}", preset_duration);
+        }
+        line
+    }).collect::<Vec<_>>().join("\n")
+}
+
+async fn edit_reason_presets(ctx: Context<'_>, interaction: ComponentInteraction) -> Result<(), BotError> {
+    let locales = ctx.data().localization_manager.clone();
+    let lang = locales.get_guild_language(ctx.data().db.clone(), ctx.guild_id().unwrap()).await.unwrap();
+
+    let presets = fetch_presets(&ctx.data(), ctx.guild_id().unwrap()).await;
+
+    interaction.create_response(ctx.http(), CreateInteractionResponse::UpdateMessage(
+        CreateInteractionResponseMessage::default()
+            .embed(CreateEmbed::new()
+                .title(locales.get("commands.configuration.moderation.reason_presets.set.title", lang, &[]))
+                .description(format_reason_presets(&presets, lang, &locales)).color(BotColors::Default.color()))
+            .components(vec![create_select_menu("reason_presets_action", vec![
+                (locales.get("commands.configuration.moderation.reason_presets.set.add", lang, &[]), "add".to_string()),
+                (locales.get("commands.configuration.moderation.reason_presets.set.remove", lang, &[]), "remove".to_string()),
+            ], &locales.get("commands.configuration.config.placeholder.option", lang, &[]), CreateSelectMenuKind::String { options: Default::default() })])
+    )).await?;
+
+    let Some(interaction) = await_interaction(&ctx, &interaction.message, "reason_presets_action").await else {
+        return Ok(());
+    };
+
+    if get_selected_value(&interaction)? == "add" {
+        add_reason_preset(ctx, interaction).await
+    } else {
+        remove_reason_presets(ctx, interaction, presets).await
+    }
+}
+
+async fn add_reason_preset(ctx: Context<'_>, interaction: ComponentInteraction) -> Result<(), BotError> {
+    let locales = ctx.data().localization_manager.clone();
+    let lang = locales.get_guild_language(ctx.data().db.clone(), ctx.guild_id().unwrap()).await.unwrap();
+
+    let modal = CreateModal::new("reason_preset_modal", locales.get("commands.configuration.moderation.reason_presets.add.title", lang, &[]))
+        .components(vec![
+            CreateActionRow::InputText(CreateInputText::new(InputTextStyle::Short, locales.get("commands.configuration.moderation.reason_presets.add.reason", lang, &[]), "reason")
+                .max_length(MAX_PRESET_LENGTH as u16)
+                .required(true)),
+            CreateActionRow::InputText(CreateInputText::new(InputTextStyle::Short, locales.get("commands.configuration.moderation.reason_presets.add.points", lang, &[]), "points")
+                .placeholder("1")
+                .max_length(3)
+                .required(false)),
+            CreateActionRow::InputText(CreateInputText::new(InputTextStyle::Short, locales.get("commands.configuration.moderation.reason_presets.add.duration", lang, &[]), "duration")
+                .placeholder("7d")
+                .max_length(32)
+                .required(false)),
+        ]);
+
+    interaction.create_response(ctx.http(), Modal(modal)).await?;
+
+    let Some(interaction) = await_modal_interaction(&ctx, &interaction.message, "reason_preset_modal").await else {
+        return Ok(());
+    };
+
+    let values = get_modal_values(&interaction)?;
+    let preset_reason = values[0].trim().to_string();
+    let preset_points = values[1].trim();
+    let preset_duration = values[2].trim();
+
+    let preset_points = if preset_points.is_empty() {
+        None
+    } else {
+        match preset_points.parse::<i32>() {
+            Ok(p) if (1..=100).contains(&p) => Some(p),
+            _ => {
+                interaction.create_response(ctx.http(), CreateInteractionResponse::Message(
+                    CreateInteractionResponseMessage::default()
+                        .content(locales.get("commands.configuration.moderation.reason_presets.add.error_invalid_points", lang, &[]))
+                )).await?;
+                return Ok(());
+            }
+        }
+    };
+
+    let preset_duration = if preset_duration.is_empty() {
+        None
+    } else if let Err(why) = parse_duration(preset_duration, lang) {
+        interaction.create_response(ctx.http(), CreateInteractionResponse::Message(
+            CreateInteractionResponseMessage::default()
+                .content(why.localize(lang, &locales))
+        )).await?;
+        return Ok(());
+    } else {
+        Some(preset_duration.to_string())
+    };
+
+    insert_reason_preset(&ctx, ReasonPresets {
+        guild_id: ctx.guild_id().unwrap().get() as i64,
+        reason: preset_reason.clone(),
+        points: preset_points,
+        duration: preset_duration,
+    }).await?;
+
+    interaction.create_response(ctx.http(), CreateInteractionResponse::UpdateMessage(
+        CreateInteractionResponseMessage::default()
+            .embed(CreateEmbed::new()
+                .title(locales.get("commands.configuration.moderation.reason_presets.done.title", lang, &[]))
+                .description(locales.get("commands.configuration.moderation.reason_presets.done.added", lang, &[
+                    TranslationParam::from(preset_reason)
+                ]))
+                .color(BotColors::Default.color())
+            )
+            .components(vec![])
+    )).await?;
+
+    Ok(())
+}
+
+async fn remove_reason_presets(ctx: Context<'_>, interaction: ComponentInteraction, presets: Vec<ReasonPresets>) -> Result<(), BotError> {
+    let locales = ctx.data().localization_manager.clone();
+    let lang = locales.get_guild_language(ctx.data().db.clone(), ctx.guild_id().unwrap()).await.unwrap();
+
+    if presets.is_empty() {
+        interaction.create_response(ctx.http(), CreateInteractionResponse::UpdateMessage(
+            CreateInteractionResponseMessage::default()
+                .content(locales.get("commands.configuration.moderation.reason_presets.remove.error_empty", lang, &[]))
+                .components(vec![])
+        )).await?;
+        return Ok(());
+    }
+
+    let options: Vec<(String, String, bool)> = presets.iter()
+        .take(25)
+        .map(|preset| (preset.reason.clone(), preset.reason.clone(), false))
+        .collect();
+
+    let max_values = options.len() as u8;
+
+    interaction.create_response(ctx.http(), CreateInteractionResponse::UpdateMessage(
+        CreateInteractionResponseMessage::default()
+            .embed(CreateEmbed::new()
+                .title(locales.get("commands.configuration.moderation.reason_presets.remove.title", lang, &[]))
+                .description(locales.get("commands.configuration.moderation.reason_presets.remove.description", lang, &[])).color(BotColors::Default.color()))
+            .components(vec![create_select_menu_with_default(
+                "reason_presets_remove",
+                options,
+                &locales.get("commands.configuration.moderation.reason_presets.remove.placeholder", lang, &[]),
+                CreateSelectMenuKind::String { options: Default::default() }, Some(max_values)
+            )])
+    )).await?;
+
+    if let Some(interaction) = await_interaction(&ctx, &interaction.message, "reason_presets_remove").await {
+        let selected = get_selected_values(&interaction)?;
+
+        delete_reason_presets(&ctx, selected.clone()).await?;
+
+        interaction.create_response(ctx.http(), CreateInteractionResponse::UpdateMessage(
+            CreateInteractionResponseMessage::default()
+                .embed(CreateEmbed::new()
+                    .title(locales.get("commands.configuration.moderation.reason_presets.done.title", lang, &[]))
+                    .description(locales.get("commands.configuration.moderation.reason_presets.done.removed", lang, &[
+                        TranslationParam::from(selected.len().to_string())
+                    ]))
+                    .color(BotColors::Default.color())
+                )
+                .components(vec![])
+        )).await?;
+    }
+
+    Ok(())
+}
+
+async fn insert_reason_preset(ctx: &Context<'_>, preset: ReasonPresets) -> Result<(), BotError> {
+    use crate::database::schema::reason_presets::dsl::*;
+
+    ctx.data().db.run(move |conn| {
+        diesel::insert_into(reason_presets)
+            .values(preset)
+            .execute(conn)
+    }).await?;
+
+    Ok(())
+}
+
+async fn delete_reason_presets(ctx: &Context<'_>, reasons: Vec<String>) -> Result<(), BotError> {
+    use crate::database::schema::reason_presets::dsl::*;
+
+    ctx.data().db.run(move |conn| {
+        diesel::delete(reason_presets
+            .filter(guild_id.eq(ctx.guild_id().unwrap().get() as i64))
+            .filter(reason.eq_any(reasons))
+        ).execute(conn)
+    }).await?;
+
+    Ok(())
+}
+
+fn format_case_types(custom_types: &[CaseTypes], lang: Language, locales: &LocalizationManager) -> String {
+    if custom_types.is_empty() {
+        return locales.get("commands.configuration.config.none", lang, &[]);
+    }
+
+    custom_types.iter().map(|case_type| {
+        let mut line = format!("• **{}** (`{}`)", case_type.display_name, case_type.name);
+        if let Some(case_points) = case_type.points {
+            line += &format!(" — {} pts", case_points);
+            if !case_type.counts_points {
+                line += &format!(" ({})", locales.get("commands.configuration.moderation.case_types.not_counted", lang, &[]));
+            }
+        }
+        if let Some(ref case_duration) = case_type.duration {
+            line += &format!(" — {}", case_duration);
+        }
+        line
+    }).collect::<Vec<_>>().join("\n")
+}
+
+async fn edit_case_types(ctx: Context<'_>, interaction: ComponentInteraction) -> Result<(), BotError> {
+    let locales = ctx.data().localization_manager.clone();
+    let lang = locales.get_guild_language(ctx.data().db.clone(), ctx.guild_id().unwrap()).await.unwrap();
+
+    let custom_types = fetch_case_types(&ctx.data(), ctx.guild_id().unwrap()).await;
+
+    interaction.create_response(ctx.http(), CreateInteractionResponse::UpdateMessage(
+        CreateInteractionResponseMessage::default()
+            .embed(CreateEmbed::new()
+                .title(locales.get("commands.configuration.moderation.case_types.set.title", lang, &[]))
+                .description(format_case_types(&custom_types, lang, &locales)).color(BotColors::Default.color()))
+            .components(vec![create_select_menu("case_types_action", vec![
+                (locales.get("commands.configuration.moderation.case_types.set.add", lang, &[]), "add".to_string()),
+                (locales.get("commands.configuration.moderation.case_types.set.remove", lang, &[]), "remove".to_string()),
+            ], &locales.get("commands.configuration.config.placeholder.option", lang, &[]), CreateSelectMenuKind::String { options: Default::default() })])
+    )).await?;
+
+    let Some(interaction) = await_interaction(&ctx, &interaction.message, "case_types_action").await else {
+        return Ok(());
+    };
+
+    if get_selected_value(&interaction)? == "add" {
+        add_case_type(ctx, interaction).await
+    } else {
+        remove_case_types(ctx, interaction, custom_types).await
+    }
+}
+
+async fn add_case_type(ctx: Context<'_>, interaction: ComponentInteraction) -> Result<(), BotError> {
+    let locales = ctx.data().localization_manager.clone();
+    let lang = locales.get_guild_language(ctx.data().db.clone(), ctx.guild_id().unwrap()).await.unwrap();
+
+    let modal = CreateModal::new("case_type_modal", locales.get("commands.configuration.moderation.case_types.add.title", lang, &[]))
+        .components(vec![
+            CreateActionRow::InputText(CreateInputText::new(InputTextStyle::Short, locales.get("commands.configuration.moderation.case_types.add.name", lang, &[]), "name")
+                .placeholder("Verbal warning")
+                .max_length(100)
+                .required(true)),
+            CreateActionRow::InputText(CreateInputText::new(InputTextStyle::Short, locales.get("commands.configuration.moderation.case_types.add.points", lang, &[]), "points")
+                .placeholder("0")
+                .max_length(3)
+                .required(false)),
+            CreateActionRow::InputText(CreateInputText::new(InputTextStyle::Short, locales.get("commands.configuration.moderation.case_types.add.duration", lang, &[]), "duration")
+                .placeholder("30d")
+                .max_length(32)
+                .required(false)),
+            CreateActionRow::InputText(CreateInputText::new(InputTextStyle::Short, locales.get("commands.configuration.moderation.case_types.add.counts_points", lang, &[]), "counts_points")
+                .placeholder("yes / no")
+                .max_length(3)
+                .required(false)),
+        ]);
+
+    interaction.create_response(ctx.http(), Modal(modal)).await?;
+
+    let Some(interaction) = await_modal_interaction(&ctx, &interaction.message, "case_type_modal").await else {
+        return Ok(());
+    };
+
+    let values = get_modal_values(&interaction)?;
+    let type_display_name = values[0].trim().to_string();
+    let type_points = values[1].trim();
+    let type_duration = values[2].trim();
+    let type_counts_points = values[3].trim().to_lowercase();
+
+    let type_name = case_type_key(&type_display_name);
+
+    let error = |key: &str| locales.get(&format!("commands.configuration.moderation.case_types.add.{}", key), lang, &[]);
+
+    let type_points = if type_points.is_empty() {
+        Ok(None)
+    } else {
+        match type_points.parse::<i32>() {
+            Ok(p) if (0..=100).contains(&p) => Ok(Some(p)),
+            _ => Err(error("error_invalid_points")),
+        }
+    };
+
+    let type_duration = if type_duration.is_empty() {
+        Ok(None)
+    } else {
+        parse_duration(type_duration, lang)
+            .map(|_| Some(type_duration.to_string()))
+            .map_err(|why| why.localize(lang, &locales))
+    };
+
+    let type_counts_points = match type_counts_points.as_str() {
+        "" | "yes" | "y" | "tak" | "t" => Ok(true),
+        "no" | "n" | "nie" => Ok(false),
+        _ => Err(error("error_invalid_counts_points")),
+    };
+
+    let checked = if type_name.is_empty() {
+        Err(error("error_invalid_name"))
+    } else if BUILTIN_CASE_TYPES.contains(&type_name.as_str()) {
+        Err(error("error_builtin_name"))
+    } else {
+        type_points.and_then(|points| type_duration.and_then(|duration| type_counts_points.map(|counts_points| (points, duration, counts_points))))
+    };
+
+    let (type_points, type_duration, type_counts_points) = match checked {
+        Ok(checked) => checked,
+        Err(message) => {
+            interaction.create_response(ctx.http(), CreateInteractionResponse::Message(
+                CreateInteractionResponseMessage::default().content(message)
+            )).await?;
+            return Ok(());
+        }
+    };
+
+    insert_case_type(&ctx, CaseTypes {
+        guild_id: ctx.guild_id().unwrap().get() as i64,
+        name: type_name.clone(),
+        display_name: type_display_name.clone(),
+        points: type_points,
+        duration: type_duration,
+        counts_points: type_counts_points,
+    }).await?;
+
+    interaction.create_response(ctx.http(), CreateInteractionResponse::UpdateMessage(
+        CreateInteractionResponseMessage::default()
+            .embed(CreateEmbed::new()
+                .title(locales.get("commands.configuration.moderation.case_types.done.title", lang, &[]))
+                .description(locales.get("commands.configuration.moderation.case_types.done.added", lang, &[
+                    TranslationParam::from(type_display_name),
+                    TranslationParam::from(type_name)
+                ]))
+                .color(BotColors::Default.color())
+            )
+            .components(vec![])
+    )).await?;
+
+    Ok(())
+}
+
+async fn remove_case_types(ctx: Context<'_>, interaction: ComponentInteraction, custom_types: Vec<CaseTypes>) -> Result<(), BotError> {
+    let locales = ctx.data().localization_manager.clone();
+    let lang = locales.get_guild_language(ctx.data().db.clone(), ctx.guild_id().unwrap()).await.unwrap();
+
+    if custom_types.is_empty() {
+        interaction.create_response(ctx.http(), CreateInteractionResponse::UpdateMessage(
+            CreateInteractionResponseMessage::default()
+                .content(locales.get("commands.configuration.moderation.case_types.remove.error_empty", lang, &[]))
+                .components(vec![])
+        )).await?;
+        return Ok(());
+    }
+
+    let options: Vec<(String, String, bool)> = custom_types.iter()
+        .take(25)
+        .map(|case_type| (case_type.display_name.clone(), case_type.name.clone(), false))
+        .collect();
+
+    let max_values = options.len() as u8;
+
+    interaction.create_response(ctx.http(), CreateInteractionResponse::UpdateMessage(
+        CreateInteractionResponseMessage::default()
+            .embed(CreateEmbed::new()
+                .title(locales.get("commands.configuration.moderation.case_types.remove.title", lang, &[]))
+                .description(locales.get("commands.configuration.moderation.case_types.remove.description", lang, &[])).color(BotColors::Default.color()))
+            .components(vec![create_select_menu_with_default(
+                "case_types_remove",
+                options,
+                &locales.get("commands.configuration.moderation.case_types.remove.placeholder", lang, &[]),
+                CreateSelectMenuKind::String { options: Default::default() }, Some(max_values)
+            )])
+    )).await?;
+
+    if let Some(interaction) = await_interaction(&ctx, &interaction.message, "case_types_remove").await {
+        let selected = get_selected_values(&interaction)?;
+
+        delete_case_types(&ctx, selected.clone()).await?;
+
+        interaction.create_response(ctx.http(), CreateInteractionResponse::UpdateMessage(
+            CreateInteractionResponseMessage::default()
+                .embed(CreateEmbed::new()
+                    .title(locales.get("commands.configuration.moderation.case_types.done.title", lang, &[]))
+                    .description(locales.get("commands.configuration.moderation.case_types.done.removed", lang, &[
+                        TranslationParam::from(selected.len().to_string())
+                    ]))
+                    .color(BotColors::Default.color())
+                )
+                .components(vec![])
+        )).await?;
+    }
+
+    Ok(())
+}
+
+/// Redefining an existing name replaces its defaults, cases already opened with it keep theirs
+async fn insert_case_type(ctx: &Context<'_>, case_type: CaseTypes) -> Result<(), BotError> {
+    use crate::database::schema::case_types::dsl::*;
+
+    ctx.data().db.run(move |conn| {
+        diesel::insert_into(case_types)
+            .values(&case_type)
+            .on_conflict((guild_id, name))
+            .do_update()
+            .set((
+                display_name.eq(case_type.display_name.clone()),
+                points.eq(case_type.points),
+                duration.eq(case_type.duration.clone()),
+                counts_points.eq(case_type.counts_points),
+            ))
+            .execute(conn)
+    }).await?;
+
+    Ok(())
+}
+
+async fn delete_case_types(ctx: &Context<'_>, names: Vec<String>) -> Result<(), BotError> {
+    use crate::database::schema::case_types::dsl::*;
+
+    ctx.data().db.run(move |conn| {
+        diesel::delete(case_types
+            .filter(guild_id.eq(ctx.guild_id().unwrap().get() as i64))
+            .filter(name.eq_any(names))
+        ).execute(conn)
+    }).await?;
+
+    Ok(())
+}
+
+async fn edit_evidence_channel(ctx: Context<'_>, interaction: ComponentInteraction, mod_table: ModerationSettings) -> Result<(), BotError> {
+    let locales = ctx.data().localization_manager.clone();
+    let lang = locales.get_guild_language(ctx.data().db.clone(), ctx.guild_id().unwrap()).await.unwrap();
+
+    interaction.create_response(ctx.http(), CreateInteractionResponse::UpdateMessage(
+        CreateInteractionResponseMessage::default()
+            .embed(CreateEmbed::new()
+                .title(locales.get("commands.configuration.moderation.evidence_channel.set.title", lang, &[]))
+                .description(locales.get("commands.configuration.moderation.evidence_channel.set.description", lang, &[])).color(BotColors::Default.color()))
+            .components(vec![create_select_menu_with_default(
+                "evidence_channel",
+                vec![],
+                &locales.get("commands.configuration.moderation.evidence_channel.set.placeholder", lang, &[]),
+                CreateSelectMenuKind::Channel {
+                    channel_types: Some(Cow::from(vec![ChannelType::Text])),
+                    default_channels: mod_table.evidence_channel.map(|id| Cow::Owned(vec![ChannelId::new(id as u64)]))
+                }, None
+            )])
+    )).await?;
+
+    if let Some(interaction) = await_interaction(&ctx, &interaction.message, "evidence_channel").await {
+        let selected_channel = get_selected_value(&interaction)?;
+
+        let channel_id = if selected_channel == "none" {
+            None
+        } else {
+            Some(selected_channel.parse::<i64>().unwrap())
+        };
+
+        update_evidence_channel(&ctx, channel_id).await?;
+
+        interaction.create_response(ctx.http(), CreateInteractionResponse::UpdateMessage(
+            CreateInteractionResponseMessage::default()
+                .embed(CreateEmbed::new()
+                    .title(locales.get("commands.configuration.moderation.evidence_channel.done.title", lang, &[]))
+                    .description(locales.get("commands.configuration.moderation.evidence_channel.done.description", lang, &[
+                        TranslationParam::from(channel_id.map_or("None".to_string(), |id| format!("<#{}>", id)))
+                    ]))
+                    .color(BotColors::Default.color())
+                )
+                .components(vec![])
+        )).await?;
+    }
+
+    Ok(())
+}
+
+async fn update_evidence_channel(ctx: &Context<'_>, channel_id: Option<i64>) -> Result<(), BotError> {
+    use crate::database::schema::moderation_settings::dsl::*;
+
+    ctx.data().db.run(move |conn| {
+        diesel::update(moderation_settings.filter(guild_id.eq(ctx.guild_id().unwrap().get() as i64)))
+            .set(evidence_channel.eq(channel_id))
+            .execute(conn)
+    }).await?;
+
+    Ok(())
+}
@@ -3,13 +3,15 @@ use chrono::{DateTime, Utc};
 use diesel::associations::HasTable;
 use diesel_async::RunQueryDsl;
 use poise::{command, CreateReply, send_reply};
-use poise::serenity_prelude::{Member};
+use poise::serenity_prelude::{Attachment, Member};
 use crate::{BotError, Context};
 use crate::database::models::Cases;
 use crate::localization::manager::{TranslationParam};
+use crate::modules::moderation::evidence::{archive_evidence, is_guild_message_link};
 use crate::modules::moderation::logs::{log_action, LogData, LogType};
+use crate::modules::moderation::presets::{autocomplete_reason, find_preset};
 
-use crate::util::time::parse_to_time;
+use crate::util::time::{fetch_guild_timezone, format_duration, parse_duration_or_date};
 
 /// Ban a user
 #[command(slash_command, default_member_permissions="ADMINISTRATOR", guild_only)]
@@ -17,14 +19,19 @@ pub async fn ban(
     ctx: Context<'_>,
     #[description = "a user to ban"]
     user: Member,
-    #[description = "The ban duration (e.g. \"13d\"). Leave empty for permanent."]
+    #[description = "The ban duration (e.g. \"1d12h\") or end date (e.g. \"friday 18:00\"). Leave empty for permanent."]
     duration: Option<String>,
     #[description = "reason for banning the user"]
     #[rename = "reason"]
+    #[autocomplete = "autocomplete_reason"]
     action_reason: Option<String>,
     #[description = "number of days to delete messages from the user"]
     #[min = 1] #[max = 7]
-    delete_message_days: Option<u8>
+    delete_message_days: Option<u8>,
+    #[description = "Screenshot or file backing up the ban"]
+    evidence: Option<Attachment>,
+    #[description = "Link to a message backing up the ban"]
+    evidence_link: Option<String>
 ) -> Result<(), BotError> {
     use crate::database::schema::cases::dsl::*;
     let db = ctx.data().db.clone();
@@ -45,6 +52,11 @@ pub async fn ban(
         return Ok(());
     }
 
+    if evidence_link.as_deref().is_some_and(|link| !is_guild_message_link(link, ctx.guild_id().unwrap())) {
+        send_reply(ctx, CreateReply::new().content(locales.get("commands.moderation.evidence.error_invalid_link", guild_lang, &[])).ephemeral(true)).await?;
+        return Ok(());
+    }
+
     let guild = ctx.guild().unwrap().clone();
     if guild.owner_id == user.user.id {
         send_reply(ctx,
@@ -80,18 +92,28 @@ pub async fn ban(
         return Ok(());
     }
 
-    if parse_to_time(duration.clone().unwrap()).lt(&60.into()) {
+    let preset = find_preset(&ctx.data(), guild.id, action_reason.as_deref()).await;
+    let guild_tz = fetch_guild_timezone(&ctx.data(), ctx.guild_id().unwrap()).await;
+    let duration = match duration.or(preset.and_then(|preset| preset.duration)).map(|d| parse_duration_or_date(&d, guild_lang, guild_tz)) {
+        Some(Err(why)) => {
+            send_reply(ctx,
+                       CreateReply::new().content(why.localize(guild_lang, &locales)).ephemeral(true)
+            ).await?;
+            return Ok(());
+        },
+        Some(Ok(seconds)) => seconds,
+        None => None,
+    };
+
+    if duration.is_some_and(|seconds| seconds < 60) {
         send_reply(ctx,
                    CreateReply::new().content(locales.get("commands.moderation.ban.error_time_too_less", guild_lang, &[])).ephemeral(true)
         ).await?;
         return Ok(());
     }
     
-    let expires_at: Option<DateTime<Utc>> = duration.clone().map(|d| {
-        let duration = parse_to_time(d).unwrap();
-        #[warn(clippy::needless_return)]
-        return Utc::now() + chrono::Duration::seconds(duration as i64);
-    });
+    let expires_at: Option<DateTime<Utc>> = duration.map(|seconds| Utc::now() + chrono::Duration::seconds(seconds as i64));
+    let duration = duration.map(|seconds| format_duration(seconds, guild_lang));
     
     
     
@@ -114,8 +136,10 @@ pub async fn ban(
         case_type: "BAN".to_string(),
         reason: action_reason.clone().or(None),
         created_at: Utc::now(),
-        end_date: Some(expires_at.unwrap()).or(None),
-        points: None
+        end_date: expires_at,
+        points: None,
+        revoked_at: None,
+        revoke_reason: None
     };
 
     data.db.run(|conn| {
@@ -160,6 +184,8 @@ pub async fn ban(
     ).await?;
     
     let data = ctx.data().clone();
+
+    let evidence = archive_evidence(ctx.serenity_context(), &data, ctx.guild_id().unwrap(), new_case_id, evidence.as_ref(), evidence_link.as_deref()).await?;
     
     let log_data = LogData {
         ctx: Some(ctx.serenity_context()),
@@ -169,7 +195,9 @@ pub async fn ban(
         moderator_id: Some(ctx.author().id),
         reason: Some(action_reason),
         duration: duration.clone(),
+        ends_at: expires_at,
         case_id: Some(new_case_id),
+        evidence: Some(evidence),
         ..LogData::default()
     };
     
@@ -0,0 +1,119 @@
+use chrono::Utc;
+use poise::{command, CreateReply, send_reply};
+use poise::serenity_prelude::{CreateEmbed, Timestamp, User};
+use crate::{BotError, Context};
+use crate::database::models::Cases;
+use crate::localization::manager::TranslationParam;
+use crate::modules::moderation::case_types::{autocomplete_custom_case_type, find_case_type};
+use crate::modules::moderation::cases::{active_points, create_case};
+use crate::modules::moderation::logs::{log_action, LogData, LogType};
+use crate::modules::moderation::presets::autocomplete_reason;
+use crate::util::color::BotColors;
+use crate::util::time::{fetch_guild_timezone, format_duration, parse_duration_or_date};
+use crate::util::timestamp::{Format, TimestampExt};
+
+#[command(slash_command, default_member_permissions="MODERATE_MEMBERS", subcommands("create"), subcommand_required, guild_only)]
+pub async fn case(_: Context<'_>) -> Result<(), BotError> { Ok(()) }
+
+/// Open a case of one of the server's custom case types
+#[command(slash_command, default_member_permissions="MODERATE_MEMBERS", guild_only)]
+pub async fn create(
+    ctx: Context<'_>,
+    #[description = "The user the case is about"]
+    user: User,
+    #[description = "The case type"]
+    #[rename = "type"]
+    #[autocomplete = "autocomplete_custom_case_type"]
+    kind: String,
+    #[description = "Reason for the case"]
+    #[rename = "reason"]
+    #[autocomplete = "autocomplete_reason"]
+    action_reason: Option<String>,
+    #[description = "Points for the case. Defaults to the case type's points."]
+    #[min = 0] #[max = 100]
+    points: Option<i32>,
+    #[description = "When the case expires (e.g. \"30d\"). Defaults to the case type's expiry."]
+    duration: Option<String>
+) -> Result<(), BotError> {
+    let db = ctx.data().db.clone();
+    let locales = ctx.data().localization_manager.clone();
+    let guild_lang = locales
+        .get_guild_language(db, ctx.guild_id().unwrap()).await.unwrap();
+
+    let guild = ctx.guild_id().unwrap();
+    let data = ctx.data().clone();
+
+    let Some(case_type) = find_case_type(&data, guild, &kind).await else {
+        send_reply(ctx, CreateReply::new().content(locales.get("commands.moderation.case.error_unknown_type", guild_lang, &[])).ephemeral(true)).await?;
+        return Ok(());
+    };
+
+    let guild_tz = fetch_guild_timezone(&data, guild).await;
+
+    let seconds = match duration.or(case_type.duration.clone()).map(|d| parse_duration_or_date(&d, guild_lang, guild_tz)) {
+        Some(Ok(seconds)) => seconds,
+        Some(Err(why)) => {
+            send_reply(ctx, CreateReply::new().content(why.localize(guild_lang, &locales)).ephemeral(true)).await?;
+            return Ok(());
+        },
+        None => None,
+    };
+
+    let expires_at = seconds.map(|seconds| Utc::now() + chrono::Duration::seconds(seconds as i64));
+    let case_points = points.or(case_type.points).filter(|points| *points > 0);
+
+    let new_case_id = create_case(&data, Cases {
+        guild_id: guild.get() as i64,
+        user_id: user.id.get() as i64,
+        moderator_id: ctx.author().id.get() as i64,
+        case_id: 0,
+        case_type: case_type.name.clone(),
+        reason: action_reason.clone(),
+        created_at: Utc::now(),
+        end_date: expires_at,
+        points: case_points,
+        revoked_at: None,
+        revoke_reason: None
+    }).await?;
+
+    let mut embed = CreateEmbed::new()
+        .title(locales.get("commands.moderation.case.reply_title", guild_lang, &[
+            TranslationParam::from(case_type.display_name.clone()),
+            TranslationParam::from(new_case_id.to_string()),
+        ]))
+        .color(BotColors::Default.color())
+        .field(locales.get("commands.moderation.case.field_user", guild_lang, &[]), format!("<@{}>", user.id), true)
+        .field(locales.get("commands.moderation.case.field_moderator", guild_lang, &[]), format!("<@{}>", ctx.author().id), true);
+
+    if let Some(case_points) = case_points {
+        embed = embed.field(locales.get("commands.moderation.case.field_points", guild_lang, &[]), case_points.to_string(), true);
+
+        if case_type.counts_points {
+            let total_points = active_points(&data, guild, user.id).await?;
+            embed = embed.field(locales.get("commands.moderation.case.field_total", guild_lang, &[]), total_points.to_string(), true);
+        }
+    }
+
+    if let Some(expires_at) = expires_at {
+        embed = embed.field(locales.get("commands.moderation.case.field_expires", guild_lang, &[]), Timestamp::from(expires_at).to_discord_timestamp(Format::LongDateShortTime), true);
+    }
+
+    send_reply(ctx, CreateReply::new().embed(embed)).await?;
+
+    log_action(LogType::CaseCreate, LogData {
+        data: Some(&*data),
+        ctx: Some(ctx.serenity_context()),
+        guild_id: Some(guild.get()),
+        user_id: Some(user.id.get()),
+        moderator_id: Some(ctx.author().id),
+        reason: action_reason,
+        case_id: Some(new_case_id),
+        points: case_points,
+        duration: seconds.map(|seconds| format_duration(seconds, guild_lang)),
+        ends_at: expires_at,
+        message: Some(case_type.display_name),
+        ..LogData::default()
+    }).await?;
+
+    Ok(())
+}
@@ -1,19 +1,37 @@
 
-use std::collections::{BTreeMap, HashMap};
-use diesel::{ExpressionMethods, QueryDsl,  SelectableHelper};
+use std::collections::HashMap;
+use chrono::Utc;
+use diesel::{ExpressionMethods, PgTextExpressionMethods, QueryDsl,  SelectableHelper};
 use diesel_async::RunQueryDsl;
 use futures::future::join_all;
 use poise::CreateReply;
-use poise::serenity_prelude::{ButtonStyle,  CreateActionRow, CreateButton, CreateEmbed, CreateEmbedAuthor, CreateEmbedFooter, CreateInteractionResponse, CreateInteractionResponseMessage, Timestamp, User, UserId};
+use poise::serenity_prelude::{CreateEmbed, CreateEmbedAuthor, CreateEmbedFooter, Timestamp, User, UserId};
 use poise::serenity_prelude::nonmax::NonMaxU64;
 use crate::{BotError, Context};
-use crate::database::models::Cases;
+use crate::database::models::{Cases, PurgeTranscripts};
+use crate::commands::moderation::note::NOTE_CASE_TYPE;
 use crate::localization::manager::TranslationParam;
+use crate::modules::moderation::bans::{is_unknown_ban, reconcile_bans};
+use crate::modules::moderation::case_types::{autocomplete_case_type, fetch_case_types};
+use crate::modules::moderation::evidence::{delete_evidence, fetch_evidence};
 use crate::modules::moderation::logs::{log_action, LogData, LogType};
+use crate::modules::moderation::transcripts::transcript_attachments;
 use crate::util::color::{BotColors};
+use crate::util::interaction::{paginate_entries, send_paginated, DEFAULT_PAGE_SIZE};
+use crate::util::time::{fetch_guild_timezone, format_datetime, parse_date};
 use crate::util::timestamp::{Format, TimestampExt};
 
-#[poise::command(slash_command, guild_only, subcommands("view", "remove"), subcommand_required)]
+#[derive(Debug, Clone, Copy, PartialEq, poise::ChoiceParameter)]
+pub enum CaseStatus {
+    #[name = "Active"]
+    Active,
+    #[name = "Expired"]
+    Expired,
+    #[name = "Permanent"]
+    Permanent,
+}
+
+#[poise::command(slash_command, guild_only, subcommands("view", "remove", "transcript", "sync"), subcommand_required)]
 pub async fn cases(_: Context<'_>) -> Result<(), BotError> {
     Ok(())
 }
@@ -24,8 +42,13 @@ pub async fn view(
     ctx: Context<'_>,
     #[description = "Case(s) of this user"] user: Option<User>,
     #[description = "The case ID"] case: Option<i32>,
-    #[description = "The case type"] #[rename = "type"] case_res_type: Option<String>,
+    #[description = "The case type"] #[rename = "type"] #[autocomplete = "autocomplete_case_type"] case_res_type: Option<String>,
     #[description = "The case(s) moderator"] #[rename = "mod"] case_res_moderator: Option<User>,
+    #[description = "Only active, expired or permanent cases"] status: Option<CaseStatus>,
+    #[description = "Created on or after this date (YYYY-MM-DD)"] created_after: Option<String>,
+    #[description = "Created on or before this date (YYYY-MM-DD)"] created_before: Option<String>,
+    #[description = "Words the reason must contain"] search: Option<String>,
+    #[description = "Cases per page"] #[min = 1] #[max = 25] page_size: Option<u8>,
 ) -> Result<(), BotError> {
     use crate::database::schema::cases::dsl::*;
 
@@ -36,341 +59,222 @@ pub async fn view(
     let guild_lang = locales
         .get_guild_language(db, ctx.guild_id().unwrap()).await.unwrap();
 
-
-
     let guild = ctx.guild_id().unwrap().get() as i64;
 
     let data = ctx.data();
 
+    let guild_tz = fetch_guild_timezone(&data, ctx.guild_id().unwrap()).await;
 
-    if let Some(case_res_id) = case {
-
-        if user.is_some() {
-            ctx.say(locales.get("commands.moderation.cases.view_error_user_and_id", guild_lang, &[])).await?;
-            return Ok(());
-        }
-
-        if case_res_type.is_some() {
-            ctx.say(locales.get("commands.moderation.cases.view_error_id_and_type", guild_lang, &[])).await?;
-            return Ok(());
-        }
-
-        if case_res_moderator.is_some() {
-            ctx.say(locales.get("commands.moderation.cases.view_error_id_and_mod", guild_lang, &[])).await?;
-            return Ok(());
-        }
-
-
-        let case = data.db.run(|conn| {
-            cases
-                .filter(guild_id.eq(guild))
-                .filter(case_id.eq(case_res_id))
-                .select(Cases::as_select())
-                .first::<Cases>(conn)
-        }).await.ok();
-
-        if case.is_none() {
-            ctx.say(locales.get("commands.moderation.cases.view_error_no_case", guild_lang, &[])).await?;
+    let after_date = match created_after.as_deref().map(|date| parse_date(date, guild_tz)) {
+        Some(None) => {
+            ctx.say(locales.get("commands.moderation.cases.view_error_invalid_date", guild_lang, &[])).await?;
             return Ok(());
-        }
-
-        let case = case.unwrap();
-
-
-        let before_user = UserId::new(u64::from(NonMaxU64::try_from(case.user_id as u64).unwrap()));
-
-        let moderator = UserId::new(u64::from(NonMaxU64::try_from(case.moderator_id as u64).unwrap()));
-
-        let user = before_user.to_user(ctx.http()).await?;
-
-        let points_info = if case.case_type == "WARN" {
-            format!("`{}:` {}", locales.get("commands.moderation.cases.view_points", guild_lang, &[]), case.points.unwrap_or(0))
-        } else {
-            String::new()
-        };
-
-
-
-        let action_reason = case.reason.clone().unwrap_or_else(|| locales.get("commands.moderation.cases.no_reason", guild_lang, &[]));
-
-        let case_trans = locales.get("commands.moderation.cases.view_case", guild_lang, &[
-            TranslationParam::from(case.case_id.to_string()),
-            TranslationParam::from(before_user.get().to_string()),
-            TranslationParam::from(before_user.get().to_string()),
-            TranslationParam::from(case.case_type.clone()),
-            TranslationParam::from(moderator.get().to_string()),
-            TranslationParam::from(action_reason),
-            TranslationParam::from(points_info),
-            TranslationParam::from(case.created_at.timestamp().to_string()),
-            TranslationParam::from(case.end_date.map_or(locales.get("commands.moderation.cases.never", guild_lang, &[]), |dt| {
-                Timestamp::from(dt).to_discord_timestamp(Format::LongDateShortTime)
-            })),
-        ]);
-
-
-        let embed = CreateEmbed::new().color(BotColors::Default.color())
-            .author(CreateEmbedAuthor::new(format!("Case Info for {}", user.clone().global_name.unwrap_or_else(|| user.name.clone())))
-                .icon_url(user.avatar_url().unwrap_or_default()))
-            .description(case_trans);
-
-            ctx.send(CreateReply::new().embed(embed)).await?;
-            return Ok(())
-        }
-
-    if let Some(moderator_res) = case_res_moderator {
-        if user.is_some() {
-            ctx.say(locales.get("commands.moderation.cases.view_error_user_and_mod", guild_lang, &[])).await?;
-            return Ok(());
-        }
-
-        if case_res_type.is_some() {
-            ctx.say(locales.get("commands.moderation.cases.view_error_mod_and_type", guild_lang, &[])).await?;
-            return Ok(());
-        }
-
-        if case.is_some() {
-            ctx.say(locales.get("commands.moderation.cases.view_error_id_and_mod", guild_lang, &[])).await?;
-            return Ok(());
-        }
-
-        let moderator = UserId::new(u64::from(NonMaxU64::try_from(moderator_res.id.get()).unwrap())).to_user(ctx.http()).await?;
-
-        let cases_results = data.db.run(|conn| {
-            cases
-                .filter(guild_id.eq(guild))
-                .filter(moderator_id.eq(moderator.id.get() as i64))
-                .select(Cases::as_select())
-                .load::<Cases>(conn)
-        }).await.ok();
+        },
+        Some(date) => date,
+        None => None,
+    };
 
-        if cases_results.is_none() {
-            ctx.say(locales.get("commands.moderation.cases.view_error_no_cases_mod", guild_lang, &[
-                TranslationParam::from(moderator.clone().global_name.unwrap())
-            ])).await?;
+    // The whole "before" day is included
+    let before_date = match created_before.as_deref().map(|date| parse_date(date, guild_tz)) {
+        Some(None) => {
+            ctx.say(locales.get("commands.moderation.cases.view_error_invalid_date", guild_lang, &[])).await?;
             return Ok(());
-        }
+        },
+        Some(date) => date.map(|date| date + chrono::Duration::days(1)),
+        None => None,
+    };
 
-        let cases_results = cases_results.unwrap();
+    let mut query = cases
+        .filter(guild_id.eq(guild))
+        .select(Cases::as_select())
+        .into_boxed();
 
-        let mut result = String::new();
+    // Notes are moderator-only, anyone else doesn't get to see them at all
+    let is_moderator = ctx.author_member().await
+        .and_then(|member| member.permissions(ctx.cache()).ok())
+        .is_some_and(|permissions| permissions.moderate_members());
 
+    if !is_moderator {
+        query = query.filter(case_type.ne(NOTE_CASE_TYPE));
+    }
 
+    if let Some(case_res_id) = case {
+        query = query.filter(case_id.eq(case_res_id));
+    }
 
-        for case in cases_results {
-            let user = UserId::new(u64::from(NonMaxU64::try_from(case.user_id as u64).unwrap()));
-            let points_info = if case.case_type == "WARN" {
-                format!("`{}:` {}", locales.get("commands.moderation.cases.view_points", guild_lang, &[]), case.points.unwrap_or(0))
-            } else {
-                String::new()
-            };
+    if let Some(ref moderator_res) = case_res_moderator {
+        query = query.filter(moderator_id.eq(moderator_res.id.get() as i64));
+    }
 
+    if let Some(ref user) = user {
+        query = query.filter(user_id.eq(user.id.get() as i64));
+    }
 
+    if let Some(ref case_res_type) = case_res_type {
+        query = query.filter(case_type.eq(case_res_type.to_uppercase()));
+    }
 
+    // Revoked cases count as expired whatever their end date, permanent ones are a subset of active
+    match status {
+        Some(CaseStatus::Active) => query = query.filter(revoked_at.is_null().and(end_date.is_null().or(end_date.gt(Utc::now())))),
+        Some(CaseStatus::Expired) => query = query.filter(revoked_at.is_not_null().or(end_date.le(Utc::now()))),
+        Some(CaseStatus::Permanent) => query = query.filter(revoked_at.is_null().and(end_date.is_null())),
+        None => {}
+    }
 
-            let action_reason = case.reason.clone().unwrap_or_else(|| locales.get("commands.moderation.cases.no_reason", guild_lang, &[]));
+    if let Some(after_date) = after_date {
+        query = query.filter(created_at.ge(after_date));
+    }
 
-            let case_trans = locales.get("commands.moderation.cases.view_case", guild_lang, &[
-                TranslationParam::from(case.case_id.to_string()),
-                TranslationParam::from(user.get().to_string()),
-                TranslationParam::from(user.get().to_string()),
-                TranslationParam::from(case.case_type.clone()),
-                TranslationParam::from(moderator.id.get().to_string()),
-                TranslationParam::from(action_reason),
-                TranslationParam::from(points_info),
-                TranslationParam::from(case.created_at.timestamp().to_string()),
-                TranslationParam::from(case.end_date.map_or(locales.get("commands.moderation.cases.never", guild_lang, &[]), |dt| {
-                    Timestamp::from(dt).to_discord_timestamp(Format::LongDateShortTime)
-                })),
-            ]);
+    if let Some(before_date) = before_date {
+        query = query.filter(created_at.lt(before_date));
+    }
 
-            result += &*case_trans;
-        }
+    // Every word has to appear somewhere in the reason, in any order
+    for word in search.iter().flat_map(|search| search.split_whitespace()) {
+        let escaped = word.replace('\\', "\\\\").replace('%', "\\%").replace('_', "\\_");
+        query = query.filter(reason.ilike(format!("%{}%", escaped)));
+    }
 
+    let mut cases_result = data.db.run(|conn| {
+        query.load::<Cases>(conn)
+    }).await?;
 
-        let embed = CreateEmbed::new().color(BotColors::Default.color())
-            .author(CreateEmbedAuthor::new(locales.get("commands.moderation.cases.view_user_cases", guild_lang, &[
-                TranslationParam::from(moderator.clone().global_name.unwrap())
-            ]))
-                .icon_url(moderator.avatar_url().unwrap_or_default()))
-            .description(result);
+    if cases_result.is_empty() {
+        let filtered = case_res_type.is_some() || status.is_some() || after_date.is_some() || before_date.is_some() || search.is_some();
+
+        let error = if case.is_some() && !filtered {
+            locales.get("commands.moderation.cases.view_error_no_case", guild_lang, &[])
+        } else if filtered {
+            locales.get("commands.moderation.cases.view_error_no_matches", guild_lang, &[])
+        } else if let Some(ref moderator_res) = case_res_moderator {
+            locales.get("commands.moderation.cases.view_error_no_cases_mod", guild_lang, &[
+                TranslationParam::from(moderator_res.global_name.clone().map_or(moderator_res.name.to_string(), |name| name.to_string()))
+            ])
+        } else {
+            locales.get("commands.moderation.cases.view_error_no_cases", guild_lang, &[])
+        };
 
-        ctx.send(CreateReply::new().embed(embed)).await?;
+        ctx.say(error).await?;
         return Ok(());
     }
 
-    let cases_result = if let Some(ref user) = user {
-        let user = UserId::new(u64::from(NonMaxU64::try_from(user.id.get()).unwrap()));
-
-        data.db.run(|conn| {
-            cases
-                .filter(guild_id.eq(guild))
-                .filter(user_id.eq(user.get() as i64))
-                .select(Cases::as_select())
-                .load::<Cases>(conn)
-        }).await?
-    } else {
-        data.db.run(|conn| {
-            cases
-                .filter(guild_id.eq(guild))
-                .select(Cases::as_select())
-                .load::<Cases>(conn)
-        }).await?
-    };
-
-    if cases_result.is_empty() {
-        ctx.say(locales.get("commands.moderation.cases.view_error_no_cases", guild_lang, &[])).await?;
-        return Ok(());
+    // Same as `/note list`, notes must never end up in a public channel
+    if cases_result.iter().any(|case| case.case_type == NOTE_CASE_TYPE) {
+        ctx.defer_ephemeral().await?;
     }
 
+    cases_result.sort_by_key(|case| (case.user_id, case.case_id));
 
-    let mut grouped_cases: BTreeMap<i64, Vec<Cases>> = BTreeMap::new();
     let mut user_ids = Vec::new();
-    let mut moderator_ids = Vec::new();
-
     for case in &cases_result {
-        grouped_cases.entry(case.user_id)
-            .or_default()
-            .push(case.clone());
         if !user_ids.contains(&case.user_id) {
             user_ids.push(case.user_id);
         }
-        if !moderator_ids.contains(&case.moderator_id) {
-            moderator_ids.push(case.moderator_id);
-        }
-    }
-
-    for all_cases in grouped_cases.values_mut() {
-        all_cases.sort_by_key(|case| case.case_id);
     }
 
     let user_futures: Vec<_> = user_ids.iter()
         .map(|&id_res| UserId::new(u64::from(NonMaxU64::new(id_res as u64).unwrap())).to_user(ctx.http()))
         .collect();
-    let moderator_futures: Vec<_> = moderator_ids.iter()
-        .map(|&id_res| UserId::new(u64::from(NonMaxU64::new(id_res as u64).unwrap())).to_user(ctx.http()))
-        .collect();
 
-    let (users, moderators) = futures::join!(join_all(user_futures), join_all(moderator_futures));
+    let users = join_all(user_futures).await;
 
     let mut user_info = HashMap::new();
     for user in users.into_iter().filter_map(Result::ok) {
-        user_info.insert(user.id, user.global_name.unwrap_or_else(|| user.name.clone()));
+        user_info.insert(user.id, (user.global_name.clone().unwrap_or_else(|| user.name.clone()).to_string(), user.avatar_url()));
     }
 
-    let mut moderator_info = HashMap::new();
-    for moderator in moderators.into_iter().filter_map(Result::ok) {
-        moderator_info.insert(moderator.id, moderator.global_name.unwrap_or_else(|| moderator.name.clone()));
-    }
+    // A header per user is only needed when the results can span several users
+    let grouped = case.is_none() && user.is_none();
 
-    let cases_per_page = 10;
-    let total_cases: usize = grouped_cases.values().map(|v| v.len()).sum();
-    let pages = (total_cases as f32 / cases_per_page as f32).ceil() as usize;
-    let mut current_page = 0;
+    let custom_types: HashMap<String, String> = fetch_case_types(&data, ctx.guild_id().unwrap()).await
+        .into_iter()
+        .map(|case_type| (case_type.name, case_type.display_name))
+        .collect();
 
+    let evidence = fetch_evidence(&data, ctx.guild_id().unwrap(), cases_result.iter().map(|case| case.case_id).collect()).await;
 
-    let create_message = async move |page: usize| {
-        let mut cases_embed = CreateEmbed::new().color(BotColors::Default.color());
-        let mut result = String::new();
-        let mut count = 0;
-        let start = page * cases_per_page;
-        let end = start + cases_per_page;
+    let entries: Vec<(Option<String>, String)> = cases_result.iter().map(|case| {
+        let user_res_id = UserId::new(u64::from(NonMaxU64::try_from(case.user_id as u64).unwrap()));
+        let moderator = UserId::new(u64::from(NonMaxU64::try_from(case.moderator_id as u64).unwrap()));
 
-        for (&user_res_id, cases_res) in &grouped_cases {
-            let user_res_id = UserId::new(u64::from(NonMaxU64::new(user_res_id as u64).unwrap()));
-            let user_name = user_info.get(&user_res_id).cloned().unwrap_or_else(|| user_res_id.to_string().parse().unwrap());
+        let group = grouped.then(|| {
+            let user_name = user_info.get(&user_res_id).map_or(user_res_id.to_string(), |(name, _)| name.clone());
+            locales.get("commands.moderation.cases.view_user_cases", guild_lang, &[
+                TranslationParam::from(user_name)
+            ])
+        });
 
-            if count >= start && count < end {
-                let user_cases = locales.get("commands.moderation.cases.view_user_cases", guild_lang, &[
-                    TranslationParam::from(user_name.clone())
-                ]);
+        let points_info = case.points.map_or(String::new(), |case_points| {
+            format!("`{}:` {}", locales.get("commands.moderation.cases.view_points", guild_lang, &[]), case_points)
+        });
 
-                result += &format!("**{}**\n", user_cases);
-            }
+        let type_name = custom_types.get(&case.case_type).cloned().unwrap_or_else(|| case.case_type.clone());
 
-            for case in cases_res {
-                if count >= start && count < end {
-                    let moderator = UserId::new(u64::from(NonMaxU64::try_from(case.moderator_id as u64).unwrap()));
-                    let _moderator_name = moderator_info.get(&moderator).cloned().unwrap_or_else(|| moderator.to_string().parse().unwrap());
+        let action_reason = case.reason.clone().unwrap_or_else(|| locales.get("commands.moderation.cases.no_reason", guild_lang, &[]));
 
-                    let points_info = if case.case_type == "WARN" {
-                        format!("`{}:` {}", locales.get("commands.moderation.cases.view_points", guild_lang, &[]), case.points.unwrap_or(1))
-                    } else {
-                        String::new()
-                    };
+        let case_trans = locales.get("commands.moderation.cases.view_case", guild_lang, &[
+            TranslationParam::from(case.case_id.to_string()),
+            TranslationParam::from(user_res_id.get().to_string()),
+            TranslationParam::from(user_res_id.get().to_string()),
+            TranslationParam::from(type_name),
+            TranslationParam::from(moderator.get().to_string()),
+            TranslationParam::from(action_reason),
+            TranslationParam::from(points_info),
+            TranslationParam::from(case.created_at.timestamp().to_string()),
+            TranslationParam::from(case.end_date.map_or(locales.get("commands.moderation.cases.never", guild_lang, &[]), |dt| {
+                format!("{} ({})", Timestamp::from(dt).to_discord_timestamp(Format::LongDateShortTime), format_datetime(dt, guild_tz))
+            })),
+            TranslationParam::from(format_datetime(case.created_at, guild_tz)),
+        ]);
 
-                    let action_reason = case.reason.clone().unwrap_or_else(|| locales.get("commands.moderation.cases.no_reason", guild_lang, &[]));
-
-                    let case_trans = locales.get("commands.moderation.cases.view_case", guild_lang, &[
-                        TranslationParam::from(case.case_id.to_string()),
-                        TranslationParam::from(user_res_id.to_string().clone()),
-                        TranslationParam::from(user_res_id.to_string().clone()),
-                        TranslationParam::from(case.case_type.clone()),
-                        TranslationParam::from(moderator.get().to_string().clone()),
-                        TranslationParam::from(action_reason),
-                        TranslationParam::from(points_info),
-                        TranslationParam::from(case.created_at.timestamp().to_string()),
-                        TranslationParam::from(case.end_date.map_or(locales.get("commands.moderation.cases.never", guild_lang, &[]), |dt| {
-                            Timestamp::from(dt).to_discord_timestamp(Format::LongDateShortTime)
-                        })),
-                    ]);
-
-                    result += &*case_trans;
-                }
-                count += 1;
-                if count >= end {
-                    break;
-                }
-            }
-            if count >= end {
-                break;
-            }
-        }
+        let case_trans = match case.revoked_at {
+            Some(revoked) => format!("{}`{}:` {} ({})\n", case_trans, locales.get("commands.moderation.cases.view_revoked", guild_lang, &[]),
+                case.revoke_reason.clone().unwrap_or_default(),
+                Timestamp::from(revoked).to_discord_timestamp(Format::LongDateShortTime)),
+            None => case_trans,
+        };
 
-        cases_embed = cases_embed.description(result);
-        if let Some(ref user) = user {
-            cases_embed = cases_embed.author(CreateEmbedAuthor::new(locales.get("commands.moderation.cases.view_cases_for", guild_lang, &[
-                TranslationParam::from(user.name.clone())
-            ])));
-        } else {
-            cases_embed = cases_embed.author(CreateEmbedAuthor::new(locales.get("commands.moderation.cases.view_cases_for_guild", guild_lang, &[])).icon_url(ctx.guild().unwrap().icon_url().unwrap_or_default()));
-        }
-        cases_embed = cases_embed.footer(CreateEmbedFooter::new(locales.get("commands.moderation.cases.view_case_page", guild_lang, &[
-            TranslationParam::from((page + 1).to_string()),
-            TranslationParam::from(pages.to_string())
-        ])));
-
-        let mut components = vec![];
-        let previous_button = CreateButton::new("prev").style(ButtonStyle::Primary).emoji('⬅').disabled(page == 0);
-        let next_button = CreateButton::new("next")
-            .style(ButtonStyle::Primary)
-            .emoji('➡')
-            .disabled(page >= pages - 1);
-        let action_row = CreateActionRow::Buttons(vec![previous_button, next_button]);
-        components.push(action_row);
-
-        (cases_embed, components)
+        let case_trans = match evidence.get(&case.case_id) {
+            Some(entries) => format!("{}`{}:` {}\n", case_trans, locales.get("commands.moderation.cases.view_evidence", guild_lang, &[]), entries.iter()
+                .enumerate()
+                .map(|(index, entry)| format!("[{}]({})", index + 1, entry.url))
+                .collect::<Vec<_>>()
+                .join(" ")),
+            None => case_trans,
+        };
+
+        (group, case_trans)
+    }).collect();
+
+    let pages = paginate_entries(&entries, page_size.map_or(DEFAULT_PAGE_SIZE, usize::from));
+
+    let author = if case.is_some() && cases_result.len() == 1 {
+        let case_user = UserId::new(u64::from(NonMaxU64::try_from(cases_result[0].user_id as u64).unwrap()));
+        let (name, avatar) = user_info.get(&case_user).cloned().unwrap_or((case_user.to_string(), None));
+        CreateEmbedAuthor::new(format!("Case Info for {}", name)).icon_url(avatar.unwrap_or_default())
+    } else if let Some(ref moderator_res) = case_res_moderator {
+        CreateEmbedAuthor::new(locales.get("commands.moderation.cases.view_user_cases", guild_lang, &[
+            TranslationParam::from(moderator_res.global_name.clone().map_or(moderator_res.name.to_string(), |name| name.to_string()))
+        ])).icon_url(moderator_res.avatar_url().unwrap_or_default())
+    } else if let Some(ref user) = user {
+        CreateEmbedAuthor::new(locales.get("commands.moderation.cases.view_cases_for", guild_lang, &[
+            TranslationParam::from(user.name.clone())
+        ]))
+    } else {
+        let icon = ctx.guild().unwrap().icon_url().unwrap_or_default();
+        CreateEmbedAuthor::new(locales.get("commands.moderation.cases.view_cases_for_guild", guild_lang, &[])).icon_url(icon)
     };
 
-    let (content, components) = create_message(current_page).await;
-    let message = ctx.send(CreateReply::new()
-        .embed(content)
-        .components(components)
-    ).await?;
-
-    while let Some(interaction) = message.message().await?.await_component_interaction(ctx.serenity_context().clone().shard).await {
-        match interaction.data.custom_id.as_str() {
-            "prev" => current_page = current_page.saturating_sub(1),
-            "next" => current_page = (current_page + 1).min(pages - 1),
-            _ => continue,
-        }
-        if interaction.user.id != ctx.author().id {
-            interaction.create_response(ctx.http(), CreateInteractionResponse::Message(CreateInteractionResponseMessage::new().ephemeral(true).content("You can't interact with this message.").ephemeral(true))).await?;
-            continue;
-        }
-        
-        let (content, components) = create_message(current_page).await;
-        interaction.create_response(ctx.http(), CreateInteractionResponse::UpdateMessage(CreateInteractionResponseMessage::new().embed(content).components(components))).await?;
-    }
+    send_paginated(ctx, pages, |description, page, total| {
+        CreateEmbed::new().color(BotColors::Default.color())
+            .author(author.clone())
+            .description(description.to_string())
+            .footer(CreateEmbedFooter::new(locales.get("commands.moderation.cases.view_case_page", guild_lang, &[
+                TranslationParam::from((page + 1).to_string()),
+                TranslationParam::from(total.to_string())
+            ])))
+    }).await?;
 
     Ok(())
 }
@@ -450,7 +354,11 @@ pub async fn remove(
                     };
 
                     log_action(LogType::Unban, log_data).await?;
-                    guild.unban(ctx.http(), user, Some(&format!("Case {} removed", case_res_id))).await?;
+                    match guild.unban(ctx.http(), user, Some(&format!("Case {} removed", case_res_id))).await {
+                        Ok(()) => {},
+                        Err(why) if is_unknown_ban(&why) => {},
+                        Err(why) => return Err(why.into()),
+                    }
                 },
                 "WARN" => {
                     let points_res = case.points.unwrap_or(1);
@@ -472,6 +380,8 @@ pub async fn remove(
         ).execute(conn)
     }).await?;
 
+    delete_evidence(&data, ctx.guild_id().unwrap(), case_ids).await?;
+
     if !removed_warns.is_empty() {
         let log_data = LogData {
             ctx: Some(ctx.serenity_context()),
@@ -507,3 +417,81 @@ pub async fn remove(
 
     Ok(())
 }
+
+/// Get the full transcript of a message purge
+#[poise::command(slash_command, required_permissions="MANAGE_MESSAGES", guild_only)]
+pub async fn transcript(
+    ctx: Context<'_>,
+    #[description = "The transcript ID shown in the purge log"] transcript_id: i32,
+) -> Result<(), BotError> {
+    use crate::database::schema::purge_transcripts::dsl::*;
+
+    let db = ctx.data().db.clone();
+    let locales = ctx.data().localization_manager.clone();
+    let guild_lang = locales
+        .get_guild_language(db, ctx.guild_id().unwrap()).await.unwrap();
+
+    let guild = ctx.guild_id().unwrap().get() as i64;
+
+    let result = ctx.data().db.run(|conn| {
+        purge_transcripts
+            .filter(guild_id.eq(guild))
+            .filter(id.eq(transcript_id))
+            .select(PurgeTranscripts::as_select())
+            .first::<PurgeTranscripts>(conn)
+    }).await.ok();
+
+    let Some(result) = result else {
+        ctx.say(locales.get("commands.moderation.cases.transcript_error_not_found", guild_lang, &[])).await?;
+        return Ok(());
+    };
+
+    let content = locales.get("commands.moderation.cases.transcript_reply", guild_lang, &[
+        TranslationParam::from(transcript_id.to_string()),
+        TranslationParam::from(result.messages_deleted.to_string()),
+        TranslationParam::from(result.channel_id.to_string()),
+        TranslationParam::from(result.moderator_id.to_string()),
+        TranslationParam::from(Timestamp::from(result.created_at).to_discord_timestamp(Format::LongDateShortTime)),
+    ]);
+
+    let mut reply = CreateReply::new().content(content).ephemeral(true);
+    for attachment in transcript_attachments(&result, transcript_id) {
+        reply = reply.attachment(attachment);
+    }
+
+    ctx.send(reply).await?;
+
+    Ok(())
+}
+
+/// Match the stored ban cases with the server's actual bans
+#[poise::command(slash_command, required_permissions="BAN_MEMBERS", guild_only)]
+pub async fn sync(
+    ctx: Context<'_>,
+    #[description = "Create cases for bans that don't have one"] import: Option<bool>,
+) -> Result<(), BotError> {
+    let db = ctx.data().db.clone();
+    let locales = ctx.data().localization_manager.clone();
+    let guild_lang = locales
+        .get_guild_language(db, ctx.guild_id().unwrap()).await.unwrap();
+
+    // Fetching every ban can take a while on large servers
+    ctx.defer_ephemeral().await?;
+
+    let data = ctx.data().clone();
+    let reconciliation = reconcile_bans(ctx.serenity_context(), &data, ctx.guild_id().unwrap(), ctx.author().id, import.unwrap_or(false)).await?;
+
+    let description = if reconciliation.is_empty() {
+        locales.get("commands.moderation.cases.sync_in_sync", guild_lang, &[])
+    } else {
+        reconciliation.report(true, guild_lang, &locales)
+    };
+
+    ctx.send(CreateReply::new().embed(CreateEmbed::new()
+        .title(locales.get("commands.moderation.cases.sync_title", guild_lang, &[]))
+        .description(description)
+        .color(BotColors::Default.color())
+    ).ephemeral(true)).await?;
+
+    Ok(())
+}
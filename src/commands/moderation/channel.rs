@@ -1,13 +1,34 @@
 
-use poise::{command};
-use poise::serenity_prelude::{EditChannel, GuildChannel, PermissionOverwrite, PermissionOverwriteType, Permissions};
+use chrono::Utc;
+use diesel::prelude::*;
+use diesel_async::RunQueryDsl;
+use poise::{command, CreateReply, send_reply};
+use poise::serenity_prelude::{EditChannel, GuildChannel, RoleId};
+use regex::Regex;
 use crate::{BotError, Context};
-
+use crate::database::models::{ChannelLocks, Slowmodes};
+use crate::localization::manager::TranslationParam;
+use crate::modules::moderation::locks::{lock_channel, remove_channel_lock, unlock_channel};
 use crate::modules::moderation::logs::{log_action, LogData, LogType};
+use crate::util::time::{fetch_guild_timezone, format_duration, parse_duration, parse_duration_or_date, DurationError};
+
+/// Discord doesn't allow a slowmode longer than 6 hours
+const MAX_SLOWMODE_DELAY: u64 = 21600;
 
-#[command(slash_command, default_member_permissions="MANAGE_CHANNELS", subcommands("lock", "unlock"), guild_only)]
+#[command(slash_command, default_member_permissions="MANAGE_CHANNELS", subcommands("lock", "unlock", "slowmode"), guild_only)]
 pub async fn channel(_: Context<'_>) -> Result<(), BotError> { Ok(()) }
 
+/// Parses role mentions or raw IDs separated by spaces or commas
+fn parse_roles(input: &str) -> Vec<RoleId> {
+    let re = Regex::new(r"^(?:<@&)?(\d+)>?$").unwrap();
+
+    input.split([' ', ','])
+        .filter(|part| !part.is_empty())
+        .map(|part| re.captures(part).and_then(|c| c[1].parse::<u64>().ok()).filter(|id| *id != 0).map(RoleId::new))
+        .collect::<Option<Vec<_>>>()
+        .unwrap_or_default()
+}
+
 /// Lock a channel
 #[command(slash_command, default_member_permissions="MANAGE_CHANNELS", guild_only)]
 pub async fn lock(
@@ -16,7 +37,11 @@ pub async fn lock(
     #[channel_types("Text")]
     channel: Option<GuildChannel>,
     #[description = "Reason for locking the channel"]
-    reason: Option<String>
+    reason: Option<String>,
+    #[description = "Unlock the channel automatically after this time (e.g. \"30m\")"]
+    duration: Option<String>,
+    #[description = "Roles to lock the channel for (mentions or IDs). Defaults to @everyone."]
+    roles: Option<String>
 ) -> Result<(), BotError> {
     let current_channel = ctx.guild_channel().await.unwrap();
 
@@ -24,39 +49,59 @@ pub async fn lock(
     let locales = ctx.data().localization_manager.clone();
     let guild_lang = locales
         .get_guild_language(db, ctx.guild_id().unwrap()).await.unwrap();
-    
-    
 
-    let mut channel = channel.unwrap_or(current_channel);
+    let guild_tz = fetch_guild_timezone(&ctx.data(), ctx.guild_id().unwrap()).await;
+    let unlock_after = match duration.map(|d| parse_duration_or_date(&d, guild_lang, guild_tz)) {
+        Some(Ok(seconds)) => seconds,
+        Some(Err(why)) => {
+            send_reply(ctx, CreateReply::new().content(why.localize(guild_lang, &locales)).ephemeral(true)).await?;
+            return Ok(());
+        },
+        None => None,
+    };
 
-    let everyone = ctx.guild().unwrap().id.everyone_role().get();
+    let duration = unlock_after.map(|seconds| format_duration(seconds, guild_lang));
 
-    let mut permissions: Vec<PermissionOverwrite> = channel.permission_overwrites.clone().into();
 
-    if let Some(perms) = permissions.iter_mut().find(|p| p.kind == PermissionOverwriteType::Role(everyone.into())) {
-        if perms.deny.contains(Permissions::SEND_MESSAGES) {
-            ctx.say(locales.get("commands.moderation.channel.error_already_locked", guild_lang, &[])).await?;
+    let channel = channel.unwrap_or(current_channel);
+
+    let target_roles = match roles.as_deref().map(parse_roles) {
+        Some(parsed) if parsed.is_empty() => {
+            send_reply(ctx, CreateReply::new().content(locales.get("commands.moderation.channel.error_invalid_roles", guild_lang, &[])).ephemeral(true)).await?;
             return Ok(());
-        } else {
-            ctx.say(locales.get("commands.moderation.channel.reply_locked", guild_lang, &[])).await?;
-            perms.deny.insert(Permissions::SEND_MESSAGES);
-            perms.deny.insert(Permissions::SEND_MESSAGES_IN_THREADS);
-            perms.allow.remove(Permissions::SEND_MESSAGES);
-            perms.allow.remove(Permissions::SEND_MESSAGES_IN_THREADS);
-        }
-    } else {
-        permissions.push(PermissionOverwrite {
-            kind: PermissionOverwriteType::Role(everyone.into()),
-            allow: Permissions::empty(),
-            deny: Permissions::SEND_MESSAGES | Permissions::SEND_MESSAGES_IN_THREADS,
-         });
-        ctx.say(locales.get("commands.moderation.channel.reply_locked", guild_lang, &[])).await?;
-    }
+        },
+        Some(parsed) => {
+            let unknown_role = {
+                let guild = ctx.guild().unwrap();
+                parsed.iter().any(|role| guild.roles.get(role).is_none())
+            };
+            if unknown_role {
+                send_reply(ctx, CreateReply::new().content(locales.get("commands.moderation.channel.error_invalid_roles", guild_lang, &[])).ephemeral(true)).await?;
+                return Ok(());
+            }
+            parsed
+        },
+        None => vec![ctx.guild_id().unwrap().everyone_role()],
+    };
 
-    channel.edit(ctx.http(), EditChannel::new().permissions(permissions)).await?;
-    
     let data = ctx.data().clone();
-    
+
+    let locked_roles = lock_channel(ctx.serenity_context(), &data, &channel, &target_roles, ChannelLocks {
+        channel_id: channel.id.get() as i64,
+        guild_id: ctx.guild_id().unwrap().get() as i64,
+        moderator_id: ctx.author().id.get() as i64,
+        reason: reason.clone(),
+        created_at: Utc::now(),
+        end_date: unlock_after.map(|seconds| Utc::now() + chrono::Duration::seconds(seconds as i64)),
+    }).await?;
+
+    if locked_roles.is_empty() {
+        ctx.say(locales.get("commands.moderation.channel.error_already_locked", guild_lang, &[])).await?;
+        return Ok(());
+    }
+
+    ctx.say(locales.get("commands.moderation.channel.reply_locked", guild_lang, &[])).await?;
+
     let log_data = LogData {
         data: Some(&*data),
         ctx: Some(ctx.serenity_context()),
@@ -64,6 +109,9 @@ pub async fn lock(
         channel_id: Some(channel.id.get()),
         moderator_id: Some(ctx.author().id),
         reason: reason.or(Option::from(locales.get("commands.moderation.channel.no_reason", guild_lang, &[]))),
+        duration,
+        ends_at: unlock_after.map(|seconds| Utc::now() + chrono::Duration::seconds(seconds as i64)),
+        roles: roles.map(|_| locked_roles.iter().map(|role| role.get()).collect()),
         ..LogData::default()
     };
     
@@ -89,31 +137,20 @@ pub async fn unlock(
     
     let current_channel = ctx.guild_channel().await.unwrap();
 
-    let mut channel = channel.unwrap_or(current_channel);
+    let channel = channel.unwrap_or(current_channel);
 
-    let everyone = ctx.guild().unwrap().id.everyone_role().get();
+    let everyone = ctx.guild().unwrap().id.everyone_role();
 
-    let mut permissions: Vec<PermissionOverwrite> = channel.permission_overwrites.clone().into();
+    let data = ctx.data().clone();
 
-    if let Some(perms) = permissions.iter_mut().find(|p| p.kind == PermissionOverwriteType::Role(everyone.into())) {
-        if perms.deny.contains(Permissions::SEND_MESSAGES) {
-            ctx.say(locales.get("commands.moderation.channel.reply_unlocked", guild_lang, &[])).await?;
-            perms.deny.remove(Permissions::SEND_MESSAGES);
-            perms.allow.insert(Permissions::SEND_MESSAGES);
-            perms.deny.remove(Permissions::SEND_MESSAGES_IN_THREADS);
-            perms.allow.insert(Permissions::SEND_MESSAGES_IN_THREADS);
-        } else {
-            ctx.say(locales.get("commands.moderation.channel.error_already_unlocked", guild_lang, &[])).await?;
-            return Ok(());
-        }
-    } else {
+    if !unlock_channel(ctx.serenity_context(), &data, &channel, everyone).await? {
         ctx.say(locales.get("commands.moderation.channel.error_already_unlocked", guild_lang, &[])).await?;
         return Ok(());
     }
 
-    channel.edit(ctx.http(), EditChannel::new().permissions(permissions)).await?;
+    ctx.say(locales.get("commands.moderation.channel.reply_unlocked", guild_lang, &[])).await?;
 
-    let data = ctx.data().clone();
+    remove_channel_lock(&data, channel.id.get() as i64).await?;
     
     let log_data = LogData {
         data: Some(&*data),
@@ -127,5 +164,135 @@ pub async fn unlock(
     
     log_action(LogType::Unlock, log_data).await?;
 
+    Ok(())
+}
+
+/// Set the slowmode of a channel
+#[command(slash_command, default_member_permissions="MANAGE_CHANNELS", guild_only)]
+pub async fn slowmode(
+    ctx: Context<'_>,
+    #[description = "Delay between messages (e.g. \"30s\"). Use \"0s\" to disable."]
+    delay: String,
+    #[description = "How long the slowmode should last (e.g. \"1h\"). Leave empty for permanent."]
+    duration: Option<String>,
+    #[description = "Channel to set the slowmode in"]
+    #[channel_types("Text")]
+    channel: Option<GuildChannel>,
+    #[description = "Reason for changing the slowmode"]
+    reason: Option<String>
+) -> Result<(), BotError> {
+    use crate::database::schema::slowmodes::dsl::*;
+
+    let db = ctx.data().db.clone();
+    let locales = ctx.data().localization_manager.clone();
+    let guild_lang = locales
+        .get_guild_language(db, ctx.guild_id().unwrap()).await.unwrap();
+
+    let current_channel = ctx.guild_channel().await.unwrap();
+
+    let mut channel = channel.unwrap_or(current_channel);
+
+    let new_delay = match parse_duration(&delay, guild_lang) {
+        Ok(Some(seconds)) => seconds,
+        Err(DurationError::Zero) => 0,
+        Ok(None) => {
+            send_reply(ctx, CreateReply::new().content(locales.get("commands.moderation.channel.error_invalid_delay", guild_lang, &[])).ephemeral(true)).await?;
+            return Ok(());
+        },
+        Err(why) => {
+            send_reply(ctx, CreateReply::new().content(why.localize(guild_lang, &locales)).ephemeral(true)).await?;
+            return Ok(());
+        }
+    };
+
+    if new_delay > MAX_SLOWMODE_DELAY {
+        send_reply(ctx, CreateReply::new().content(locales.get("commands.moderation.channel.error_delay_too_long", guild_lang, &[])).ephemeral(true)).await?;
+        return Ok(());
+    }
+
+    let guild_tz = fetch_guild_timezone(&ctx.data(), ctx.guild_id().unwrap()).await;
+    let revert_after = match duration.map(|d| parse_duration_or_date(&d, guild_lang, guild_tz)) {
+        Some(Ok(seconds)) => seconds,
+        Some(Err(why)) => {
+            send_reply(ctx, CreateReply::new().content(why.localize(guild_lang, &locales)).ephemeral(true)).await?;
+            return Ok(());
+        },
+        None => None,
+    };
+
+    let delay = format_duration(new_delay, guild_lang);
+    let duration = revert_after.map(|seconds| format_duration(seconds, guild_lang));
+
+    let current_delay = channel.rate_limit_per_user.map_or(0, u16::from);
+
+    channel.edit(ctx.http(), EditChannel::new().rate_limit_per_user(new_delay as u16)).await?;
+
+    let data = ctx.data();
+
+    let pending_revert = data.db.run(|conn| {
+        slowmodes
+            .filter(channel_id.eq(channel.id.get() as i64))
+            .select(Slowmodes::as_select())
+            .first::<Slowmodes>(conn)
+    }).await.ok();
+
+    if let Some(seconds) = revert_after {
+        // Keep the delay from before the first timed slowmode, so stacking them still restores the original value
+        let new_slowmode = Slowmodes {
+            channel_id: channel.id.get() as i64,
+            guild_id: ctx.guild_id().unwrap().get() as i64,
+            moderator_id: ctx.author().id.get() as i64,
+            previous_delay: pending_revert.map_or(current_delay as i32, |s| s.previous_delay),
+            end_date: Utc::now() + chrono::Duration::seconds(seconds as i64),
+        };
+
+        data.db.run(|conn| {
+            diesel::insert_into(slowmodes)
+                .values(&new_slowmode)
+                .on_conflict(channel_id)
+                .do_update()
+                .set((
+                    moderator_id.eq(new_slowmode.moderator_id),
+                    end_date.eq(new_slowmode.end_date),
+                ))
+                .execute(conn)
+        }).await?;
+    } else if pending_revert.is_some() {
+        data.db.run(|conn| {
+            diesel::delete(slowmodes.filter(channel_id.eq(channel.id.get() as i64))).execute(conn)
+        }).await?;
+    }
+
+    let content = if new_delay == 0 {
+        locales.get("commands.moderation.channel.reply_slowmode_disabled", guild_lang, &[
+            TranslationParam::from(channel.id.to_string()),
+        ])
+    } else {
+        locales.get("commands.moderation.channel.reply_slowmode", guild_lang, &[
+            TranslationParam::from(channel.id.to_string()),
+            TranslationParam::from(delay.clone()),
+            TranslationParam::from(duration.clone().unwrap_or_else(|| locales.get("commands.moderation.channel.permanently", guild_lang, &[]))),
+        ])
+    };
+
+    send_reply(ctx, CreateReply::new().content(content)).await?;
+
+    let data = ctx.data().clone();
+
+    let log_data = LogData {
+        data: Some(&*data),
+        ctx: Some(ctx.serenity_context()),
+        guild_id: Some(ctx.guild_id().unwrap().get()),
+        channel_id: Some(channel.id.get()),
+        moderator_id: Some(ctx.author().id),
+        delay: Some(delay),
+        duration,
+        ends_at: revert_after.map(|seconds| Utc::now() + chrono::Duration::seconds(seconds as i64)),
+        reason: reason.or(Option::from(locales.get("commands.moderation.channel.no_reason", guild_lang, &[]))),
+        ..LogData::default()
+    };
+
+    log_action(LogType::Slowmode, log_data).await?;
+
     Ok(())
 }
\ No newline at end of file
@@ -1,12 +1,25 @@
 
+use std::collections::HashSet;
 use std::path::Path;
 use std::time::Duration;
-use futures::stream::{self, StreamExt};
+use chrono::Utc;
+use lazy_static::lazy_static;
 use poise::{command, CreateReply, send_reply};
-use poise::serenity_prelude::{CreateAttachment, CreateChannel, CreateMessage, GetMessages, GuildChannel, Member, Role};
+use poise::serenity_prelude::{CreateAttachment, CreateChannel, CreateEmbed, CreateEmbedAuthor, CreateEmbedFooter, CreateMessage, EditChannel, EditWebhook, GetMessages, GuildChannel, Member, MessageId, Role, UserId};
+use regex::Regex;
 use crate::{BotError, Context};
-use crate::localization::manager::{TranslationParam, TranslationRef};
+use crate::localization::manager::{Language, LocalizationManager, TranslationParam, TranslationRef};
+use crate::database::models::PurgeTranscripts;
 use crate::modules::moderation::logs::{log_action, LogData, LogType};
+use crate::modules::moderation::transcripts::{render_html, render_text, save_transcript, transcript_attachments};
+use crate::util::color::BotColors;
+
+/// Discord refuses to bulk delete messages older than two weeks
+const BULK_DELETE_MAX_AGE_DAYS: i64 = 14;
+
+lazy_static! {
+    static ref LINK_REGEX: Regex = Regex::new(r"https?://\S+").unwrap();
+}
 
 #[command(slash_command, default_member_permissions = "MANAGE_MESSAGES", subcommands("channel", "messages"), guild_only)]
 pub async fn clear(_: Context<'_>) -> Result<(), BotError> {
@@ -20,13 +33,27 @@ pub async fn clear(_: Context<'_>) -> Result<(), BotError> {
 #[command(slash_command, default_member_permissions = "MANAGE_MESSAGES", guild_only)]
 pub async fn messages(
     ctx: Context<'_>,
-    #[description = "Number of messages to delete (1-100)"]
-    #[min = 1] #[max = 100]
-    amount: u8,
+    #[description = "Number of messages to search through (1-5000)"]
+    #[min = 1] #[max = 5000]
+    amount: u16,
     #[description = "Filter by user messages"]
     user: Option<Member>,
     #[description = "Filter by role messages"]
     role: Option<Role>,
+    #[description = "Only messages containing this text"]
+    contains: Option<String>,
+    #[description = "Only messages matching this regex"]
+    regex: Option<String>,
+    #[description = "Only messages sent by bots"]
+    bots: Option<bool>,
+    #[description = "Only messages with links"]
+    links: Option<bool>,
+    #[description = "Only messages with attachments or embeds"]
+    attachments: Option<bool>,
+    #[description = "Only messages sent before this message ID"]
+    before: Option<String>,
+    #[description = "Only messages sent after this message ID"]
+    after: Option<String>,
 ) -> Result<(), BotError> {
     let db = ctx.data().db.clone();
     let locales = ctx.data().localization_manager.clone();
@@ -36,32 +63,100 @@ pub async fn messages(
     let channel_id = ctx.channel_id();
     let guild_id = ctx.guild_id().unwrap();
 
+    let content_regex = match regex.as_deref().map(Regex::new) {
+        Some(Ok(re)) => Some(re),
+        Some(Err(_)) => {
+            send_reply(ctx, CreateReply::new().content(locales.get("commands.moderation.clear.error_invalid_regex", lang, &[])).ephemeral(true)).await?;
+            return Ok(());
+        },
+        None => None,
+    };
+
+    let (before, after) = match (parse_message_id(before.as_deref()), parse_message_id(after.as_deref())) {
+        (Ok(before), Ok(after)) => (before, after),
+        _ => {
+            send_reply(ctx, CreateReply::new().content(locales.get("commands.moderation.clear.error_invalid_message_id", lang, &[])).ephemeral(true)).await?;
+            return Ok(());
+        }
+    };
+
+    ctx.defer_ephemeral().await?;
+
+    let role_members: Option<HashSet<UserId>> = role.as_ref().map(|role| {
+        ctx.guild().unwrap().members.iter()
+            .filter(|member| member.roles.contains(&role.id))
+            .map(|member| member.user.id)
+            .collect()
+    });
+
+    let contains = contains.map(|c| c.to_lowercase());
+    let bulk_delete_cutoff = Utc::now() - chrono::Duration::days(BULK_DELETE_MAX_AGE_DAYS);
+
+    let mut filtered_messages = Vec::new();
+    let mut skipped_old = 0;
+    let mut reached_cutoff = false;
+    let mut remaining = amount as usize;
+    let mut last_id = before;
+
+    'pages: while remaining > 0 {
+        let mut request = GetMessages::new().limit(remaining.min(100) as u8);
+        if let Some(last_id) = last_id {
+            request = request.before(last_id);
+        }
+
+        let page = channel_id.messages(&ctx.http(), request).await?;
+        let page_len = page.len();
+
+        for msg in page {
+            last_id = Some(msg.id);
+            remaining -= 1;
 
+            if after.is_some_and(|after| msg.id <= after) {
+                break 'pages;
+            }
 
-    let messages = channel_id.messages(&ctx.http(), GetMessages::new().limit(amount)).await?;
+            let is_old = msg.timestamp.unix_timestamp() < bulk_delete_cutoff.timestamp();
+            reached_cutoff |= is_old;
 
-    let filtered_messages = stream::iter(messages)
-        .filter_map(|msg| async {
             let user_match = user
                 .as_ref()
                 .map_or(true, |member| member.user.id == msg.author.id);
 
-            let role_match = if let Some(role) = &role {
-                guild_id.member(&ctx.http(), msg.author.id).await
-                    .map(|member| member.roles.contains(&role.id))
-                    .unwrap_or(false)
-            } else {
-                true
-            };
-
-            if user_match && role_match {
-                Some(msg)
-            } else {
-                None
+            let role_match = role_members
+                .as_ref()
+                .map_or(true, |members| members.contains(&msg.author.id));
+
+            let contains_match = contains
+                .as_ref()
+                .map_or(true, |text| msg.content.to_lowercase().contains(text));
+
+            let regex_match = content_regex
+                .as_ref()
+                .map_or(true, |re| re.is_match(&msg.content));
+
+            let bots_match = !bots.unwrap_or(false) || msg.author.bot();
+
+            let links_match = !links.unwrap_or(false) || LINK_REGEX.is_match(&msg.content);
+
+            let attachments_match = !attachments.unwrap_or(false) || !msg.attachments.is_empty() || !msg.embeds.is_empty();
+
+            if !(user_match && role_match && contains_match && regex_match && bots_match && links_match && attachments_match) {
+                continue;
             }
-        })
-        .collect::<Vec<_>>()
-        .await;
+
+            if is_old {
+                skipped_old += 1;
+                continue;
+            }
+
+            filtered_messages.push(msg);
+        }
+
+        // Pages go from newest to oldest, the ones after this would only hold messages too old to delete
+        if reached_cutoff || page_len < 100 {
+            break;
+        }
+    }
 
     let mut messages_for_logs: Vec<String> = Vec::new();
     let mut total_length = 0;
@@ -82,7 +177,9 @@ pub async fn messages(
     let message_ids: Vec<_> = filtered_messages.iter().map(|m| m.id).collect();
 
     if !message_ids.is_empty() {
-        channel_id.delete_messages(ctx.http(), &message_ids, None).await?;
+        for chunk in message_ids.chunks(100) {
+            channel_id.delete_messages(ctx.http(), chunk, None).await?;
+        }
 
 
         let content = locales.get(
@@ -114,11 +211,23 @@ pub async fn messages(
         
         send_reply(ctx,
                    CreateReply::new()
-                       .content(content)
+                       .content(with_skipped_note(content, skipped_old, &locales, lang))
                        .ephemeral(true)
         ).await?;
 
         let data = ctx.data().clone();
+
+        let transcript = PurgeTranscripts {
+            guild_id: guild_id.get() as i64,
+            channel_id: channel_id.get() as i64,
+            moderator_id: ctx.author().id.get() as i64,
+            messages_deleted: message_ids.len() as i32,
+            created_at: Utc::now(),
+            transcript: render_text(&filtered_messages),
+            transcript_html: render_html(&filtered_messages),
+        };
+
+        let transcript_id = save_transcript(&data, &transcript).await?;
         
         let log_data = LogData {
             data: Some(&*data),
@@ -128,6 +237,8 @@ pub async fn messages(
             moderator_id: Some(ctx.author().id),
             messages_deleted: Some(message_ids.len() as u32),
             messages: Some(messages_for_logs),
+            transcript_id: Some(transcript_id),
+            attachments: Some(transcript_attachments(&transcript, transcript_id)),
             ..LogData::default()
         };
         
@@ -150,13 +261,34 @@ pub async fn messages(
         
         send_reply(ctx,
                    CreateReply::new()
-                       .content(content)
+                       .content(with_skipped_note(content, skipped_old, &locales, lang))
                        .ephemeral(true)
         ).await?;
     }
 
     Ok(())
 }
+
+fn parse_message_id(input: Option<&str>) -> Result<Option<MessageId>, BotError> {
+    match input {
+        Some(id) => match id.trim().parse::<u64>() {
+            Ok(id) if id != 0 => Ok(Some(MessageId::new(id))),
+            _ => Err(BotError::from("Invalid message ID")),
+        },
+        None => Ok(None),
+    }
+}
+
+fn with_skipped_note(content: String, skipped: usize, locales: &LocalizationManager, lang: Language) -> String {
+    if skipped == 0 {
+        return content;
+    }
+
+    format!("{}\n{}", content, locales.get("commands.moderation.clear.messages_skipped_old", lang, &[
+        TranslationParam::from(skipped.to_string())
+    ]))
+}
+
 /// Delete all messages by cloning the channel
 #[command(slash_command, default_member_permissions = "MANAGE_MESSAGES", guild_only)]
 pub async fn channel(
@@ -164,51 +296,135 @@ pub async fn channel(
     #[description = "Channel to clear"]
     #[channel_types("Text")]
     channel: Option<GuildChannel>,
+    #[description = "Repost pinned messages in the new channel as an archive"]
+    archive_pins: Option<bool>,
 ) -> Result<(), BotError> {
+    let db = ctx.data().db.clone();
+    let locales = ctx.data().localization_manager.clone();
+    let lang = locales
+        .get_guild_language(db, ctx.guild_id().unwrap()).await.unwrap();
+
     let channel = if let Some(channel) = channel {
         channel
     } else {
         ctx.guild_channel().await.unwrap()
     };
-    
-    let guild = ctx.guild().unwrap().clone();
-    let new_channel = guild.create_channel(ctx.http(), CreateChannel::new(channel.name)
-        .permissions(channel.permission_overwrites)
+
+    let mut not_preserved: Vec<String> = vec![
+        locales.get("commands.moderation.clear.channel_report_threads", lang, &[]),
+        locales.get("commands.moderation.clear.channel_report_invites", lang, &[]),
+    ];
+
+    let pins = if archive_pins.unwrap_or(false) {
+        channel.pins(ctx.http()).await.unwrap_or_else(|_| {
+            not_preserved.push(locales.get("commands.moderation.clear.channel_report_pins", lang, &[]));
+            Vec::new()
+        })
+    } else {
+        Vec::new()
+    };
+
+    let webhooks = channel.webhooks(ctx.http()).await.unwrap_or_else(|_| {
+        not_preserved.push(locales.get("commands.moderation.clear.channel_report_webhooks", lang, &[]));
+        Vec::new()
+    });
+
+    let mut builder = CreateChannel::new(channel.name.clone())
+        .permissions(channel.permission_overwrites.clone())
         .position(channel.position)
         .nsfw(channel.nsfw)
-        .topic(channel.topic.unwrap_or_default())
+        .topic(channel.topic.clone().unwrap_or_default())
         .category(channel.parent_id.unwrap_or_default())
-        .kind(channel.kind)).await;
-    ctx.guild_channel().await.unwrap().delete(ctx.http(), None).await?;
+        .rate_limit_per_user(channel.rate_limit_per_user.map_or(0, u16::from))
+        .kind(channel.kind);
 
-    if let Ok(ref channel) = new_channel {
-        let message = channel.send_message(ctx.http(), CreateMessage::new().content(format!("<@{}>", ctx.author().id)).add_file(CreateAttachment::file(&tokio::fs::File::open(Path::new("./src/images/clearall.gif")).await.unwrap(), "clearall.gif").await.unwrap()))
-            .await?;
-        
-        let http = ctx.serenity_context().clone().http;
+    if let Some(archive_duration) = channel.default_auto_archive_duration {
+        builder = builder.default_auto_archive_duration(archive_duration);
+    }
+
+    let guild = ctx.guild().unwrap().clone();
+    let mut new_channel = guild.create_channel(ctx.http(), builder).await?;
 
-        // Spawn a new task to delete the message after 5 seconds
-        tokio::spawn(async move {
-            tokio::time::sleep(Duration::from_secs(5)).await;
-            message.delete(&http, None).await.unwrap();
-        });
+    // Not part of the channel creation payload, so it has to be set afterwards
+    if let Some(thread_delay) = channel.default_thread_rate_limit_per_user.map(u16::from).filter(|d| *d > 0) {
+        if new_channel.edit(ctx.http(), EditChannel::new().default_thread_rate_limit_per_user(thread_delay)).await.is_err() {
+            not_preserved.push(locales.get("commands.moderation.clear.channel_report_thread_slowmode", lang, &[]));
+        }
     }
 
+    for mut webhook in webhooks {
+        if webhook.edit(ctx.http(), EditWebhook::new().channel_id(new_channel.id)).await.is_err() {
+            not_preserved.push(locales.get("commands.moderation.clear.channel_report_webhook", lang, &[
+                TranslationParam::from(webhook.name.as_deref().unwrap_or_default())
+            ]));
+        }
+    }
 
-    if let Ok(channel) = new_channel {
-        let data = ctx.data().clone();
-        
-        let log_data = LogData {
-            data: Some(&*data),
-            moderator_id: Some(ctx.author().id),
-            ctx: Some(ctx.serenity_context()),
-            guild_id: Some(guild.id.get()),
-            channel_id: Some(channel.id.get()),
-            ..LogData::default()
+    channel.delete(ctx.http(), None).await?;
+
+    let message = new_channel.send_message(ctx.http(), CreateMessage::new().content(format!("<@{}>", ctx.author().id)).add_file(CreateAttachment::file(&tokio::fs::File::open(Path::new("./src/images/clearall.gif")).await.unwrap(), "clearall.gif").await.unwrap()))
+        .await?;
+
+    let http = ctx.serenity_context().clone().http;
+
+    // Spawn a new task to delete the message after 5 seconds
+    tokio::spawn(async move {
+        tokio::time::sleep(Duration::from_secs(5)).await;
+        message.delete(&http, None).await.unwrap();
+    });
+
+    let mut archived_pins = 0;
+
+    // Pins come newest first, repost them in their original order
+    for pin in pins.iter().rev() {
+        let mut embed = CreateEmbed::new()
+            .color(BotColors::Default.color())
+            .author(CreateEmbedAuthor::new(pin.author.name.to_string()).icon_url(pin.author.face()))
+            .description(pin.content.to_string())
+            .timestamp(pin.timestamp)
+            .footer(CreateEmbedFooter::new(format!("ID: {}", pin.author.id)));
+
+        let attachments = pin.attachments.iter().map(|a| a.url.to_string()).collect::<Vec<_>>().join("\n");
+        if !attachments.is_empty() {
+            embed = embed.field(locales.get("commands.moderation.clear.channel_pin_attachments", lang, &[]), attachments, false);
+        }
+
+        let archived = match new_channel.send_message(ctx.http(), CreateMessage::new().embed(embed)).await {
+            Ok(archived) => archived.pin(ctx.http(), None).await.is_ok(),
+            Err(_) => false,
         };
-        
-        log_action(LogType::ClearChannel, log_data).await?;
+
+        if archived {
+            archived_pins += 1;
+        }
+    }
+
+    if archived_pins < pins.len() {
+        not_preserved.push(locales.get("commands.moderation.clear.channel_report_pins_partial", lang, &[
+            TranslationParam::from((pins.len() - archived_pins).to_string())
+        ]));
     }
 
+    let report = CreateEmbed::new()
+        .color(BotColors::Default.color())
+        .title(locales.get("commands.moderation.clear.channel_report_title", lang, &[]))
+        .description(not_preserved.iter().map(|item| format!("- {}", item)).collect::<Vec<_>>().join("\n"));
+
+    new_channel.send_message(ctx.http(), CreateMessage::new().embed(report)).await?;
+
+    let data = ctx.data().clone();
+
+    let log_data = LogData {
+        data: Some(&*data),
+        moderator_id: Some(ctx.author().id),
+        ctx: Some(ctx.serenity_context()),
+        guild_id: Some(guild.id.get()),
+        channel_id: Some(new_channel.id.get()),
+        not_preserved: Some(not_preserved),
+        ..LogData::default()
+    };
+
+    log_action(LogType::ClearChannel, log_data).await?;
+
     Ok(())
-}
\ No newline at end of file
+}
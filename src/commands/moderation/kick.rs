@@ -2,11 +2,13 @@ use diesel::associations::HasTable;
 use diesel::QueryDsl;
 use diesel_async::RunQueryDsl;
 use poise::{command, CreateReply, send_reply};
-use poise::serenity_prelude::{Member};
+use poise::serenity_prelude::{Attachment, Member};
 use crate::{BotError, Context};
 use crate::database::models::Cases;
 use crate::localization::manager::TranslationParam;
+use crate::modules::moderation::evidence::{archive_evidence, is_guild_message_link};
 use crate::modules::moderation::logs::{log_action, LogData, LogType};
+use crate::modules::moderation::presets::autocomplete_reason;
 
 /// Kick a user
 #[command(slash_command, default_member_permissions="ADMINISTRATOR", guild_only)]
@@ -15,7 +17,12 @@ pub async fn kick(
     #[description = "a user to kick"]
     user: Member,
     #[description = "reason for kicking the user"] #[rename = "reason"]
-    action_reason: Option<String>
+    #[autocomplete = "autocomplete_reason"]
+    action_reason: Option<String>,
+    #[description = "Screenshot or file backing up the kick"]
+    evidence: Option<Attachment>,
+    #[description = "Link to a message backing up the kick"]
+    evidence_link: Option<String>
 ) -> Result<(), BotError> {
     use crate::database::schema::cases::dsl::*;
 
@@ -35,6 +42,11 @@ pub async fn kick(
         return Ok(());
     }
 
+    if evidence_link.as_deref().is_some_and(|link| !is_guild_message_link(link, ctx.guild_id().unwrap())) {
+        send_reply(ctx, CreateReply::new().content(locales.get("commands.moderation.evidence.error_invalid_link", guild_lang, &[])).ephemeral(true)).await?;
+        return Ok(());
+    }
+
     let guild = ctx.guild().unwrap().clone();
     if guild.owner_id == user.user.id {
         send_reply(ctx, CreateReply::new().content(locales.get("commands.moderation.kick.error_user_owner", guild_lang, &[])).ephemeral(true)).await?;
@@ -81,6 +93,8 @@ pub async fn kick(
         created_at: chrono::Utc::now(),
         end_date: None,
         points: None,
+        revoked_at: None,
+        revoke_reason: None,
     };
     
     data.db.run(|conn| {
@@ -109,6 +123,8 @@ pub async fn kick(
     );
 
     send_reply(ctx, CreateReply::new().content(content).ephemeral(true)).await?;
+
+    let evidence = archive_evidence(ctx.serenity_context(), &data, ctx.guild_id().unwrap(), new_case_id, evidence.as_ref(), evidence_link.as_deref()).await?;
     
     let log_data = LogData {
         data: Some(&*data),
@@ -118,6 +134,7 @@ pub async fn kick(
         user_id: Some(user.user.id.get()),
         reason: Some(action_reason), 
         case_id: Some(new_case_id),
+        evidence: Some(evidence),
         ..Default::default()
     };
 
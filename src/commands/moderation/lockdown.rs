@@ -0,0 +1,118 @@
+use chrono::Utc;
+use poise::{command, CreateReply, send_reply};
+use crate::{BotError, Context};
+use crate::localization::manager::TranslationParam;
+use crate::modules::moderation::lockdown::{end_lockdown, is_locked_down, start_lockdown};
+use crate::modules::moderation::logs::{log_action, LogData, LogType};
+use crate::util::time::{fetch_guild_timezone, format_duration, parse_duration_or_date};
+
+#[command(slash_command, default_member_permissions="MANAGE_CHANNELS", subcommands("start", "end"), subcommand_required, guild_only)]
+pub async fn lockdown(_: Context<'_>) -> Result<(), BotError> { Ok(()) }
+
+/// Lock every text channel (or the configured set) at once
+#[command(slash_command, default_member_permissions="MANAGE_CHANNELS", guild_only)]
+pub async fn start(
+    ctx: Context<'_>,
+    #[description = "How long the lockdown should last (e.g. \"2h\"). Leave empty to end it manually."]
+    duration: Option<String>,
+    #[description = "Reason for the lockdown"]
+    reason: Option<String>
+) -> Result<(), BotError> {
+    let db = ctx.data().db.clone();
+    let locales = ctx.data().localization_manager.clone();
+    let guild_lang = locales
+        .get_guild_language(db, ctx.guild_id().unwrap()).await.unwrap();
+
+    let guild = ctx.guild_id().unwrap();
+
+    if is_locked_down(&ctx.data(), guild).await {
+        send_reply(ctx, CreateReply::new().content(locales.get("commands.moderation.lockdown.error_already_active", guild_lang, &[])).ephemeral(true)).await?;
+        return Ok(());
+    }
+
+    let guild_tz = fetch_guild_timezone(&ctx.data(), ctx.guild_id().unwrap()).await;
+    let seconds = match duration.map(|d| parse_duration_or_date(&d, guild_lang, guild_tz)) {
+        Some(Ok(seconds)) => seconds,
+        Some(Err(why)) => {
+            send_reply(ctx, CreateReply::new().content(why.localize(guild_lang, &locales)).ephemeral(true)).await?;
+            return Ok(());
+        },
+        None => None,
+    };
+
+    let end_date = seconds.map(|seconds| Utc::now() + chrono::Duration::seconds(seconds as i64));
+    let duration = seconds.map(|seconds| format_duration(seconds, guild_lang));
+
+    ctx.defer().await?;
+
+    let reason = reason.unwrap_or_else(|| locales.get("commands.moderation.lockdown.no_reason", guild_lang, &[]));
+
+    let locked = start_lockdown(ctx.serenity_context(), &ctx.data(), guild, ctx.author().id, Some(reason.clone()), end_date).await?;
+
+    ctx.say(locales.get("commands.moderation.lockdown.reply_started", guild_lang, &[
+        TranslationParam::from(locked.to_string()),
+        TranslationParam::from(duration.clone().unwrap_or_else(|| locales.get("commands.moderation.lockdown.until_ended", guild_lang, &[]))),
+    ])).await?;
+
+    let data = ctx.data().clone();
+
+    let log_data = LogData {
+        data: Some(&*data),
+        ctx: Some(ctx.serenity_context()),
+        guild_id: Some(guild.get()),
+        moderator_id: Some(ctx.author().id),
+        channels_locked: Some(locked as u32),
+        duration,
+        ends_at: end_date,
+        reason: Some(reason),
+        ..LogData::default()
+    };
+
+    log_action(LogType::Lockdown, log_data).await?;
+
+    Ok(())
+}
+
+/// End the lockdown and restore every channel's previous permissions
+#[command(slash_command, default_member_permissions="MANAGE_CHANNELS", guild_only)]
+pub async fn end(
+    ctx: Context<'_>,
+    #[description = "Reason for ending the lockdown"]
+    reason: Option<String>
+) -> Result<(), BotError> {
+    let db = ctx.data().db.clone();
+    let locales = ctx.data().localization_manager.clone();
+    let guild_lang = locales
+        .get_guild_language(db, ctx.guild_id().unwrap()).await.unwrap();
+
+    let guild = ctx.guild_id().unwrap();
+
+    if !is_locked_down(&ctx.data(), guild).await {
+        send_reply(ctx, CreateReply::new().content(locales.get("commands.moderation.lockdown.error_not_active", guild_lang, &[])).ephemeral(true)).await?;
+        return Ok(());
+    }
+
+    ctx.defer().await?;
+
+    let restored = end_lockdown(ctx.serenity_context(), &ctx.data(), guild).await?;
+
+    ctx.say(locales.get("commands.moderation.lockdown.reply_ended", guild_lang, &[
+        TranslationParam::from(restored.to_string()),
+    ])).await?;
+
+    let data = ctx.data().clone();
+
+    let log_data = LogData {
+        data: Some(&*data),
+        ctx: Some(ctx.serenity_context()),
+        guild_id: Some(guild.get()),
+        moderator_id: Some(ctx.author().id),
+        channels_locked: Some(restored as u32),
+        reason: reason.or(Option::from(locales.get("commands.moderation.lockdown.no_reason", guild_lang, &[]))),
+        ..LogData::default()
+    };
+
+    log_action(LogType::LockdownEnd, log_data).await?;
+
+    Ok(())
+}
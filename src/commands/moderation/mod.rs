@@ -1,8 +1,19 @@
 pub mod ban;
+pub mod case;
 pub mod cases;
 pub mod channel;
 pub mod clear;
 pub mod kick;
+pub mod lockdown;
+pub mod modinfo;
+pub mod modstats;
 pub mod mute;
+pub mod nick;
+pub mod note;
+pub mod quarantine;
+pub mod release;
+pub mod role;
+pub mod undo;
 pub mod unmute;
+pub mod voice;
 pub mod warn;
\ No newline at end of file
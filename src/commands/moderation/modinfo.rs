@@ -0,0 +1,153 @@
+use std::collections::BTreeMap;
+use chrono::Utc;
+use diesel::prelude::*;
+use diesel_async::RunQueryDsl;
+use poise::{command, CreateReply, send_reply};
+use poise::serenity_prelude::{CreateEmbed, CreateEmbedAuthor, Timestamp, User};
+use crate::{BotError, Context};
+use crate::database::models::Cases;
+use crate::commands::moderation::note::NOTE_CASE_TYPE;
+use crate::localization::manager::TranslationParam;
+use crate::modules::moderation::cases::active_points;
+use crate::modules::moderation::quarantine::fetch_quarantine;
+use crate::util::color::BotColors;
+use crate::util::timestamp::{Format, TimestampExt};
+
+const RECENT_CASES: usize = 5;
+
+const RECENT_NOTES: usize = 3;
+
+/// Keeps the notes field under Discord's 1024 character limit
+const NOTE_PREVIEW_LENGTH: usize = 200;
+
+/// Same for the recent cases field, which lists more entries with a longer prefix
+const REASON_PREVIEW_LENGTH: usize = 140;
+
+/// Show a summary of a user's moderation history
+#[command(slash_command, default_member_permissions="MODERATE_MEMBERS", guild_only)]
+pub async fn modinfo(
+    ctx: Context<'_>,
+    #[description = "The user to look up"]
+    user: User
+) -> Result<(), BotError> {
+    use crate::database::schema::cases::dsl::*;
+
+    let db = ctx.data().db.clone();
+    let locales = ctx.data().localization_manager.clone();
+    let guild_lang = locales
+        .get_guild_language(db, ctx.guild_id().unwrap()).await.unwrap();
+
+    let guild = ctx.guild_id().unwrap();
+    let data = ctx.data();
+
+    let mut user_cases = data.db.run(|conn| {
+        cases
+            .filter(guild_id.eq(guild.get() as i64))
+            .filter(user_id.eq(user.id.get() as i64))
+            .select(Cases::as_select())
+            .load::<Cases>(conn)
+    }).await?;
+
+    user_cases.sort_by(|a, b| b.created_at.cmp(&a.created_at));
+
+    let now = Utc::now();
+
+    let active_points = active_points(&data, guild, user.id).await?;
+
+    let format_end = |case: &Cases| case.end_date.map_or(
+        locales.get("commands.moderation.modinfo.permanent", guild_lang, &[]),
+        |end| Timestamp::from(end).to_discord_timestamp(Format::Relative)
+    );
+
+    // Mutes are Discord timeouts and always have an end date, bans without one are permanent
+    let active_mute = user_cases.iter()
+        .find(|case| case.case_type == "MUTE" && case.revoked_at.is_none() && case.end_date.is_some_and(|end| end > now))
+        .map(format_end);
+
+    let active_ban = user_cases.iter()
+        .find(|case| case.case_type == "BAN" && case.revoked_at.is_none() && case.end_date.is_none_or(|end| end > now))
+        .map(format_end);
+
+    let mut counts: BTreeMap<&str, u32> = BTreeMap::new();
+    for case in &user_cases {
+        *counts.entry(case.case_type.as_str()).or_default() += 1;
+    }
+
+    let none = locales.get("commands.moderation.modinfo.none", guild_lang, &[]);
+
+    let counts_text = if counts.is_empty() {
+        none.clone()
+    } else {
+        counts.iter().map(|(kind, count)| format!("`{}`: {}", kind, count)).collect::<Vec<_>>().join("\n")
+    };
+
+    let (notes, actions): (Vec<&Cases>, Vec<&Cases>) = user_cases.iter().partition(|case| case.case_type == NOTE_CASE_TYPE);
+
+    let recent_text = if actions.is_empty() {
+        none.clone()
+    } else {
+        actions.iter()
+            .take(RECENT_CASES)
+            .map(|case| format!(
+                "`#{}` **{}** — {} ({})",
+                case.case_id,
+                case.case_type,
+                case.reason.as_deref().map_or_else(
+                    || locales.get("commands.moderation.modinfo.no_reason", guild_lang, &[]),
+                    |case_reason| case_reason.chars().take(REASON_PREVIEW_LENGTH).collect()
+                ),
+                Timestamp::from(case.created_at).to_discord_timestamp(Format::ShortDate)
+            ))
+            .collect::<Vec<_>>()
+            .join("\n")
+    };
+
+    let notes_text = if notes.is_empty() {
+        none.clone()
+    } else {
+        notes.iter()
+            .take(RECENT_NOTES)
+            .map(|note| format!(
+                "`#{}` {} — <@{}> ({})",
+                note.case_id,
+                note.reason.as_deref().unwrap_or_default().chars().take(NOTE_PREVIEW_LENGTH).collect::<String>(),
+                note.moderator_id,
+                Timestamp::from(note.created_at).to_discord_timestamp(Format::ShortDate)
+            ))
+            .collect::<Vec<_>>()
+            .join("\n")
+    };
+
+    let joined_at = guild.member(ctx.http(), user.id).await.ok()
+        .and_then(|member| member.joined_at)
+        .map_or(locales.get("commands.moderation.modinfo.not_member", guild_lang, &[]), |joined| joined.to_discord_timestamp(Format::LongDate));
+
+    // There's no watchlist yet, quarantine is the only flag a member can carry
+    let quarantined = fetch_quarantine(&data, guild, user.id).await.is_some();
+
+    let flags = if quarantined {
+        locales.get("commands.moderation.modinfo.flag_quarantined", guild_lang, &[])
+    } else {
+        none.clone()
+    };
+
+    let embed = CreateEmbed::new().color(BotColors::Default.color())
+        .author(CreateEmbedAuthor::new(locales.get("commands.moderation.modinfo.title", guild_lang, &[
+            TranslationParam::from(user.global_name.clone().map_or(user.name.to_string(), |name| name.to_string()))
+        ])).icon_url(user.avatar_url().unwrap_or_default()))
+        .field(locales.get("commands.moderation.modinfo.active_points", guild_lang, &[]), active_points.to_string(), true)
+        .field(locales.get("commands.moderation.modinfo.active_mute", guild_lang, &[]), active_mute.unwrap_or(none.clone()), true)
+        .field(locales.get("commands.moderation.modinfo.active_ban", guild_lang, &[]), active_ban.unwrap_or(none.clone()), true)
+        .field(locales.get("commands.moderation.modinfo.account_created", guild_lang, &[]), user.id.created_at().to_discord_timestamp(Format::LongDate), true)
+        .field(locales.get("commands.moderation.modinfo.joined", guild_lang, &[]), joined_at, true)
+        .field(locales.get("commands.moderation.modinfo.flags", guild_lang, &[]), flags, true)
+        .field(locales.get("commands.moderation.modinfo.case_counts", guild_lang, &[]), counts_text, false)
+        .field(locales.get("commands.moderation.modinfo.recent_cases", guild_lang, &[]), recent_text, false)
+        .field(locales.get("commands.moderation.modinfo.notes", guild_lang, &[
+            TranslationParam::from(notes.len().to_string())
+        ]), notes_text, false);
+
+    send_reply(ctx, CreateReply::new().embed(embed).ephemeral(true)).await?;
+
+    Ok(())
+}
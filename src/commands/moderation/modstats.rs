@@ -0,0 +1,152 @@
+use std::collections::BTreeMap;
+use chrono::{Duration, Utc};
+use diesel::prelude::*;
+use diesel_async::RunQueryDsl;
+use poise::{command, CreateReply, send_reply};
+use poise::serenity_prelude::{CreateAttachment, CreateEmbed, CreateEmbedAuthor, User};
+use serde_json::json;
+use crate::{BotError, Context};
+use crate::localization::manager::TranslationParam;
+use crate::util::color::BotColors;
+
+/// Windows the counts are computed over, in days
+const PERIODS: [i64; 3] = [7, 30, 90];
+
+const LEADERBOARD_SIZE: usize = 10;
+
+/// Case counts per period, in the same order as [`PERIODS`]
+type Counts = [u32; 3];
+
+/// Per moderator, per case type counts
+type ModeratorStats = BTreeMap<i64, BTreeMap<String, Counts>>;
+
+fn total(by_type: &BTreeMap<String, Counts>) -> Counts {
+    by_type.values().fold([0; 3], |mut acc, counts| {
+        for (sum, count) in acc.iter_mut().zip(counts) {
+            *sum += count;
+        }
+        acc
+    })
+}
+
+fn format_counts(counts: &Counts) -> String {
+    PERIODS.iter().zip(counts)
+        .map(|(days, count)| format!("{}d: **{}**", days, count))
+        .collect::<Vec<_>>()
+        .join(" · ")
+}
+
+fn stats_to_json(guild: u64, stats: &ModeratorStats) -> serde_json::Value {
+    let period_map = |counts: &Counts| PERIODS.iter().zip(counts)
+        .map(|(days, count)| (format!("{}d", days), json!(count)))
+        .collect::<serde_json::Map<_, _>>();
+
+    json!({
+        "guild_id": guild.to_string(),
+        "generated_at": Utc::now().to_rfc3339(),
+        "periods": PERIODS,
+        "moderators": stats.iter().map(|(moderator, by_type)| json!({
+            "moderator_id": moderator.to_string(),
+            "total": period_map(&total(by_type)),
+            "by_type": by_type.iter().map(|(kind, counts)| (kind.clone(), json!(period_map(counts)))).collect::<serde_json::Map<_, _>>(),
+        })).collect::<Vec<_>>(),
+    })
+}
+
+/// Show case counts per moderator over the last 7, 30 and 90 days
+#[command(slash_command, default_member_permissions="MODERATE_MEMBERS", guild_only)]
+pub async fn modstats(
+    ctx: Context<'_>,
+    #[description = "Only show this moderator. Leave empty for the server leaderboard."]
+    moderator: Option<User>,
+    #[description = "Send the statistics as a JSON file"]
+    json: Option<bool>
+) -> Result<(), BotError> {
+    use crate::database::schema::cases::dsl::*;
+
+    let db = ctx.data().db.clone();
+    let locales = ctx.data().localization_manager.clone();
+    let guild_lang = locales
+        .get_guild_language(db, ctx.guild_id().unwrap()).await.unwrap();
+
+    let guild = ctx.guild_id().unwrap();
+    let now = Utc::now();
+    let oldest = now - Duration::days(PERIODS[PERIODS.len() - 1]);
+
+    let mut query = cases
+        .filter(guild_id.eq(guild.get() as i64))
+        .filter(created_at.gt(oldest))
+        .select((moderator_id, case_type, created_at))
+        .into_boxed();
+
+    if let Some(ref moderator) = moderator {
+        query = query.filter(moderator_id.eq(moderator.id.get() as i64));
+    }
+
+    let rows = ctx.data().db.run(|conn| {
+        query.load::<(i64, String, chrono::DateTime<Utc>)>(conn)
+    }).await?;
+
+    let mut stats: ModeratorStats = BTreeMap::new();
+
+    for (moderator, kind, date) in rows {
+        let counts = stats.entry(moderator).or_default().entry(kind).or_default();
+
+        for (count, days) in counts.iter_mut().zip(PERIODS) {
+            if date > now - Duration::days(days) {
+                *count += 1;
+            }
+        }
+    }
+
+    if stats.is_empty() {
+        send_reply(ctx, CreateReply::new().content(locales.get("commands.moderation.modstats.error_no_cases", guild_lang, &[])).ephemeral(true)).await?;
+        return Ok(());
+    }
+
+    if json.unwrap_or(false) {
+        let file = serde_json::to_string_pretty(&stats_to_json(guild.get(), &stats))?;
+
+        send_reply(ctx, CreateReply::new()
+            .attachment(CreateAttachment::bytes(file.into_bytes(), format!("modstats-{}.json", guild.get())))
+            .ephemeral(true)
+        ).await?;
+        return Ok(());
+    }
+
+    let embed = if let Some(moderator) = moderator {
+        let by_type = &stats[&(moderator.id.get() as i64)];
+
+        let mut embed = CreateEmbed::new().color(BotColors::Default.color())
+            .author(CreateEmbedAuthor::new(locales.get("commands.moderation.modstats.title_moderator", guild_lang, &[
+                TranslationParam::from(moderator.global_name.clone().map_or(moderator.name.to_string(), |name| name.to_string()))
+            ])).icon_url(moderator.avatar_url().unwrap_or_default()))
+            .description(format!("{}: {}", locales.get("commands.moderation.modstats.total", guild_lang, &[]), format_counts(&total(by_type))));
+
+        for (kind, counts) in by_type {
+            embed = embed.field(kind.clone(), format_counts(counts), false);
+        }
+
+        embed
+    } else {
+        let mut leaderboard: Vec<(&i64, Counts)> = stats.iter().map(|(moderator, by_type)| (moderator, total(by_type))).collect();
+
+        // Ranked by the 30 day window, the longer one breaks ties
+        leaderboard.sort_by(|a, b| b.1[1].cmp(&a.1[1]).then(b.1[2].cmp(&a.1[2])));
+
+        let description = leaderboard.iter()
+            .take(LEADERBOARD_SIZE)
+            .enumerate()
+            .map(|(rank, (moderator, counts))| format!("`#{}` <@{}> — {}", rank + 1, moderator, format_counts(counts)))
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        CreateEmbed::new().color(BotColors::Default.color())
+            .title(locales.get("commands.moderation.modstats.title_leaderboard", guild_lang, &[]))
+            .description(description)
+    };
+
+    send_reply(ctx, CreateReply::new().embed(embed).ephemeral(true)).await?;
+
+    Ok(())
+}
@@ -2,13 +2,15 @@ use chrono::{DateTime, Utc};
 
 use diesel_async::RunQueryDsl;
 use poise::{command, CreateReply, send_reply};
-use poise::serenity_prelude::{EditMember, Member};
+use poise::serenity_prelude::{Attachment, EditMember, Member};
 use diesel::prelude::*;
 use crate::{BotError, Context};
 use crate::database::models::Cases;
 use crate::localization::manager::TranslationParam;
+use crate::modules::moderation::evidence::{archive_evidence, is_guild_message_link};
 use crate::modules::moderation::logs::{log_action, LogData, LogType};
-use crate::util::time::{date_after, parse_to_time};
+use crate::modules::moderation::presets::{autocomplete_reason, find_preset};
+use crate::util::time::{date_after, fetch_guild_timezone, format_duration, parse_duration_or_date};
 
 /// Mute a user
 #[command(slash_command, default_member_permissions="ADMINISTRATOR", guild_only)]
@@ -16,10 +18,15 @@ pub async fn mute(
     ctx: Context<'_>,
     #[description = "a user to mute"]
     mut user: Member,
-    #[description = "time to mute the user (no more than 28 days)"]
-    duration: String,
+    #[description = "time to mute the user (no more than 28 days). Defaults to the reason preset's duration."]
+    duration: Option<String>,
     #[description = "reason for muting the user"] #[rename = "reason"]
-    action_reason: Option<String>
+    #[autocomplete = "autocomplete_reason"]
+    action_reason: Option<String>,
+    #[description = "Screenshot or file backing up the mute"]
+    evidence: Option<Attachment>,
+    #[description = "Link to a message backing up the mute"]
+    evidence_link: Option<String>
 ) -> Result<(), BotError> {
     use crate::database::schema::cases::dsl::*;
     
@@ -51,6 +58,11 @@ pub async fn mute(
         return Ok(());
     }
 
+    if evidence_link.as_deref().is_some_and(|link| !is_guild_message_link(link, guild.id)) {
+        send_reply(ctx, CreateReply::new().content(locales.get("commands.moderation.evidence.error_invalid_link", guild_lang, &[])).ephemeral(true)).await?;
+        return Ok(());
+    }
+
     let author_highest_role_position = guild.member_highest_role(&ctx.author_member().await.unwrap()).map(|r| r.position).unwrap_or(0);
 
     let user_highest_role_position = guild.member_highest_role(&user).map(|r| r.position).unwrap_or(0);
@@ -67,14 +79,30 @@ pub async fn mute(
         return Ok(());
     }
 
-    if parse_to_time(duration.clone()).is_none() {
-        send_reply(ctx, CreateReply::new().content(locales.get("commands.moderation.mute.error_invalid_time", guild_lang, &[])).ephemeral(true)).await?;
+    let preset = find_preset(&ctx.data(), guild.id, action_reason.as_deref()).await;
+
+    let Some(duration) = duration.or(preset.and_then(|preset| preset.duration)) else {
+        send_reply(ctx, CreateReply::new().content(locales.get("commands.moderation.mute.error_no_duration", guild_lang, &[])).ephemeral(true)).await?;
         return Ok(());
-    }
+    };
+
+    let guild_tz = fetch_guild_timezone(&ctx.data(), ctx.guild_id().unwrap()).await;
+    let seconds = match parse_duration_or_date(&duration, guild_lang, guild_tz) {
+        Ok(Some(seconds)) => seconds,
+        // Discord timeouts can't be permanent
+        Ok(None) => {
+            send_reply(ctx, CreateReply::new().content(locales.get("commands.moderation.mute.error_permanent", guild_lang, &[])).ephemeral(true)).await?;
+            return Ok(());
+        },
+        Err(why) => {
+            send_reply(ctx, CreateReply::new().content(why.localize(guild_lang, &locales)).ephemeral(true)).await?;
+            return Ok(());
+        }
+    };
 
-    let date = date_after(parse_to_time(duration.clone()).unwrap());
+    let date = date_after(seconds);
 
-    if date.timestamp() - Utc::now().timestamp() > 2419200 {
+    if seconds > 2419200 {
         send_reply(ctx, CreateReply::new().content(locales.get("commands.moderation.mute.error_time_too_long", guild_lang, &[])).ephemeral(true)).await?;
         return Ok(());
     }
@@ -110,9 +138,8 @@ pub async fn mute(
             .first::<Option<i32>>(conn)
     }).await?.unwrap_or(0) + 1;
     
-    let expires_at: Option<DateTime<Utc>> = parse_to_time(duration.clone()).map(|d| {
-        Utc::now() + chrono::Duration::seconds(d as i64)
-    });
+    let expires_at: Option<DateTime<Utc>> = Some(Utc::now() + chrono::Duration::seconds(seconds as i64));
+    let duration = format_duration(seconds, guild_lang);
     
     
     let new_case: Cases = Cases {
@@ -124,7 +151,9 @@ pub async fn mute(
         reason: action_reason.clone(),
         created_at: Utc::now(),
         end_date: Some(expires_at.unwrap()).or(None),
-        points: None
+        points: None,
+        revoked_at: None,
+        revoke_reason: None
     };
     
     data.db.run(|conn| {
@@ -143,6 +172,8 @@ pub async fn mute(
 
     let data = ctx.data().clone();
 
+    let evidence = archive_evidence(ctx.serenity_context(), &data, guild.id, new_case_id, evidence.as_ref(), evidence_link.as_deref()).await?;
+
     let log_data = LogData {
         data: Some(&*data),
         ctx: Some(ctx.serenity_context()),
@@ -150,8 +181,10 @@ pub async fn mute(
         user_id: Some(user.user.id.get()),
         moderator_id: Some(ctx.author().id),
         duration: Some(duration),
+        ends_at: expires_at,
         reason: action_reason.or(None), 
         case_id: Some(new_case_id),
+        evidence: Some(evidence),
         ..LogData::default()
     };
 
@@ -0,0 +1,153 @@
+use poise::{command, CreateReply, send_reply};
+use poise::serenity_prelude::{EditMember, Member};
+use crate::{BotError, Context};
+use crate::localization::manager::TranslationParam;
+use crate::modules::moderation::logs::{log_action, LogData, LogType};
+
+#[command(slash_command, default_member_permissions="MANAGE_NICKNAMES", subcommands("set", "reset"), subcommand_required, guild_only)]
+pub async fn nick(_: Context<'_>) -> Result<(), BotError> { Ok(()) }
+
+/// Checks shared by both subcommands, replies with the error and returns `false` if the member can't be renamed
+async fn can_rename(ctx: Context<'_>, user: &Member) -> Result<bool, BotError> {
+    let db = ctx.data().db.clone();
+    let locales = ctx.data().localization_manager.clone();
+    let guild_lang = locales
+        .get_guild_language(db, ctx.guild_id().unwrap()).await.unwrap();
+
+    let guild = ctx.guild().unwrap().clone();
+
+    if guild.owner_id == user.user.id {
+        send_reply(ctx, CreateReply::new().content(locales.get("commands.moderation.nick.error_user_owner", guild_lang, &[])).ephemeral(true)).await?;
+        return Ok(false);
+    }
+
+    let author_highest_role_position = guild.member_highest_role(&ctx.author_member().await.unwrap()).map(|r| r.position).unwrap_or(0);
+
+    let user_highest_role_position = guild.member_highest_role(user).map(|r| r.position).unwrap_or(0);
+
+    let bot_highest_role_position = guild.member_highest_role(&guild.id.member(ctx.http(), ctx.http().get_current_user().await.unwrap().id).await.unwrap()).map(|r| r.position).unwrap_or(0);
+
+    if user_highest_role_position >= bot_highest_role_position {
+        send_reply(ctx, CreateReply::new().content(locales.get("commands.moderation.nick.error_user_higher_role", guild_lang, &[])).ephemeral(true)).await?;
+        return Ok(false);
+    }
+
+    if guild.owner_id != ctx.author().id && user.user.id != ctx.author().id && author_highest_role_position <= user_highest_role_position {
+        send_reply(ctx, CreateReply::new().content(locales.get("commands.moderation.nick.error_user_higher_role", guild_lang, &[])).ephemeral(true)).await?;
+        return Ok(false);
+    }
+
+    Ok(true)
+}
+
+/// Change a member's nickname
+#[command(slash_command, default_member_permissions="MANAGE_NICKNAMES", guild_only)]
+pub async fn set(
+    ctx: Context<'_>,
+    #[description = "Member to rename"]
+    user: Member,
+    #[description = "The new nickname"]
+    #[max_length = 32]
+    nickname: String,
+    #[description = "Reason for changing the nickname"]
+    reason: Option<String>
+) -> Result<(), BotError> {
+    let db = ctx.data().db.clone();
+    let locales = ctx.data().localization_manager.clone();
+    let guild_lang = locales
+        .get_guild_language(db, ctx.guild_id().unwrap()).await.unwrap();
+
+    if !can_rename(ctx, &user).await? {
+        return Ok(());
+    }
+
+    let old_nickname = user.display_name().to_string();
+
+    let mut builder = EditMember::new().nickname(nickname.clone());
+
+    if let Some(ref reason) = reason {
+        builder = builder.audit_log_reason(reason);
+    }
+
+    ctx.guild_id().unwrap().edit_member(ctx.http(), user.user.id, builder).await?;
+
+    send_reply(ctx, CreateReply::new().content(locales.get("commands.moderation.nick.reply_set", guild_lang, &[
+        TranslationParam::from(user.user.tag()),
+        TranslationParam::from(nickname.clone()),
+    ])).ephemeral(true)).await?;
+
+    let data = ctx.data().clone();
+
+    let log_data = LogData {
+        data: Some(&*data),
+        ctx: Some(ctx.serenity_context()),
+        guild_id: Some(ctx.guild_id().unwrap().get()),
+        user_id: Some(user.user.id.get()),
+        moderator_id: Some(ctx.author().id),
+        old_nickname: Some(old_nickname),
+        nickname: Some(nickname),
+        reason: reason.or(Option::from(locales.get("commands.moderation.nick.no_reason", guild_lang, &[]))),
+        ..LogData::default()
+    };
+
+    log_action(LogType::Nickname, log_data).await?;
+
+    Ok(())
+}
+
+/// Remove a member's nickname
+#[command(slash_command, default_member_permissions="MANAGE_NICKNAMES", guild_only)]
+pub async fn reset(
+    ctx: Context<'_>,
+    #[description = "Member whose nickname to remove"]
+    user: Member,
+    #[description = "Reason for removing the nickname"]
+    reason: Option<String>
+) -> Result<(), BotError> {
+    let db = ctx.data().db.clone();
+    let locales = ctx.data().localization_manager.clone();
+    let guild_lang = locales
+        .get_guild_language(db, ctx.guild_id().unwrap()).await.unwrap();
+
+    if user.nick.is_none() {
+        send_reply(ctx, CreateReply::new().content(locales.get("commands.moderation.nick.error_no_nickname", guild_lang, &[])).ephemeral(true)).await?;
+        return Ok(());
+    }
+
+    if !can_rename(ctx, &user).await? {
+        return Ok(());
+    }
+
+    let old_nickname = user.display_name().to_string();
+
+    // An empty nickname makes Discord fall back to the username
+    let mut builder = EditMember::new().nickname("");
+
+    if let Some(ref reason) = reason {
+        builder = builder.audit_log_reason(reason);
+    }
+
+    ctx.guild_id().unwrap().edit_member(ctx.http(), user.user.id, builder).await?;
+
+    send_reply(ctx, CreateReply::new().content(locales.get("commands.moderation.nick.reply_reset", guild_lang, &[
+        TranslationParam::from(user.user.tag()),
+    ])).ephemeral(true)).await?;
+
+    let data = ctx.data().clone();
+
+    let log_data = LogData {
+        data: Some(&*data),
+        ctx: Some(ctx.serenity_context()),
+        guild_id: Some(ctx.guild_id().unwrap().get()),
+        user_id: Some(user.user.id.get()),
+        moderator_id: Some(ctx.author().id),
+        old_nickname: Some(old_nickname),
+        nickname: Some(user.user.name.to_string()),
+        reason: reason.or(Option::from(locales.get("commands.moderation.nick.no_reason", guild_lang, &[]))),
+        ..LogData::default()
+    };
+
+    log_action(LogType::Nickname, log_data).await?;
+
+    Ok(())
+}
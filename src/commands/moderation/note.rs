@@ -0,0 +1,194 @@
+use chrono::Utc;
+use diesel::prelude::*;
+use diesel_async::RunQueryDsl;
+use poise::{command, CreateReply, send_reply};
+use poise::serenity_prelude::{CreateEmbed, CreateEmbedAuthor, CreateEmbedFooter, Timestamp, User};
+use crate::{BotError, Context};
+use crate::database::models::Cases;
+use crate::localization::manager::TranslationParam;
+use crate::modules::moderation::cases::create_case;
+use crate::modules::moderation::logs::{log_action, LogData, LogType};
+use crate::util::color::BotColors;
+use crate::util::interaction::{paginate_entries, send_paginated, DEFAULT_PAGE_SIZE};
+use crate::util::timestamp::{Format, TimestampExt};
+
+/// Notes are stored as cases of this type, they never carry points or an end date
+pub const NOTE_CASE_TYPE: &str = "NOTE";
+
+#[command(slash_command, default_member_permissions="MODERATE_MEMBERS", subcommands("add", "list", "remove"), subcommand_required, guild_only)]
+pub async fn note(_: Context<'_>) -> Result<(), BotError> { Ok(()) }
+
+/// Add a moderator-only note to a user
+#[command(slash_command, default_member_permissions="MODERATE_MEMBERS", guild_only)]
+pub async fn add(
+    ctx: Context<'_>,
+    #[description = "The user the note is about"]
+    user: User,
+    #[description = "The note, only visible to moderators"]
+    #[max_length = 1000]
+    content: String
+) -> Result<(), BotError> {
+    let db = ctx.data().db.clone();
+    let locales = ctx.data().localization_manager.clone();
+    let guild_lang = locales
+        .get_guild_language(db, ctx.guild_id().unwrap()).await.unwrap();
+
+    let content = content.trim().to_string();
+
+    if content.is_empty() {
+        send_reply(ctx, CreateReply::new().content(locales.get("commands.moderation.note.error_invalid_content", guild_lang, &[])).ephemeral(true)).await?;
+        return Ok(());
+    }
+
+    let data = ctx.data().clone();
+
+    let new_case_id = create_case(&data, Cases {
+        guild_id: ctx.guild_id().unwrap().get() as i64,
+        user_id: user.id.get() as i64,
+        moderator_id: ctx.author().id.get() as i64,
+        case_id: 0,
+        case_type: NOTE_CASE_TYPE.to_string(),
+        reason: Some(content.clone()),
+        created_at: Utc::now(),
+        end_date: None,
+        points: None,
+        revoked_at: None,
+        revoke_reason: None
+    }).await?;
+
+    send_reply(ctx, CreateReply::new().content(locales.get("commands.moderation.note.reply_added", guild_lang, &[
+        TranslationParam::from(user.tag()),
+        TranslationParam::from(new_case_id.to_string()),
+    ])).ephemeral(true)).await?;
+
+    let log_data = LogData {
+        data: Some(&*data),
+        ctx: Some(ctx.serenity_context()),
+        guild_id: Some(ctx.guild_id().unwrap().get()),
+        user_id: Some(user.id.get()),
+        moderator_id: Some(ctx.author().id),
+        reason: Some(content),
+        case_id: Some(new_case_id),
+        ..LogData::default()
+    };
+
+    log_action(LogType::Note, log_data).await?;
+
+    Ok(())
+}
+
+/// List the notes on a user
+#[command(slash_command, default_member_permissions="MODERATE_MEMBERS", guild_only)]
+pub async fn list(
+    ctx: Context<'_>,
+    #[description = "The user to list notes for"]
+    user: User
+) -> Result<(), BotError> {
+    use crate::database::schema::cases::dsl::*;
+
+    let db = ctx.data().db.clone();
+    let locales = ctx.data().localization_manager.clone();
+    let guild_lang = locales
+        .get_guild_language(db, ctx.guild_id().unwrap()).await.unwrap();
+
+    let guild = ctx.guild_id().unwrap().get() as i64;
+
+    let notes = ctx.data().db.run(|conn| {
+        cases
+            .filter(guild_id.eq(guild))
+            .filter(user_id.eq(user.id.get() as i64))
+            .filter(case_type.eq(NOTE_CASE_TYPE))
+            .order(created_at.desc())
+            .select(Cases::as_select())
+            .load::<Cases>(conn)
+    }).await?;
+
+    if notes.is_empty() {
+        send_reply(ctx, CreateReply::new().content(locales.get("commands.moderation.note.error_no_notes", guild_lang, &[])).ephemeral(true)).await?;
+        return Ok(());
+    }
+
+    // Notes must never end up in a public channel
+    ctx.defer_ephemeral().await?;
+
+    let entries: Vec<(Option<String>, String)> = notes.iter().map(|note| {
+        (None, format!(
+            "`#{}` {} — <@{}>\n{}\n",
+            note.case_id,
+            Timestamp::from(note.created_at).to_discord_timestamp(Format::ShortDate),
+            note.moderator_id,
+            note.reason.clone().unwrap_or_default()
+        ))
+    }).collect();
+
+    let pages = paginate_entries(&entries, DEFAULT_PAGE_SIZE);
+
+    let author = CreateEmbedAuthor::new(locales.get("commands.moderation.note.list_title", guild_lang, &[
+        TranslationParam::from(user.global_name.clone().map_or(user.name.to_string(), |name| name.to_string()))
+    ])).icon_url(user.avatar_url().unwrap_or_default());
+
+    send_paginated(ctx, pages, |description, page, total| {
+        CreateEmbed::new().color(BotColors::Default.color())
+            .author(author.clone())
+            .description(description.to_string())
+            .footer(CreateEmbedFooter::new(locales.get("commands.moderation.cases.view_case_page", guild_lang, &[
+                TranslationParam::from((page + 1).to_string()),
+                TranslationParam::from(total.to_string())
+            ])))
+    }).await?;
+
+    Ok(())
+}
+
+/// Remove a note
+#[command(slash_command, default_member_permissions="MODERATE_MEMBERS", guild_only)]
+pub async fn remove(
+    ctx: Context<'_>,
+    #[description = "The case ID of the note"]
+    #[rename = "id"]
+    note_id: i32
+) -> Result<(), BotError> {
+    use crate::database::schema::cases::dsl::*;
+
+    let db = ctx.data().db.clone();
+    let locales = ctx.data().localization_manager.clone();
+    let guild_lang = locales
+        .get_guild_language(db, ctx.guild_id().unwrap()).await.unwrap();
+
+    let guild = ctx.guild_id().unwrap().get() as i64;
+
+    let removed = ctx.data().db.run(|conn| {
+        diesel::delete(cases
+            .filter(guild_id.eq(guild))
+            .filter(case_id.eq(note_id))
+            .filter(case_type.eq(NOTE_CASE_TYPE))
+        ).returning(Cases::as_returning())
+            .get_result::<Cases>(conn)
+    }).await.ok();
+
+    let Some(removed) = removed else {
+        send_reply(ctx, CreateReply::new().content(locales.get("commands.moderation.note.error_not_found", guild_lang, &[])).ephemeral(true)).await?;
+        return Ok(());
+    };
+
+    send_reply(ctx, CreateReply::new().content(locales.get("commands.moderation.note.reply_removed", guild_lang, &[
+        TranslationParam::from(note_id.to_string()),
+    ])).ephemeral(true)).await?;
+
+    let data = ctx.data().clone();
+
+    let log_data = LogData {
+        data: Some(&*data),
+        ctx: Some(ctx.serenity_context()),
+        guild_id: Some(ctx.guild_id().unwrap().get()),
+        user_id: Some(removed.user_id as u64),
+        moderator_id: Some(ctx.author().id),
+        reason: removed.reason,
+        case_id: Some(note_id),
+        ..LogData::default()
+    };
+
+    log_action(LogType::NoteRemove, log_data).await?;
+
+    Ok(())
+}
@@ -0,0 +1,75 @@
+use poise::{command, CreateReply, send_reply};
+use poise::serenity_prelude::Member;
+use crate::{BotError, Context};
+use crate::localization::manager::TranslationParam;
+use crate::modules::moderation::quarantine::{fetch_quarantine_role, quarantine_member};
+
+/// Isolate a member by swapping their roles for the quarantine role
+#[command(slash_command, default_member_permissions="MANAGE_ROLES", guild_only)]
+pub async fn quarantine(
+    ctx: Context<'_>,
+    #[description = "a user to quarantine"]
+    user: Member,
+    #[description = "reason for quarantining the user"]
+    #[rename = "reason"]
+    action_reason: Option<String>
+) -> Result<(), BotError> {
+    let db = ctx.data().db.clone();
+    let locales = ctx.data().localization_manager.clone();
+    let guild_lang = locales
+        .get_guild_language(db, ctx.guild_id().unwrap()).await.unwrap();
+
+    let guild = ctx.guild().unwrap().clone();
+
+    if user.user.bot() {
+        send_reply(ctx, CreateReply::new().content(locales.get("commands.moderation.quarantine.error_user_bot", guild_lang, &[])).ephemeral(true)).await?;
+        return Ok(());
+    }
+
+    if user.user.id == ctx.author().id {
+        send_reply(ctx, CreateReply::new().content(locales.get("commands.moderation.quarantine.error_user_self", guild_lang, &[])).ephemeral(true)).await?;
+        return Ok(());
+    }
+
+    if user.user.id == guild.owner_id {
+        send_reply(ctx, CreateReply::new().content(locales.get("commands.moderation.quarantine.error_user_owner", guild_lang, &[])).ephemeral(true)).await?;
+        return Ok(());
+    }
+
+    let author_highest_role_position = guild.member_highest_role(&ctx.author_member().await.unwrap()).map(|r| r.position).unwrap_or(0);
+
+    let user_highest_role_position = guild.member_highest_role(&user).map(|r| r.position).unwrap_or(0);
+
+    let bot_highest_role_position = guild.member_highest_role(&guild.id.member(ctx.http(), ctx.http().get_current_user().await.unwrap().id).await.unwrap()).map(|r| r.position).unwrap_or(0);
+
+    if user_highest_role_position >= bot_highest_role_position {
+        send_reply(ctx, CreateReply::new().content(locales.get("commands.moderation.quarantine.error_user_higher_role", guild_lang, &[])).ephemeral(true)).await?;
+        return Ok(());
+    }
+
+    if guild.owner_id != ctx.author().id && author_highest_role_position <= user_highest_role_position {
+        send_reply(ctx, CreateReply::new().content(locales.get("commands.moderation.quarantine.error_user_higher_role", guild_lang, &[])).ephemeral(true)).await?;
+        return Ok(());
+    }
+
+    let data = ctx.data().clone();
+
+    let Some(quarantine_role) = fetch_quarantine_role(&data, guild.id).await? else {
+        send_reply(ctx, CreateReply::new().content(locales.get("commands.moderation.quarantine.error_not_configured", guild_lang, &[])).ephemeral(true)).await?;
+        return Ok(());
+    };
+
+    let action_reason = action_reason.or(Option::from(locales.get("commands.moderation.quarantine.no_reason", guild_lang, &[])));
+
+    let Some(new_case_id) = quarantine_member(ctx.serenity_context(), &data, &user, quarantine_role, ctx.author().id, action_reason).await? else {
+        send_reply(ctx, CreateReply::new().content(locales.get("commands.moderation.quarantine.error_already_quarantined", guild_lang, &[])).ephemeral(true)).await?;
+        return Ok(());
+    };
+
+    send_reply(ctx, CreateReply::new().content(locales.get("commands.moderation.quarantine.reply_success", guild_lang, &[
+        TranslationParam::from(user.user.tag()),
+        TranslationParam::from(new_case_id.to_string()),
+    ])).ephemeral(true)).await?;
+
+    Ok(())
+}
@@ -0,0 +1,53 @@
+use poise::{command, CreateReply, send_reply};
+use poise::serenity_prelude::User;
+use crate::{BotError, Context};
+use crate::localization::manager::TranslationParam;
+use crate::modules::moderation::quarantine::release_member;
+
+/// Release a member from quarantine and give back their roles
+#[command(slash_command, default_member_permissions="MANAGE_ROLES", guild_only)]
+pub async fn release(
+    ctx: Context<'_>,
+    #[description = "a user to release"]
+    user: User,
+    #[description = "reason for releasing the user"]
+    #[rename = "reason"]
+    action_reason: Option<String>
+) -> Result<(), BotError> {
+    let db = ctx.data().db.clone();
+    let locales = ctx.data().localization_manager.clone();
+    let guild_lang = locales
+        .get_guild_language(db, ctx.guild_id().unwrap()).await.unwrap();
+
+    // Members who left have no roles to compare, their saved roles are only forgotten
+    if let Ok(member) = ctx.guild_id().unwrap().member(ctx.http(), user.id).await {
+        let guild = ctx.guild().unwrap().clone();
+
+        let author_highest_role_position = guild.member_highest_role(&ctx.author_member().await.unwrap()).map(|r| r.position).unwrap_or(0);
+
+        let user_highest_role_position = guild.member_highest_role(&member).map(|r| r.position).unwrap_or(0);
+
+        let bot_highest_role_position = guild.member_highest_role(&guild.id.member(ctx.http(), ctx.http().get_current_user().await.unwrap().id).await.unwrap()).map(|r| r.position).unwrap_or(0);
+
+        if user_highest_role_position >= bot_highest_role_position
+            || (guild.owner_id != ctx.author().id && author_highest_role_position <= user_highest_role_position) {
+            send_reply(ctx, CreateReply::new().content(locales.get("commands.moderation.release.error_user_higher_role", guild_lang, &[])).ephemeral(true)).await?;
+            return Ok(());
+        }
+    }
+
+    let data = ctx.data().clone();
+
+    let action_reason = action_reason.or(Option::from(locales.get("commands.moderation.release.no_reason", guild_lang, &[])));
+
+    if !release_member(ctx.serenity_context(), &data, ctx.guild_id().unwrap(), user.id, ctx.author().id, action_reason).await? {
+        send_reply(ctx, CreateReply::new().content(locales.get("commands.moderation.release.error_not_quarantined", guild_lang, &[])).ephemeral(true)).await?;
+        return Ok(());
+    }
+
+    send_reply(ctx, CreateReply::new().content(locales.get("commands.moderation.release.reply_success", guild_lang, &[
+        TranslationParam::from(user.tag()),
+    ])).ephemeral(true)).await?;
+
+    Ok(())
+}
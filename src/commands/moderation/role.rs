@@ -0,0 +1,220 @@
+use chrono::Utc;
+use poise::{command, CreateReply, send_reply};
+use poise::serenity_prelude::{Member, Role};
+use crate::{BotError, Context};
+use crate::database::models::TempRoles;
+use crate::localization::manager::TranslationParam;
+use crate::modules::moderation::logs::{log_action, LogData, LogType};
+use crate::modules::moderation::roles::{remove_temp_role, save_temp_role};
+use crate::util::time::{fetch_guild_timezone, parse_expiry};
+
+/// Temporary roles need to last at least this long, like timed bans
+const MIN_TEMP_ROLE_SECONDS: u64 = 60;
+
+#[command(slash_command, default_member_permissions="MANAGE_ROLES", subcommands("add", "remove"), subcommand_required, guild_only)]
+pub async fn role(_: Context<'_>) -> Result<(), BotError> { Ok(()) }
+
+/// Hierarchy checks shared by both subcommands, replies with the error and returns `false` if the role can't be changed
+async fn can_manage_role(ctx: Context<'_>, user: &Member, role: &Role) -> Result<bool, BotError> {
+    let db = ctx.data().db.clone();
+    let locales = ctx.data().localization_manager.clone();
+    let guild_lang = locales
+        .get_guild_language(db, ctx.guild_id().unwrap()).await.unwrap();
+
+    let guild = ctx.guild().unwrap().clone();
+
+    if role.managed || role.id.get() == guild.id.get() {
+        send_reply(ctx, CreateReply::new().content(locales.get("commands.moderation.role.error_role_managed", guild_lang, &[])).ephemeral(true)).await?;
+        return Ok(false);
+    }
+
+    if guild.owner_id == user.user.id {
+        send_reply(ctx, CreateReply::new().content(locales.get("commands.moderation.role.error_user_owner", guild_lang, &[])).ephemeral(true)).await?;
+        return Ok(false);
+    }
+
+    let author_highest_role_position = guild.member_highest_role(&ctx.author_member().await.unwrap()).map(|r| r.position).unwrap_or(0);
+
+    let user_highest_role_position = guild.member_highest_role(user).map(|r| r.position).unwrap_or(0);
+
+    let bot_highest_role_position = guild.member_highest_role(&guild.id.member(ctx.http(), ctx.http().get_current_user().await.unwrap().id).await.unwrap()).map(|r| r.position).unwrap_or(0);
+
+    if role.position >= bot_highest_role_position {
+        send_reply(ctx, CreateReply::new().content(locales.get("commands.moderation.role.error_role_higher_bot", guild_lang, &[])).ephemeral(true)).await?;
+        return Ok(false);
+    }
+
+    if guild.owner_id != ctx.author().id && role.position >= author_highest_role_position {
+        send_reply(ctx, CreateReply::new().content(locales.get("commands.moderation.role.error_role_higher", guild_lang, &[])).ephemeral(true)).await?;
+        return Ok(false);
+    }
+
+    if guild.owner_id != ctx.author().id && author_highest_role_position <= user_highest_role_position {
+        send_reply(ctx, CreateReply::new().content(locales.get("commands.moderation.role.error_user_higher_role", guild_lang, &[])).ephemeral(true)).await?;
+        return Ok(false);
+    }
+
+    Ok(true)
+}
+
+/// Give a member a role, optionally only for a limited time
+#[command(slash_command, default_member_permissions="MANAGE_ROLES", guild_only)]
+pub async fn add(
+    ctx: Context<'_>,
+    #[description = "Member to give the role to"]
+    user: Member,
+    #[description = "The role to give"]
+    role: Role,
+    #[description = "Remove the role again after this time (e.g. \"3d\"). Leave empty for permanent."]
+    duration: Option<String>,
+    #[description = "Reason for giving the role"]
+    reason: Option<String>
+) -> Result<(), BotError> {
+    let db = ctx.data().db.clone();
+    let locales = ctx.data().localization_manager.clone();
+    let guild_lang = locales
+        .get_guild_language(db, ctx.guild_id().unwrap()).await.unwrap();
+
+    if user.roles.contains(&role.id) {
+        send_reply(ctx, CreateReply::new().content(locales.get("commands.moderation.role.error_already_has_role", guild_lang, &[])).ephemeral(true)).await?;
+        return Ok(());
+    }
+
+    if !can_manage_role(ctx, &user, &role).await? {
+        return Ok(());
+    }
+
+    let guild_tz = fetch_guild_timezone(&ctx.data(), ctx.guild_id().unwrap()).await;
+
+    let (expires_at, duration) = match parse_expiry(duration.as_deref(), guild_lang, guild_tz, MIN_TEMP_ROLE_SECONDS) {
+        Ok(expiry) => expiry,
+        Err(why) => {
+            send_reply(ctx, CreateReply::new().content(why.localize(guild_lang, &locales)).ephemeral(true)).await?;
+            return Ok(());
+        }
+    };
+
+    user.add_role(ctx.http(), role.id, reason.as_deref()).await?;
+
+    let data = ctx.data().clone();
+
+    match expires_at {
+        Some(expires_at) => save_temp_role(&data, TempRoles {
+            guild_id: ctx.guild_id().unwrap().get() as i64,
+            user_id: user.user.id.get() as i64,
+            role_id: role.id.get() as i64,
+            moderator_id: ctx.author().id.get() as i64,
+            action: "ADD".to_string(),
+            reason: reason.clone(),
+            created_at: Utc::now(),
+            end_date: expires_at,
+        }).await?,
+        None => remove_temp_role(&data, ctx.guild_id().unwrap().get() as i64, user.user.id.get() as i64, role.id.get() as i64).await?,
+    }
+
+    let duration_text = duration.clone().unwrap_or(locales.get("commands.moderation.role.permanently", guild_lang, &[]));
+
+    send_reply(ctx, CreateReply::new().content(locales.get("commands.moderation.role.reply_add", guild_lang, &[
+        TranslationParam::from(role.name.to_string()),
+        TranslationParam::from(user.user.tag()),
+        TranslationParam::from(duration_text),
+    ])).ephemeral(true)).await?;
+
+    let log_data = LogData {
+        data: Some(&*data),
+        ctx: Some(ctx.serenity_context()),
+        guild_id: Some(ctx.guild_id().unwrap().get()),
+        user_id: Some(user.user.id.get()),
+        moderator_id: Some(ctx.author().id),
+        roles: Some(vec![role.id.get()]),
+        duration,
+        ends_at: expires_at,
+        reason: reason.or(Option::from(locales.get("commands.moderation.role.no_reason", guild_lang, &[]))),
+        ..LogData::default()
+    };
+
+    log_action(LogType::RoleAdd, log_data).await?;
+
+    Ok(())
+}
+
+/// Take a role away from a member, optionally only for a limited time
+#[command(slash_command, default_member_permissions="MANAGE_ROLES", guild_only)]
+pub async fn remove(
+    ctx: Context<'_>,
+    #[description = "Member to take the role from"]
+    user: Member,
+    #[description = "The role to take away"]
+    role: Role,
+    #[description = "Give the role back after this time (e.g. \"3d\"). Leave empty for permanent."]
+    duration: Option<String>,
+    #[description = "Reason for removing the role"]
+    reason: Option<String>
+) -> Result<(), BotError> {
+    let db = ctx.data().db.clone();
+    let locales = ctx.data().localization_manager.clone();
+    let guild_lang = locales
+        .get_guild_language(db, ctx.guild_id().unwrap()).await.unwrap();
+
+    if !user.roles.contains(&role.id) {
+        send_reply(ctx, CreateReply::new().content(locales.get("commands.moderation.role.error_missing_role", guild_lang, &[])).ephemeral(true)).await?;
+        return Ok(());
+    }
+
+    if !can_manage_role(ctx, &user, &role).await? {
+        return Ok(());
+    }
+
+    let guild_tz = fetch_guild_timezone(&ctx.data(), ctx.guild_id().unwrap()).await;
+
+    let (expires_at, duration) = match parse_expiry(duration.as_deref(), guild_lang, guild_tz, MIN_TEMP_ROLE_SECONDS) {
+        Ok(expiry) => expiry,
+        Err(why) => {
+            send_reply(ctx, CreateReply::new().content(why.localize(guild_lang, &locales)).ephemeral(true)).await?;
+            return Ok(());
+        }
+    };
+
+    user.remove_role(ctx.http(), role.id, reason.as_deref()).await?;
+
+    let data = ctx.data().clone();
+
+    match expires_at {
+        Some(expires_at) => save_temp_role(&data, TempRoles {
+            guild_id: ctx.guild_id().unwrap().get() as i64,
+            user_id: user.user.id.get() as i64,
+            role_id: role.id.get() as i64,
+            moderator_id: ctx.author().id.get() as i64,
+            action: "REMOVE".to_string(),
+            reason: reason.clone(),
+            created_at: Utc::now(),
+            end_date: expires_at,
+        }).await?,
+        None => remove_temp_role(&data, ctx.guild_id().unwrap().get() as i64, user.user.id.get() as i64, role.id.get() as i64).await?,
+    }
+
+    let duration_text = duration.clone().unwrap_or(locales.get("commands.moderation.role.permanently", guild_lang, &[]));
+
+    send_reply(ctx, CreateReply::new().content(locales.get("commands.moderation.role.reply_remove", guild_lang, &[
+        TranslationParam::from(role.name.to_string()),
+        TranslationParam::from(user.user.tag()),
+        TranslationParam::from(duration_text),
+    ])).ephemeral(true)).await?;
+
+    let log_data = LogData {
+        data: Some(&*data),
+        ctx: Some(ctx.serenity_context()),
+        guild_id: Some(ctx.guild_id().unwrap().get()),
+        user_id: Some(user.user.id.get()),
+        moderator_id: Some(ctx.author().id),
+        roles: Some(vec![role.id.get()]),
+        duration,
+        ends_at: expires_at,
+        reason: reason.or(Option::from(locales.get("commands.moderation.role.no_reason", guild_lang, &[]))),
+        ..LogData::default()
+    };
+
+    log_action(LogType::RoleRemove, log_data).await?;
+
+    Ok(())
+}
@@ -0,0 +1,158 @@
+use chrono::Utc;
+use diesel::prelude::*;
+use diesel_async::RunQueryDsl;
+use poise::{command, CreateReply, send_reply};
+use poise::serenity_prelude::{ChannelId, UserId};
+use poise::serenity_prelude::nonmax::NonMaxU64;
+use crate::{BotError, Context};
+use crate::database::models::{Cases, ChannelLocks};
+use crate::localization::manager::TranslationParam;
+use crate::modules::moderation::bans::is_unknown_ban;
+use crate::modules::moderation::cases::revoke_case;
+use crate::modules::moderation::locks::{remove_channel_lock, unlock_channel};
+use crate::modules::moderation::logs::{log_action, LogData, LogType};
+
+/// Actions older than this are left to `/cases remove` and `/channel unlock`
+const UNDO_WINDOW_MINUTES: i64 = 15;
+
+/// Case types `/undo` knows how to reverse
+const UNDOABLE_CASE_TYPES: [&str; 3] = ["BAN", "MUTE", "WARN"];
+
+/// Reason stored on cases reverted with `/undo`
+const UNDO_REASON: &str = "undone";
+
+enum UndoTarget {
+    Case(Cases),
+    Lock(ChannelLocks),
+}
+
+/// Reverse your most recent ban, mute, warn or channel lock
+#[command(slash_command, default_member_permissions="MODERATE_MEMBERS", guild_only)]
+pub async fn undo(ctx: Context<'_>) -> Result<(), BotError> {
+    let db = ctx.data().db.clone();
+    let locales = ctx.data().localization_manager.clone();
+    let guild_lang = locales
+        .get_guild_language(db, ctx.guild_id().unwrap()).await.unwrap();
+
+    let guild = ctx.guild_id().unwrap();
+    let moderator = ctx.author().id.get() as i64;
+    let cutoff = Utc::now() - chrono::Duration::minutes(UNDO_WINDOW_MINUTES);
+    let data = ctx.data().clone();
+
+    let last_case = {
+        use crate::database::schema::cases::dsl::*;
+
+        data.db.run(|conn| {
+            cases
+                .filter(guild_id.eq(guild.get() as i64))
+                .filter(moderator_id.eq(moderator))
+                .filter(case_type.eq_any(UNDOABLE_CASE_TYPES))
+                .filter(revoked_at.is_null())
+                .filter(created_at.gt(cutoff))
+                .order(created_at.desc())
+                .select(Cases::as_select())
+                .first::<Cases>(conn)
+        }).await.ok()
+    };
+
+    let last_lock = {
+        use crate::database::schema::channel_locks::dsl::*;
+
+        data.db.run(|conn| {
+            channel_locks
+                .filter(guild_id.eq(guild.get() as i64))
+                .filter(moderator_id.eq(moderator))
+                .filter(created_at.gt(cutoff))
+                .order(created_at.desc())
+                .select(ChannelLocks::as_select())
+                .first::<ChannelLocks>(conn)
+        }).await.ok()
+    };
+
+    let target = match (last_case, last_lock) {
+        (Some(case), Some(lock)) if lock.created_at > case.created_at => UndoTarget::Lock(lock),
+        (Some(case), _) => UndoTarget::Case(case),
+        (None, Some(lock)) => UndoTarget::Lock(lock),
+        (None, None) => {
+            send_reply(ctx, CreateReply::new().content(locales.get("commands.moderation.undo.error_nothing_to_undo", guild_lang, &[
+                TranslationParam::from(UNDO_WINDOW_MINUTES.to_string())
+            ])).ephemeral(true)).await?;
+            return Ok(());
+        }
+    };
+
+    match target {
+        UndoTarget::Case(case) => {
+            let user = UserId::new(u64::from(NonMaxU64::try_from(case.user_id as u64).unwrap()));
+
+            match case.case_type.as_str() {
+                "BAN" => {
+                    match guild.unban(ctx.http(), user, Some("Ban undone")).await {
+                        Ok(()) => {},
+                        // The ban may already have been lifted by hand
+                        Err(why) if is_unknown_ban(&why) => {},
+                        Err(why) => return Err(why.into()),
+                    }
+                },
+                "MUTE" => {
+                    // The user may have left the server since
+                    if let Ok(mut member) = guild.member(ctx.http(), user).await {
+                        member.enable_communication(ctx.http()).await?;
+                    }
+                },
+                _ => {}
+            }
+
+            revoke_case(&data, guild, case.case_id, UNDO_REASON).await?;
+
+            send_reply(ctx, CreateReply::new().content(locales.get(&format!("commands.moderation.undo.reply_{}", case.case_type.to_lowercase()), guild_lang, &[
+                TranslationParam::from(format!("<@{}>", user)),
+                TranslationParam::from(case.case_id.to_string()),
+            ])).ephemeral(true)).await?;
+
+            let log_data = LogData {
+                data: Some(&*data),
+                ctx: Some(ctx.serenity_context()),
+                guild_id: Some(guild.get()),
+                user_id: Some(user.get()),
+                moderator_id: Some(ctx.author().id),
+                reason: Some(UNDO_REASON.to_string()),
+                case_id: Some(case.case_id),
+                points: case.points,
+                message: Some(case.case_type),
+                ..LogData::default()
+            };
+
+            log_action(LogType::Undo, log_data).await?;
+        },
+        UndoTarget::Lock(lock) => {
+            let channel = ChannelId::new(u64::from(NonMaxU64::try_from(lock.channel_id as u64).unwrap()));
+
+            // The channel may have been deleted while it was locked
+            if let Some(channel) = ctx.http().get_channel(channel).await.ok().and_then(|c| c.guild()) {
+                unlock_channel(ctx.serenity_context(), &data, &channel, guild.everyone_role()).await?;
+            }
+
+            remove_channel_lock(&data, lock.channel_id).await?;
+
+            send_reply(ctx, CreateReply::new().content(locales.get("commands.moderation.undo.reply_lock", guild_lang, &[
+                TranslationParam::from(format!("<#{}>", channel)),
+            ])).ephemeral(true)).await?;
+
+            let log_data = LogData {
+                data: Some(&*data),
+                ctx: Some(ctx.serenity_context()),
+                guild_id: Some(guild.get()),
+                channel_id: Some(channel.get()),
+                moderator_id: Some(ctx.author().id),
+                reason: Some(UNDO_REASON.to_string()),
+                message: Some("LOCK".to_string()),
+                ..LogData::default()
+            };
+
+            log_action(LogType::Undo, log_data).await?;
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,292 @@
+use chrono::{DateTime, Utc};
+use poise::{command, CreateReply, send_reply};
+use poise::serenity_prelude::{EditMember, GuildChannel, Member};
+use crate::{BotError, Context};
+use crate::database::models::{Cases, VoicePunishments};
+use crate::localization::manager::TranslationParam;
+use crate::modules::moderation::cases::create_case;
+use crate::modules::moderation::logs::{log_action, LogData, LogType};
+use crate::modules::moderation::voice::{remove_voice_punishment, save_voice_punishment};
+use crate::util::time::{fetch_guild_timezone, parse_expiry};
+
+#[command(slash_command, default_member_permissions="MUTE_MEMBERS", subcommands("mute", "deafen", "kick", "move_member"), subcommand_required, guild_only)]
+pub async fn voice(_: Context<'_>) -> Result<(), BotError> { Ok(()) }
+
+/// Checks shared by every subcommand, replies with the error and returns `false` if the member can't be moderated
+async fn can_moderate(ctx: Context<'_>, user: &Member) -> Result<bool, BotError> {
+    let db = ctx.data().db.clone();
+    let locales = ctx.data().localization_manager.clone();
+    let guild_lang = locales
+        .get_guild_language(db, ctx.guild_id().unwrap()).await.unwrap();
+
+    if user.user.bot() {
+        send_reply(ctx, CreateReply::new().content(locales.get("commands.moderation.voice.error_user_bot", guild_lang, &[])).ephemeral(true)).await?;
+        return Ok(false);
+    }
+
+    if user.user.id == ctx.author().id {
+        send_reply(ctx, CreateReply::new().content(locales.get("commands.moderation.voice.error_user_self", guild_lang, &[])).ephemeral(true)).await?;
+        return Ok(false);
+    }
+
+    let guild = ctx.guild().unwrap().clone();
+
+    if guild.owner_id == user.user.id {
+        send_reply(ctx, CreateReply::new().content(locales.get("commands.moderation.voice.error_user_owner", guild_lang, &[])).ephemeral(true)).await?;
+        return Ok(false);
+    }
+
+    // Discord rejects voice edits for members who aren't connected
+    if guild.voice_states.get(&user.user.id).and_then(|state| state.channel_id).is_none() {
+        send_reply(ctx, CreateReply::new().content(locales.get("commands.moderation.voice.error_not_connected", guild_lang, &[])).ephemeral(true)).await?;
+        return Ok(false);
+    }
+
+    let author_highest_role_position = guild.member_highest_role(&ctx.author_member().await.unwrap()).map(|r| r.position).unwrap_or(0);
+
+    let user_highest_role_position = guild.member_highest_role(user).map(|r| r.position).unwrap_or(0);
+
+    let bot_highest_role_position = guild.member_highest_role(&guild.id.member(ctx.http(), ctx.http().get_current_user().await.unwrap().id).await.unwrap()).map(|r| r.position).unwrap_or(0);
+
+    if user_highest_role_position >= bot_highest_role_position {
+        send_reply(ctx, CreateReply::new().content(locales.get("commands.moderation.voice.error_user_higher_role", guild_lang, &[])).ephemeral(true)).await?;
+        return Ok(false);
+    }
+
+    if guild.owner_id != ctx.author().id && author_highest_role_position <= user_highest_role_position {
+        send_reply(ctx, CreateReply::new().content(locales.get("commands.moderation.voice.error_user_higher_role", guild_lang, &[])).ephemeral(true)).await?;
+        return Ok(false);
+    }
+
+    Ok(true)
+}
+
+/// Opens the case and logs it, shared by every subcommand
+#[allow(clippy::too_many_arguments)]
+async fn record_action(
+    ctx: Context<'_>,
+    log_type: LogType,
+    case_kind: &str,
+    user: &Member,
+    reason: Option<String>,
+    duration: Option<String>,
+    expires_at: Option<DateTime<Utc>>,
+    channel: Option<u64>
+) -> Result<(), BotError> {
+    let data = ctx.data().clone();
+
+    let new_case_id = create_case(&data, Cases {
+        guild_id: ctx.guild_id().unwrap().get() as i64,
+        user_id: user.user.id.get() as i64,
+        moderator_id: ctx.author().id.get() as i64,
+        case_id: 0,
+        case_type: case_kind.to_string(),
+        reason: reason.clone(),
+        created_at: Utc::now(),
+        end_date: expires_at,
+        points: None,
+        revoked_at: None,
+        revoke_reason: None
+    }).await?;
+
+    if matches!(case_kind, "VOICE_MUTE" | "VOICE_DEAFEN") {
+        let guild = ctx.guild_id().unwrap().get() as i64;
+        let member = user.user.id.get() as i64;
+
+        match expires_at {
+            Some(expires_at) => save_voice_punishment(&data, VoicePunishments {
+                guild_id: guild,
+                user_id: member,
+                kind: case_kind.to_string(),
+                case_id: new_case_id,
+                end_date: expires_at,
+                pending: false,
+            }).await?,
+            None => remove_voice_punishment(&data, guild, member, case_kind).await?,
+        }
+    }
+
+    let log_data = LogData {
+        data: Some(&*data),
+        ctx: Some(ctx.serenity_context()),
+        guild_id: Some(ctx.guild_id().unwrap().get()),
+        user_id: Some(user.user.id.get()),
+        channel_id: channel,
+        moderator_id: Some(ctx.author().id),
+        duration,
+        ends_at: expires_at,
+        reason,
+        case_id: Some(new_case_id),
+        ..LogData::default()
+    };
+
+    log_action(log_type, log_data).await?;
+
+    Ok(())
+}
+
+/// Server mute a member in voice
+#[command(slash_command, required_permissions="MUTE_MEMBERS", guild_only)]
+pub async fn mute(
+    ctx: Context<'_>,
+    #[description = "Member to mute"]
+    user: Member,
+    #[description = "Unmute the member after this time (e.g. \"30m\"). Leave empty for permanent."]
+    duration: Option<String>,
+    #[description = "Reason for muting the member"]
+    reason: Option<String>
+) -> Result<(), BotError> {
+    let db = ctx.data().db.clone();
+    let locales = ctx.data().localization_manager.clone();
+    let guild_lang = locales
+        .get_guild_language(db, ctx.guild_id().unwrap()).await.unwrap();
+
+    if !can_moderate(ctx, &user).await? {
+        return Ok(());
+    }
+
+    let guild_tz = fetch_guild_timezone(&ctx.data(), ctx.guild_id().unwrap()).await;
+
+    let (expires_at, duration) = match parse_expiry(duration.as_deref(), guild_lang, guild_tz, 0) {
+        Ok(expiry) => expiry,
+        Err(why) => {
+            send_reply(ctx, CreateReply::new().content(why.localize(guild_lang, &locales)).ephemeral(true)).await?;
+            return Ok(());
+        }
+    };
+
+    let mut builder = EditMember::new().mute(true);
+
+    if let Some(ref reason) = reason {
+        builder = builder.audit_log_reason(reason);
+    }
+
+    ctx.guild_id().unwrap().edit_member(ctx.http(), user.user.id, builder).await?;
+
+    let duration_text = duration.clone().unwrap_or(locales.get("commands.moderation.voice.permanently", guild_lang, &[]));
+
+    send_reply(ctx, CreateReply::new().content(locales.get("commands.moderation.voice.reply_mute", guild_lang, &[
+        TranslationParam::from(user.user.tag()),
+        TranslationParam::from(duration_text),
+    ])).ephemeral(true)).await?;
+
+    record_action(ctx, LogType::VoiceMute, "VOICE_MUTE", &user, reason, duration, expires_at, None).await
+}
+
+/// Server deafen a member in voice
+#[command(slash_command, required_permissions="DEAFEN_MEMBERS", guild_only)]
+pub async fn deafen(
+    ctx: Context<'_>,
+    #[description = "Member to deafen"]
+    user: Member,
+    #[description = "Undeafen the member after this time (e.g. \"30m\"). Leave empty for permanent."]
+    duration: Option<String>,
+    #[description = "Reason for deafening the member"]
+    reason: Option<String>
+) -> Result<(), BotError> {
+    let db = ctx.data().db.clone();
+    let locales = ctx.data().localization_manager.clone();
+    let guild_lang = locales
+        .get_guild_language(db, ctx.guild_id().unwrap()).await.unwrap();
+
+    if !can_moderate(ctx, &user).await? {
+        return Ok(());
+    }
+
+    let guild_tz = fetch_guild_timezone(&ctx.data(), ctx.guild_id().unwrap()).await;
+
+    let (expires_at, duration) = match parse_expiry(duration.as_deref(), guild_lang, guild_tz, 0) {
+        Ok(expiry) => expiry,
+        Err(why) => {
+            send_reply(ctx, CreateReply::new().content(why.localize(guild_lang, &locales)).ephemeral(true)).await?;
+            return Ok(());
+        }
+    };
+
+    let mut builder = EditMember::new().deafen(true);
+
+    if let Some(ref reason) = reason {
+        builder = builder.audit_log_reason(reason);
+    }
+
+    ctx.guild_id().unwrap().edit_member(ctx.http(), user.user.id, builder).await?;
+
+    let duration_text = duration.clone().unwrap_or(locales.get("commands.moderation.voice.permanently", guild_lang, &[]));
+
+    send_reply(ctx, CreateReply::new().content(locales.get("commands.moderation.voice.reply_deafen", guild_lang, &[
+        TranslationParam::from(user.user.tag()),
+        TranslationParam::from(duration_text),
+    ])).ephemeral(true)).await?;
+
+    record_action(ctx, LogType::VoiceDeafen, "VOICE_DEAFEN", &user, reason, duration, expires_at, None).await
+}
+
+/// Disconnect a member from voice
+#[command(slash_command, required_permissions="MOVE_MEMBERS", guild_only)]
+pub async fn kick(
+    ctx: Context<'_>,
+    #[description = "Member to disconnect"]
+    user: Member,
+    #[description = "Reason for disconnecting the member"]
+    reason: Option<String>
+) -> Result<(), BotError> {
+    let db = ctx.data().db.clone();
+    let locales = ctx.data().localization_manager.clone();
+    let guild_lang = locales
+        .get_guild_language(db, ctx.guild_id().unwrap()).await.unwrap();
+
+    if !can_moderate(ctx, &user).await? {
+        return Ok(());
+    }
+
+    let mut builder = EditMember::new().disconnect_member();
+
+    if let Some(ref reason) = reason {
+        builder = builder.audit_log_reason(reason);
+    }
+
+    ctx.guild_id().unwrap().edit_member(ctx.http(), user.user.id, builder).await?;
+
+    send_reply(ctx, CreateReply::new().content(locales.get("commands.moderation.voice.reply_kick", guild_lang, &[
+        TranslationParam::from(user.user.tag()),
+    ])).ephemeral(true)).await?;
+
+    record_action(ctx, LogType::VoiceKick, "VOICE_KICK", &user, reason, None, None, None).await
+}
+
+/// Move a member to another voice channel
+#[command(slash_command, rename = "move", required_permissions="MOVE_MEMBERS", guild_only)]
+pub async fn move_member(
+    ctx: Context<'_>,
+    #[description = "Member to move"]
+    user: Member,
+    #[description = "Voice channel to move the member to"]
+    #[channel_types("Voice", "Stage")]
+    channel: GuildChannel,
+    #[description = "Reason for moving the member"]
+    reason: Option<String>
+) -> Result<(), BotError> {
+    let db = ctx.data().db.clone();
+    let locales = ctx.data().localization_manager.clone();
+    let guild_lang = locales
+        .get_guild_language(db, ctx.guild_id().unwrap()).await.unwrap();
+
+    if !can_moderate(ctx, &user).await? {
+        return Ok(());
+    }
+
+    let mut builder = EditMember::new().voice_channel(channel.id);
+
+    if let Some(ref reason) = reason {
+        builder = builder.audit_log_reason(reason);
+    }
+
+    ctx.guild_id().unwrap().edit_member(ctx.http(), user.user.id, builder).await?;
+
+    send_reply(ctx, CreateReply::new().content(locales.get("commands.moderation.voice.reply_move", guild_lang, &[
+        TranslationParam::from(user.user.tag()),
+        TranslationParam::from(channel.id.to_string()),
+    ])).ephemeral(true)).await?;
+
+    record_action(ctx, LogType::VoiceMove, "VOICE_MOVE", &user, reason, None, None, Some(channel.id.get())).await
+}
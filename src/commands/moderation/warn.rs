@@ -1,23 +1,29 @@
 use diesel::{ExpressionMethods, QueryDsl};
 use diesel_async::{RunQueryDsl};
-use diesel::dsl::sum;
 use poise::{command, CreateReply};
-use poise::serenity_prelude::{CreateEmbed, Member, Timestamp};
+use poise::serenity_prelude::{Attachment, CreateEmbed, Member, Timestamp};
 use crate::{BotError, Context};
 use crate::database::models::Cases;
 use crate::database::schema::moderation_settings::dsl::moderation_settings;
 use crate::localization::manager::TranslationParam;
+use crate::modules::moderation::cases::{active_points, NEVER_EXPIRE};
+use crate::modules::moderation::evidence::{archive_evidence, is_guild_message_link};
 use crate::modules::moderation::logs::{log_action, LogData, LogType};
+use crate::modules::moderation::presets::{autocomplete_reason, find_preset};
 use crate::util::color::BotColors;
+use crate::util::time::{fetch_guild_timezone, format_duration, parse_duration_or_date};
 use crate::util::timestamp::{Format, TimestampExt};
 
+/// Warn a user. Without `expire` the warning uses the reason preset's duration, then the server's expiry.
 #[command(slash_command, default_member_permissions="ADMINISTRATOR", guild_only)]
 pub async fn warn(
     ctx: Context<'_>,
     #[description = "User to warn"] user: Member,
     #[description = "Number of warning points"] #[min = 1] #[max = 100] #[rename = "points"] action_points: Option<i32>,
-    #[description = "Reason for the warning"] #[rename = "reason"] action_reason: Option<String>,
-    #[description = "Should the warning expire?"] expire: Option<bool>,
+    #[description = "Reason for the warning"] #[rename = "reason"] #[autocomplete = "autocomplete_reason"] action_reason: Option<String>,
+    #[description = "When it expires (e.g. \"12h\", \"30d\" or \"never\"). Defaults to the preset's, then the server's expiry."] expire: Option<String>,
+    #[description = "Screenshot or file backing up the warning"] evidence: Option<Attachment>,
+    #[description = "Link to a message backing up the warning"] evidence_link: Option<String>,
 ) -> Result<(), BotError> {
     use crate::database::schema::cases::dsl::*;
     use crate::database::schema::moderation_settings::*;
@@ -56,9 +62,15 @@ pub async fn warn(
         }
     }
 
+    if evidence_link.as_deref().is_some_and(|link| !is_guild_message_link(link, guild.id)) {
+        ctx.reply(locales.get("commands.moderation.evidence.error_invalid_link", guild_lang, &[])).await?;
+        return Ok(());
+    }
+
     let guild = ctx.guild_id().unwrap();
     let data = ctx.data();
-    let action_points = action_points.unwrap_or(1);
+    let preset = find_preset(&data, guild, action_reason.as_deref()).await;
+    let action_points = action_points.or(preset.as_ref().and_then(|preset| preset.points)).unwrap_or(1);
 
     let new_case_id: i32 = data.db.run(|conn| {
         cases
@@ -66,7 +78,6 @@ pub async fn warn(
             .first::<Option<i32>>(conn)
     }).await?.unwrap_or(0) + 1;
 
-    use crate::database::schema::cases::dsl::guild_id as cases_guild_id;
     use crate::database::schema::moderation_settings::dsl::guild_id as moderation_settings_guild_id;
 
     let expire_time: i64 = data.db.run(|conn| {
@@ -78,12 +89,22 @@ pub async fn warn(
 
 
 
-    let end_res_date = if expire.unwrap_or(false) {
-        Some(chrono::Utc::now() + chrono::Duration::days(expire_time))
-    } else {
-        None
+    let guild_tz = fetch_guild_timezone(&data, guild).await;
+    let expire = expire.or(preset.and_then(|preset| preset.duration));
+
+    let expire_seconds = match expire.map(|expire| parse_duration_or_date(&expire, guild_lang, guild_tz)) {
+        Some(Err(why)) => {
+            ctx.reply(why.localize(guild_lang, &locales)).await?;
+            return Ok(());
+        },
+        Some(Ok(seconds)) => seconds,
+        // Unlike the old `expire: bool`, leaving it out applies the server's expiry rather than none.
+        // Guilds that want plain warnings to last forever set the expiry to never in `/config`.
+        None => (expire_time != NEVER_EXPIRE).then(|| expire_time as u64 * 86400),
     };
 
+    let end_res_date = expire_seconds.map(|seconds| chrono::Utc::now() + chrono::Duration::seconds(seconds as i64));
+
     let action_reason_for_logging = action_reason.clone();
 
     let new_case: Cases = Cases {
@@ -96,6 +117,8 @@ pub async fn warn(
         created_at: chrono::Utc::now(),
         end_date: end_res_date,
         points: Some(action_points).or(None),
+        revoked_at: None,
+        revoke_reason: None,
     };
 
     data.db.run(|conn| {
@@ -104,13 +127,7 @@ pub async fn warn(
             .execute(conn)
     }).await?;
 
-    let total_points = data.db.run(|conn| {
-        cases
-            .filter(cases_guild_id.eq(guild.get() as i64))
-            .filter(user_id.eq(user.user.id.get() as i64))
-            .select(sum(points))
-            .first::<Option<i64>>(conn)
-    }).await.unwrap_or(Option::from(action_points as i64));
+    let total_points = active_points(&data, guild, user.user.id).await?;
 
     let points_text = if action_points == 1 {
         locales.get("commands.moderation.warn.point", guild_lang, &[])
@@ -130,7 +147,7 @@ pub async fn warn(
         .color(BotColors::Default.color())
         .field(locales.get("commands.moderation.warn.reply_success_field_user", guild_lang, &[]), format!("<@{}>", user.user.id), true)
         .field(locales.get("commands.moderation.warn.reply_success_field_mod", guild_lang, &[]), format!("<@{}>", ctx.author().id), true)
-        .field(locales.get("commands.moderation.warn.reply_success_field_total", guild_lang, &[]), total_points.unwrap().to_string(), true);
+        .field(locales.get("commands.moderation.warn.reply_success_field_total", guild_lang, &[]), total_points.to_string(), true);
 
     if let Some(end_res_date) = end_res_date {
         e = e.field(locales.get("commands.moderation.warn.reply_success_field_expires", guild_lang, &[]), Timestamp::from(end_res_date).to_discord_timestamp(Format::LongDateShortTime), true);
@@ -138,6 +155,8 @@ pub async fn warn(
 
     ctx.send(CreateReply::new().embed(e)).await?;
 
+    let evidence = archive_evidence(ctx.serenity_context(), &data, guild, new_case_id, evidence.as_ref(), evidence_link.as_deref()).await?;
+
     log_action(LogType::Warn, LogData {
         data: Some(&*ctx.data()),
         ctx: Some(ctx.serenity_context()),
@@ -147,7 +166,9 @@ pub async fn warn(
         reason: action_reason_for_logging,
         case_id: Some(new_case_id),
         points: Some(action_points),
-        duration: end_res_date.map(|_| expire_time.to_string()),
+        duration: expire_seconds.map(|seconds| format_duration(seconds, guild_lang)),
+        ends_at: end_res_date,
+        evidence: Some(evidence),
         ..Default::default()
     }).await?;
 
@@ -7,6 +7,7 @@ use diesel::prelude::*;
 pub struct GuildSettings {
     pub guild_id: i64,
     pub lang: String,
+    pub timezone: String,
 }
 
 
@@ -18,6 +19,19 @@ pub struct ModerationSettings {
     pub warn_expire_time: i64,
     pub default_log_channel: Option<i64>,
     pub log_types: i32,
+    /// Every log type that existed when `log_types` was last brought up to date
+    pub known_log_types: i32,
+    pub lockdown_channels: Vec<i64>,
+    pub auto_dehoist: bool,
+    pub nickname_patterns: Vec<String>,
+    pub nickname_template: String,
+    pub quarantine_role: Option<i64>,
+    pub quarantine_channel: Option<i64>,
+    pub quarantine_automod_rules: Vec<i64>,
+    pub evidence_channel: Option<i64>,
+    /// Points a user loses every `warn_decay_period` seconds, decay is off without a period
+    pub warn_decay_points: i32,
+    pub warn_decay_period: Option<i64>,
 }
 
 #[derive(Queryable, Selectable, Insertable, Clone, Debug)]
@@ -33,4 +47,157 @@ pub struct Cases {
     pub created_at: DateTime<Utc>,
     pub end_date: Option<DateTime<Utc>>,
     pub points: Option<i32>,
-}
\ No newline at end of file
+    pub revoked_at: Option<DateTime<Utc>>,
+    pub revoke_reason: Option<String>,
+}
+
+#[derive(Queryable, Selectable, Insertable, Clone, Debug)]
+#[diesel(table_name = crate::database::schema::slowmodes)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct Slowmodes {
+    pub channel_id: i64,
+    pub guild_id: i64,
+    pub moderator_id: i64,
+    pub previous_delay: i32,
+    pub end_date: DateTime<Utc>,
+}
+
+#[derive(Queryable, Selectable, Insertable, Clone, Debug)]
+#[diesel(table_name = crate::database::schema::lockdowns)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct Lockdowns {
+    pub guild_id: i64,
+    pub moderator_id: i64,
+    pub reason: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub end_date: Option<DateTime<Utc>>,
+}
+
+/// Snapshot of a channel's @everyone overwrite taken before a lockdown.
+/// `None` bits mean the channel had no overwrite at all.
+#[derive(Queryable, Selectable, Insertable, Clone, Debug)]
+#[diesel(table_name = crate::database::schema::lockdown_overwrites)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct LockdownOverwrites {
+    pub guild_id: i64,
+    pub channel_id: i64,
+    pub allow: Option<i64>,
+    pub deny: Option<i64>,
+}
+
+#[derive(Queryable, Selectable, Insertable, Clone, Debug)]
+#[diesel(table_name = crate::database::schema::channel_locks)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct ChannelLocks {
+    pub channel_id: i64,
+    pub guild_id: i64,
+    pub moderator_id: i64,
+    pub reason: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub end_date: Option<DateTime<Utc>>,
+}
+
+/// Overwrite a role had on a channel before it was locked for that role.
+/// `None` bits mean the lock created the overwrite.
+#[derive(Queryable, Selectable, Insertable, Clone, Debug)]
+#[diesel(table_name = crate::database::schema::channel_lock_overwrites)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct ChannelLockOverwrites {
+    pub channel_id: i64,
+    pub role_id: i64,
+    pub allow: Option<i64>,
+    pub deny: Option<i64>,
+}
+
+#[derive(Queryable, Selectable, Insertable, Clone, Debug)]
+#[diesel(table_name = crate::database::schema::purge_transcripts)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct PurgeTranscripts {
+    pub guild_id: i64,
+    pub channel_id: i64,
+    pub moderator_id: i64,
+    pub messages_deleted: i32,
+    pub created_at: DateTime<Utc>,
+    pub transcript: String,
+    pub transcript_html: String,
+}
+
+/// A role given or taken away for a limited time. `action` is the step that was taken,
+/// either `ADD` or `REMOVE`, and gets reversed once `end_date` passes.
+#[derive(Queryable, Selectable, Insertable, Clone, Debug)]
+#[diesel(table_name = crate::database::schema::temp_roles)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct TempRoles {
+    pub guild_id: i64,
+    pub user_id: i64,
+    pub role_id: i64,
+    pub moderator_id: i64,
+    pub action: String,
+    pub reason: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub end_date: DateTime<Utc>,
+}
+
+/// A timed server mute or deafen, `kind` is the case type. It's lifted once `end_date` passes,
+/// or marked `pending` if the member is out of voice then and lifted when they connect again.
+#[derive(Queryable, Selectable, Insertable, Clone, Debug)]
+#[diesel(table_name = crate::database::schema::voice_punishments)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct VoicePunishments {
+    pub guild_id: i64,
+    pub user_id: i64,
+    pub kind: String,
+    pub case_id: i32,
+    pub end_date: DateTime<Utc>,
+    pub pending: bool,
+}
+
+/// Roles a member had before being quarantined, given back on release
+#[derive(Queryable, Selectable, Insertable, Clone, Debug)]
+#[diesel(table_name = crate::database::schema::quarantines)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct Quarantines {
+    pub guild_id: i64,
+    pub user_id: i64,
+    pub moderator_id: i64,
+    pub roles: Vec<i64>,
+    pub reason: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// A guild-defined reason offered by autocomplete, with defaults applied when it's picked
+#[derive(Queryable, Selectable, Insertable, Clone, Debug)]
+#[diesel(table_name = crate::database::schema::reason_presets)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct ReasonPresets {
+    pub guild_id: i64,
+    pub reason: String,
+    pub points: Option<i32>,
+    pub duration: Option<String>,
+}
+
+/// A guild-defined case type, `name` is what gets stored in `cases.case_type`
+#[derive(Queryable, Selectable, Insertable, Clone, Debug)]
+#[diesel(table_name = crate::database::schema::case_types)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct CaseTypes {
+    pub guild_id: i64,
+    pub name: String,
+    pub display_name: String,
+    pub points: Option<i32>,
+    pub duration: Option<String>,
+    pub counts_points: bool,
+}
+
+/// Evidence attached to a case. `url` points at the archived copy, `source` at what was originally posted
+#[derive(Queryable, Selectable, Insertable, Clone, Debug)]
+#[diesel(table_name = crate::database::schema::case_evidence)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct CaseEvidence {
+    pub guild_id: i64,
+    pub case_id: i32,
+    pub kind: String,
+    pub url: String,
+    pub source: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
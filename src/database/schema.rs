@@ -11,6 +11,33 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    case_evidence (id) {
+        id -> Int4,
+        guild_id -> Int8,
+        case_id -> Int4,
+        #[max_length = 16]
+        kind -> Varchar,
+        url -> Text,
+        source -> Nullable<Text>,
+        created_at -> Timestamptz,
+    }
+}
+
+diesel::table! {
+    case_types (guild_id, name) {
+        guild_id -> Int8,
+        #[max_length = 32]
+        name -> Varchar,
+        #[max_length = 100]
+        display_name -> Varchar,
+        points -> Nullable<Int4>,
+        #[max_length = 32]
+        duration -> Nullable<Varchar>,
+        counts_points -> Bool,
+    }
+}
+
 diesel::table! {
     cases (id) {
         id -> Int4,
@@ -24,6 +51,28 @@ diesel::table! {
         created_at -> Timestamptz,
         end_date -> Nullable<Timestamptz>,
         points -> Nullable<Int4>,
+        revoked_at -> Nullable<Timestamptz>,
+        revoke_reason -> Nullable<Text>,
+    }
+}
+
+diesel::table! {
+    channel_lock_overwrites (channel_id, role_id) {
+        channel_id -> Int8,
+        role_id -> Int8,
+        allow -> Nullable<Int8>,
+        deny -> Nullable<Int8>,
+    }
+}
+
+diesel::table! {
+    channel_locks (channel_id) {
+        channel_id -> Int8,
+        guild_id -> Int8,
+        moderator_id -> Int8,
+        reason -> Nullable<Text>,
+        created_at -> Timestamptz,
+        end_date -> Nullable<Timestamptz>,
     }
 }
 
@@ -32,6 +81,27 @@ diesel::table! {
         guild_id -> Int8,
         #[max_length = 255]
         lang -> Varchar,
+        #[max_length = 64]
+        timezone -> Varchar,
+    }
+}
+
+diesel::table! {
+    lockdown_overwrites (guild_id, channel_id) {
+        guild_id -> Int8,
+        channel_id -> Int8,
+        allow -> Nullable<Int8>,
+        deny -> Nullable<Int8>,
+    }
+}
+
+diesel::table! {
+    lockdowns (guild_id) {
+        guild_id -> Int8,
+        moderator_id -> Int8,
+        reason -> Nullable<Text>,
+        created_at -> Timestamptz,
+        end_date -> Nullable<Timestamptz>,
     }
 }
 
@@ -41,6 +111,89 @@ diesel::table! {
         warn_expire_time -> Int8,
         default_log_channel -> Nullable<Int8>,
         log_types -> Int4,
+        known_log_types -> Int4,
+        lockdown_channels -> Array<Int8>,
+        auto_dehoist -> Bool,
+        nickname_patterns -> Array<Text>,
+        #[max_length = 32]
+        nickname_template -> Varchar,
+        quarantine_role -> Nullable<Int8>,
+        quarantine_channel -> Nullable<Int8>,
+        quarantine_automod_rules -> Array<Int8>,
+        evidence_channel -> Nullable<Int8>,
+        warn_decay_points -> Int4,
+        warn_decay_period -> Nullable<Int8>,
+    }
+}
+
+diesel::table! {
+    reason_presets (id) {
+        id -> Int4,
+        guild_id -> Int8,
+        reason -> Text,
+        points -> Nullable<Int4>,
+        #[max_length = 32]
+        duration -> Nullable<Varchar>,
+    }
+}
+
+diesel::table! {
+    purge_transcripts (id) {
+        id -> Int4,
+        guild_id -> Int8,
+        channel_id -> Int8,
+        moderator_id -> Int8,
+        messages_deleted -> Int4,
+        created_at -> Timestamptz,
+        transcript -> Text,
+        transcript_html -> Text,
+    }
+}
+
+diesel::table! {
+    quarantines (guild_id, user_id) {
+        guild_id -> Int8,
+        user_id -> Int8,
+        moderator_id -> Int8,
+        roles -> Array<Int8>,
+        reason -> Nullable<Text>,
+        created_at -> Timestamptz,
+    }
+}
+
+diesel::table! {
+    slowmodes (channel_id) {
+        channel_id -> Int8,
+        guild_id -> Int8,
+        moderator_id -> Int8,
+        previous_delay -> Int4,
+        end_date -> Timestamptz,
+    }
+}
+
+diesel::table! {
+    temp_roles (guild_id, user_id, role_id) {
+        guild_id -> Int8,
+        user_id -> Int8,
+        role_id -> Int8,
+        moderator_id -> Int8,
+        #[max_length = 16]
+        action -> Varchar,
+        reason -> Nullable<Text>,
+        created_at -> Timestamptz,
+        end_date -> Timestamptz,
+    }
+}
+
+diesel::table! {
+    voice_punishments (guild_id, user_id, kind) {
+        guild_id -> Int8,
+        user_id -> Int8,
+        #[max_length = 16]
+        kind -> Varchar,
+        case_id -> Int4,
+        end_date -> Timestamptz,
+        pending -> Bool,
     }
 }
 
@@ -48,7 +201,19 @@ diesel::joinable!(cases -> guild_settings (guild_id));
 
 diesel::allow_tables_to_appear_in_same_query!(
     _sqlx_migrations,
+    case_evidence,
+    case_types,
     cases,
+    channel_lock_overwrites,
+    channel_locks,
     guild_settings,
+    lockdown_overwrites,
+    lockdowns,
     moderation_settings,
+    purge_transcripts,
+    quarantines,
+    reason_presets,
+    slowmodes,
+    temp_roles,
+    voice_punishments,
 );
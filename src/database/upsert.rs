@@ -4,6 +4,9 @@ use crate::database::manager::DbManager;
 use diesel_async::RunQueryDsl;
 use diesel::prelude::*;
 use poise::serenity_prelude::GuildId;
+use strum::IntoEnumIterator;
+use crate::modules::moderation::logs::LogType;
+use crate::modules::moderation::nicknames::DEFAULT_NICKNAME_TEMPLATE;
 
 pub async fn upsert_database(
     db: Arc<DbManager>,
@@ -13,7 +16,11 @@ pub async fn upsert_database(
 
     upsert_guild_settings(db.clone(), guilds).await?;
     
-    upsert_moderation_settings(db.clone(), guilds).await?;
+    let all_log_types = LogType::iter().fold(0u32, |mask, log_type| mask | log_type.as_bit()) as i32;
+
+    upsert_moderation_settings(db.clone(), guilds, all_log_types).await?;
+
+    enable_new_log_types(db, all_log_types).await?;
 
     Ok(())
 }
@@ -25,7 +32,7 @@ async fn upsert_guild_settings(
     use crate::database::schema::guild_settings::dsl::*;
 
     let new_guild_settings: Vec<_> = guilds.iter().map(|guild| {
-        (guild_id.eq(guild.get() as i64), lang.eq("en"))
+        (guild_id.eq(guild.get() as i64), lang.eq("en"), timezone.eq("UTC"))
     }).collect::<Vec<_>>();
     
     db.run(|conn| {
@@ -39,9 +46,35 @@ async fn upsert_guild_settings(
     Ok(())
 }
 
+/// Turns on log types added since a guild's settings were created, without touching
+/// the ones the guild already knew about and might have turned off
+async fn enable_new_log_types(db: Arc<DbManager>, all_log_types: i32) -> Result<(), BotError> {
+    use crate::database::schema::moderation_settings::dsl::*;
+
+    let outdated = db.run(|conn| {
+        moderation_settings
+            .filter(known_log_types.ne(all_log_types))
+            .select((guild_id, log_types, known_log_types))
+            .load::<(i64, i32, i32)>(conn)
+    }).await?;
+
+    for (guild, mask, known) in outdated {
+        let mask = mask | (all_log_types & !known);
+
+        db.run(|conn| {
+            diesel::update(moderation_settings.filter(guild_id.eq(guild)))
+                .set((log_types.eq(mask), known_log_types.eq(all_log_types)))
+                .execute(conn)
+        }).await?;
+    }
+
+    Ok(())
+}
+
 async fn upsert_moderation_settings(
     db: Arc<DbManager>,
-    guilds: &[GuildId]
+    guilds: &[GuildId],
+    all_log_types: i32
 ) -> Result<(), BotError> {
     use crate::database::schema::moderation_settings::dsl::*;
 
@@ -49,8 +82,19 @@ async fn upsert_moderation_settings(
         (
             guild_id.eq(guild.get() as i64),
             warn_expire_time.eq(3),
-            log_types.eq(4095),
+            log_types.eq(all_log_types),
+            known_log_types.eq(all_log_types),
             default_log_channel.eq::<Option<i64>>(None),
+            lockdown_channels.eq(Vec::<i64>::new()),
+            auto_dehoist.eq(false),
+            nickname_patterns.eq(Vec::<String>::new()),
+            nickname_template.eq(DEFAULT_NICKNAME_TEMPLATE),
+            quarantine_role.eq::<Option<i64>>(None),
+            quarantine_channel.eq::<Option<i64>>(None),
+            quarantine_automod_rules.eq(Vec::<i64>::new()),
+            evidence_channel.eq::<Option<i64>>(None),
+            warn_decay_points.eq(1),
+            warn_decay_period.eq::<Option<i64>>(None),
         )
     }).collect::<Vec<_>>();
 
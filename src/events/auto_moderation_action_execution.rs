@@ -0,0 +1,38 @@
+use diesel::prelude::*;
+use diesel_async::RunQueryDsl;
+use poise::serenity_prelude::{ActionExecution, CacheHttp};
+use crate::{BotError, Data};
+use crate::modules::moderation::quarantine::{fetch_quarantine_role, quarantine_member};
+
+pub async fn handle(
+    framework: poise::FrameworkContext<'_, Data, BotError>,
+    execution: &ActionExecution
+) -> Result<(), BotError> {
+    use crate::database::schema::moderation_settings::dsl::*;
+
+    let data = framework.user_data();
+    let ctx = framework.serenity_context;
+
+    let rules = data.db.run(|conn| {
+        moderation_settings
+            .filter(guild_id.eq(execution.guild_id.get() as i64))
+            .select(quarantine_automod_rules)
+            .first::<Vec<i64>>(conn)
+    }).await?;
+
+    if !rules.contains(&(execution.rule_id.get() as i64)) {
+        return Ok(());
+    }
+
+    let Some(role) = fetch_quarantine_role(&data, execution.guild_id).await? else {
+        return Ok(());
+    };
+
+    let member = execution.guild_id.member(ctx.http(), execution.user_id).await?;
+
+    let bot_id = *data.client_id.read().unwrap();
+
+    quarantine_member(ctx, &data, &member, role, bot_id, Some(format!("Triggered automod rule {}", execution.rule_id))).await?;
+
+    Ok(())
+}
@@ -1,6 +1,7 @@
-
 use poise::serenity_prelude::{GuildId};
 use crate::{database, BotError, Data};
+use crate::modules::moderation::bans::reconcile_bans;
+
 pub async fn handle(
     framework: poise::FrameworkContext<'_, Data, BotError>,
     guilds: &[GuildId]
@@ -8,5 +9,19 @@ pub async fn handle(
     let data = framework.user_data();
     let db = data.db.clone();
     database::upsert::upsert_database(db, guilds).await?;
+
+    // Bans may have changed while the bot was offline
+    let ctx = framework.serenity_context.clone();
+    let guilds = guilds.to_vec();
+    tokio::spawn(async move {
+        let bot = ctx.cache.current_user().id;
+
+        for guild in guilds {
+            if let Err(why) = reconcile_bans(&ctx, &data, guild, bot, false).await {
+                eprintln!("Failed to reconcile bans for guild {}: {:?}", guild, why);
+            }
+        }
+    });
+
     Ok(())
-}
\ No newline at end of file
+}
@@ -0,0 +1,14 @@
+use poise::serenity_prelude::Member;
+use crate::{BotError, Data};
+use crate::modules::moderation::nicknames::enforce_nickname;
+use crate::modules::moderation::quarantine::reapply_quarantine;
+
+pub async fn handle(
+    framework: poise::FrameworkContext<'_, Data, BotError>,
+    new_member: &Member
+) -> Result<(), BotError> {
+    let data = framework.user_data();
+    reapply_quarantine(framework.serenity_context, &data, new_member).await?;
+    enforce_nickname(framework.serenity_context, &data, new_member).await?;
+    Ok(())
+}
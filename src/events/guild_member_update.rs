@@ -0,0 +1,22 @@
+use poise::serenity_prelude::Member;
+use crate::{BotError, Data};
+use crate::modules::moderation::nicknames::enforce_nickname;
+
+pub async fn handle(
+    framework: poise::FrameworkContext<'_, Data, BotError>,
+    old_if_available: &Option<Member>,
+    new: &Option<Member>
+) -> Result<(), BotError> {
+    let Some(new) = new else {
+        return Ok(());
+    };
+
+    // Only react to name changes, role or avatar updates don't matter here
+    if old_if_available.as_ref().is_some_and(|old| old.display_name() == new.display_name()) {
+        return Ok(());
+    }
+
+    let data = framework.user_data();
+    enforce_nickname(framework.serenity_context, &data, new).await?;
+    Ok(())
+}
@@ -2,6 +2,10 @@ mod ready;
 mod message;
 mod cache_ready;
 mod guild_create;
+mod guild_member_addition;
+mod guild_member_update;
+mod auto_moderation_action_execution;
+mod voice_state_update;
 
 
 mod message_delete;
@@ -29,6 +33,18 @@ pub async fn handle_event(
         serenity::FullEvent::GuildCreate { guild, is_new } => {
             guild_create::handle(framework, guild, is_new).await
         },
+        serenity::FullEvent::GuildMemberAddition { new_member } => {
+            guild_member_addition::handle(framework, new_member).await
+        },
+        serenity::FullEvent::GuildMemberUpdate { old_if_available, new, .. } => {
+            guild_member_update::handle(framework, old_if_available, new).await
+        },
+        serenity::FullEvent::AutoModActionExecution { execution } => {
+            auto_moderation_action_execution::handle(framework, execution).await
+        },
+        serenity::FullEvent::VoiceStateUpdate { old, new } => {
+            voice_state_update::handle(framework, old, new).await
+        },
         _ => Ok(()),
     }
 }
\ No newline at end of file
@@ -0,0 +1,23 @@
+use poise::serenity_prelude::VoiceState;
+use crate::{BotError, Data};
+use crate::modules::moderation::voice::restore_pending_voice;
+
+pub async fn handle(
+    framework: poise::FrameworkContext<'_, Data, BotError>,
+    old: &Option<VoiceState>,
+    new: &VoiceState
+) -> Result<(), BotError> {
+    let Some(guild) = new.guild_id else {
+        return Ok(());
+    };
+
+    // Only joins matter, and only while still muted or deafened by the server
+    let was_connected = old.as_ref().is_some_and(|old| old.channel_id.is_some());
+    if was_connected || new.channel_id.is_none() || !(new.mute || new.deaf) {
+        return Ok(());
+    }
+
+    let data = framework.user_data();
+    restore_pending_voice(framework.serenity_context, &data, guild, new.user_id).await?;
+    Ok(())
+}
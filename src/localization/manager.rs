@@ -287,6 +287,7 @@ impl LocalizationManager {
                 .values(GuildSettings {
                     guild_id: id.get() as i64,
                     lang: new_lang_str.clone(),
+                    timezone: "UTC".to_string(),
                 })
                 .on_conflict(guild_id)
                 .do_update()
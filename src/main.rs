@@ -40,8 +40,20 @@ pub mod util {
 
 pub mod modules {
     pub mod moderation {
+        pub mod lockdown;
+        pub mod bans;
+        pub mod case_types;
+        pub mod cases;
+        pub mod evidence;
+        pub mod locks;
         pub mod logs;
+        pub mod nicknames;
         pub mod notifications;
+        pub mod presets;
+        pub mod quarantine;
+        pub mod roles;
+        pub mod transcripts;
+        pub mod voice;
     }
 }
 
@@ -81,8 +93,10 @@ async fn main() {
             event_handler: |framework, event| Box::pin(handle_event(framework, event)),
             commands: vec![
                 ban::ban(), kick::kick(), mute::mute(), unmute::unmute(),
-                help::help(), cases::cases(), clear::clear(), channel::channel(),
-                warn::warn(),
+                help::help(), case::case(), cases::cases(), clear::clear(), channel::channel(),
+                warn::warn(), lockdown::lockdown(), nick::nick(), role::role(),
+                voice::voice(), quarantine::quarantine(), release::release(),
+                modstats::modstats(), modinfo::modinfo(), note::note(), undo::undo(),
                 config::config(),
             ],
             ..Default::default()
@@ -0,0 +1,176 @@
+use std::collections::{HashMap, HashSet};
+use chrono::Utc;
+use diesel::prelude::*;
+use diesel_async::RunQueryDsl;
+use poise::serenity_prelude::{Ban, CacheHttp, Context, GuildId, SerenityError, UserId, UserPagination};
+use poise::serenity_prelude::nonmax::NonMaxU64;
+use crate::{BotError, Data};
+use crate::database::models::Cases;
+use crate::localization::manager::{Language, LocalizationManager, TranslationParam};
+use crate::modules::moderation::cases::{create_case, revoke_case};
+use crate::modules::moderation::logs::{log_action, LogData, LogType};
+
+/// Discord returns at most this many bans per request
+const BANS_PAGE_SIZE: usize = 1000;
+
+/// Users listed per section of a reconciliation report, the rest are only counted
+const MAX_REPORTED_USERS: usize = 20;
+
+/// Reason stored on cases whose ban was lifted outside the bot
+const CLOSED_REASON: &str = "ban lifted outside the bot";
+
+/// How a guild's `BAN` cases differed from its actual ban list
+#[derive(Default)]
+pub struct BanReconciliation {
+    /// Cases closed because the user isn't banned anymore, as (user, case ID)
+    pub closed: Vec<(UserId, i32)>,
+    /// Bans that had no case and got one, as (user, case ID)
+    pub imported: Vec<(UserId, i32)>,
+    /// Bans that have no case and were left alone
+    pub untracked: Vec<UserId>,
+}
+
+impl BanReconciliation {
+    pub fn is_empty(&self) -> bool {
+        !self.has_changes() && self.untracked.is_empty()
+    }
+
+    /// Whether any case was closed or imported. Untracked bans alone change nothing.
+    pub fn has_changes(&self) -> bool {
+        !self.closed.is_empty() || !self.imported.is_empty()
+    }
+
+    /// Report for `/cases sync`. Untracked bans are left out of the log, they would repeat on every startup.
+    pub fn report(&self, include_untracked: bool, lang: Language, locales: &LocalizationManager) -> String {
+        let mut sections = Vec::new();
+
+        if !self.closed.is_empty() {
+            sections.push(locales.get("modules.moderation.bans.report_closed", lang, &[
+                TranslationParam::from(format_users(self.closed.iter().map(|(user, case)| format!("<@{}> (#{})", user, case)), lang, locales))
+            ]));
+        }
+
+        if !self.imported.is_empty() {
+            sections.push(locales.get("modules.moderation.bans.report_imported", lang, &[
+                TranslationParam::from(format_users(self.imported.iter().map(|(user, case)| format!("<@{}> (#{})", user, case)), lang, locales))
+            ]));
+        }
+
+        if include_untracked && !self.untracked.is_empty() {
+            sections.push(locales.get("modules.moderation.bans.report_untracked", lang, &[
+                TranslationParam::from(format_users(self.untracked.iter().map(|user| format!("<@{}>", user)), lang, locales))
+            ]));
+        }
+
+        sections.join(" \n")
+    }
+}
+
+fn format_users(users: impl ExactSizeIterator<Item = String>, lang: Language, locales: &LocalizationManager) -> String {
+    let total = users.len();
+    let mut listed = users.take(MAX_REPORTED_USERS).collect::<Vec<_>>().join(", ");
+
+    if total > MAX_REPORTED_USERS {
+        listed.push(' ');
+        listed.push_str(&locales.get("modules.moderation.bans.report_more", lang, &[
+            TranslationParam::from((total - MAX_REPORTED_USERS).to_string())
+        ]));
+    }
+
+    listed
+}
+
+/// Discord answers 404 when unbanning someone who isn't banned
+pub fn is_unknown_ban(why: &SerenityError) -> bool {
+    matches!(why, SerenityError::Http(why) if why.status_code().is_some_and(|status| status.as_u16() == 404))
+}
+
+async fn fetch_guild_bans(ctx: &Context, guild: GuildId) -> Result<Vec<Ban>, BotError> {
+    let mut bans = Vec::new();
+
+    loop {
+        let after = bans.last().map(|ban: &Ban| UserPagination::After(ban.user.id));
+        let page = guild.bans(ctx.http(), after, None).await?;
+        let page_len = page.len();
+
+        bans.extend(page);
+
+        if page_len < BANS_PAGE_SIZE {
+            return Ok(bans);
+        }
+    }
+}
+
+/// Compares the guild's open `BAN` cases with its ban list. Cases whose ban is gone get closed,
+/// bans without a case get one when `import` is set and are only reported otherwise.
+pub async fn reconcile_bans(ctx: &Context, data: &Data, guild: GuildId, moderator: UserId, import: bool) -> Result<BanReconciliation, BotError> {
+    use crate::database::schema::cases::dsl::*;
+
+    let bans = fetch_guild_bans(ctx, guild).await?;
+
+    let open_cases = data.db.run(|conn| {
+        cases
+            .filter(guild_id.eq(guild.get() as i64))
+            .filter(case_type.eq("BAN"))
+            .filter(revoked_at.is_null())
+            .select(Cases::as_select())
+            .load::<Cases>(conn)
+    }).await?;
+
+    let banned: HashSet<UserId> = bans.iter().map(|ban| ban.user.id).collect();
+    let tracked: HashMap<UserId, i32> = open_cases.iter()
+        .map(|case| (UserId::new(u64::from(NonMaxU64::try_from(case.user_id as u64).unwrap())), case.case_id))
+        .collect();
+
+    let mut reconciliation = BanReconciliation::default();
+
+    for case in &open_cases {
+        let user = UserId::new(u64::from(NonMaxU64::try_from(case.user_id as u64).unwrap()));
+
+        if !banned.contains(&user) {
+            revoke_case(data, guild, case.case_id, CLOSED_REASON).await?;
+            reconciliation.closed.push((user, case.case_id));
+        }
+    }
+
+    for ban in bans.iter().filter(|ban| !tracked.contains_key(&ban.user.id)) {
+        if !import {
+            reconciliation.untracked.push(ban.user.id);
+            continue;
+        }
+
+        let new_case_id = create_case(data, Cases {
+            guild_id: guild.get() as i64,
+            user_id: ban.user.id.get() as i64,
+            moderator_id: moderator.get() as i64,
+            case_id: 0,
+            case_type: "BAN".to_string(),
+            reason: ban.reason.as_ref().map(|ban_reason| ban_reason.to_string()),
+            created_at: Utc::now(),
+            end_date: None,
+            points: None,
+            revoked_at: None,
+            revoke_reason: None
+        }).await?;
+
+        reconciliation.imported.push((ban.user.id, new_case_id));
+    }
+
+    if reconciliation.has_changes() {
+        let guild_lang = data.localization_manager
+            .get_guild_language(data.db.clone(), guild).await.unwrap_or(Language::English);
+
+        let log_data = LogData {
+            ctx: Some(ctx),
+            data: Some(data),
+            guild_id: Some(guild.get()),
+            moderator_id: Some(moderator),
+            message: Some(reconciliation.report(false, guild_lang, &data.localization_manager)),
+            ..LogData::default()
+        };
+
+        log_action(LogType::BanSync, log_data).await?;
+    }
+
+    Ok(reconciliation)
+}
@@ -0,0 +1,97 @@
+use diesel::prelude::*;
+use diesel_async::RunQueryDsl;
+use poise::serenity_prelude::{AutocompleteChoice, CreateAutocompleteResponse, GuildId};
+use crate::{Context, Data};
+use crate::database::models::CaseTypes;
+
+/// Discord shows at most this many autocomplete choices
+const MAX_CHOICES: usize = 25;
+
+/// Longest key that fits `case_types.name`
+pub const MAX_CASE_TYPE_LENGTH: usize = 32;
+
+/// Case types the bot creates itself, guilds can't define types with these names
+pub const BUILTIN_CASE_TYPES: [&str; 10] = [
+    "BAN", "KICK", "MUTE", "WARN", "NOTE", "QUARANTINE", "VOICE_MUTE", "VOICE_DEAFEN", "VOICE_KICK", "VOICE_MOVE",
+];
+
+/// Turns a display name like "Verbal warning" into the key stored on cases, e.g. `VERBAL_WARNING`
+pub fn case_type_key(display_name: &str) -> String {
+    display_name
+        .to_uppercase()
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>()
+        .join("_")
+        .chars()
+        .take(MAX_CASE_TYPE_LENGTH)
+        .collect()
+}
+
+pub async fn fetch_case_types(data: &Data, guild: GuildId) -> Vec<CaseTypes> {
+    use crate::database::schema::case_types::dsl::*;
+
+    data.db.run(|conn| {
+        case_types
+            .filter(guild_id.eq(guild.get() as i64))
+            .order(name.asc())
+            .select(CaseTypes::as_select())
+            .load::<CaseTypes>(conn)
+    }).await.unwrap_or_default()
+}
+
+pub async fn find_case_type(data: &Data, guild: GuildId, case_type_name: &str) -> Option<CaseTypes> {
+    fetch_case_types(data, guild).await.into_iter().find(|case_type| case_type.name == case_type_name)
+}
+
+/// Case types whose points add up to a user's total: warns and every custom type that counts
+pub async fn counting_case_types(data: &Data, guild: GuildId) -> Vec<String> {
+    let mut counting = vec!["WARN".to_string()];
+
+    counting.extend(fetch_case_types(data, guild).await
+        .into_iter()
+        .filter(|case_type| case_type.counts_points)
+        .map(|case_type| case_type.name));
+
+    counting
+}
+
+/// Autocompletes the guild's custom case types, for `/case create`
+pub async fn autocomplete_custom_case_type<'a>(ctx: Context<'_>, partial: &'a str) -> CreateAutocompleteResponse<'a> {
+    let Some(guild) = ctx.guild_id() else {
+        return CreateAutocompleteResponse::new();
+    };
+
+    let partial = partial.to_lowercase();
+
+    let choices: Vec<AutocompleteChoice> = fetch_case_types(&ctx.data(), guild).await
+        .into_iter()
+        .filter(|case_type| case_type.display_name.to_lowercase().contains(&partial) || case_type.name.to_lowercase().contains(&partial))
+        .take(MAX_CHOICES)
+        .map(|case_type| AutocompleteChoice::new(case_type.display_name, case_type.name))
+        .collect();
+
+    CreateAutocompleteResponse::new().set_choices(choices)
+}
+
+/// Autocompletes both the built-in and the guild's custom case types, for filters
+pub async fn autocomplete_case_type<'a>(ctx: Context<'_>, partial: &'a str) -> CreateAutocompleteResponse<'a> {
+    let Some(guild) = ctx.guild_id() else {
+        return CreateAutocompleteResponse::new();
+    };
+
+    let partial = partial.to_lowercase();
+
+    let builtin = BUILTIN_CASE_TYPES.iter().map(|name| (name.to_string(), name.to_string()));
+    let custom = fetch_case_types(&ctx.data(), guild).await
+        .into_iter()
+        .map(|case_type| (case_type.display_name, case_type.name));
+
+    let choices: Vec<AutocompleteChoice> = builtin.chain(custom)
+        .filter(|(display, name)| display.to_lowercase().contains(&partial) || name.to_lowercase().contains(&partial))
+        .take(MAX_CHOICES)
+        .map(|(display, name)| AutocompleteChoice::new(display, name))
+        .collect();
+
+    CreateAutocompleteResponse::new().set_choices(choices)
+}
@@ -0,0 +1,187 @@
+use chrono::{DateTime, Utc};
+use diesel::prelude::*;
+use diesel_async::RunQueryDsl;
+use poise::serenity_prelude::{GuildId, UserId};
+use crate::{BotError, Data};
+use crate::database::models::{Cases, ModerationSettings};
+use crate::modules::moderation::case_types::counting_case_types;
+
+/// `warn_expire_time` of guilds whose warnings don't expire unless `/warn` says otherwise
+pub const NEVER_EXPIRE: i64 = 0;
+
+/// Inserts the case under the next free case ID, ignoring the one it was built with, and returns that ID
+pub async fn create_case(data: &Data, mut new_case: Cases) -> Result<i32, BotError> {
+    use crate::database::schema::cases::dsl::*;
+
+    let new_case_id: i32 = data.db.run(|conn| {
+        cases
+            .select(diesel::dsl::max(case_id))
+            .first::<Option<i32>>(conn)
+    }).await?.unwrap_or(0) + 1;
+
+    new_case.case_id = new_case_id;
+
+    data.db.run(|conn| {
+        diesel::insert_into(cases)
+            .values(&new_case)
+            .execute(conn)
+    }).await?;
+
+    Ok(new_case_id)
+}
+
+/// Points a user holds right now across the unexpired cases of every type that counts toward the total.
+/// With warn decay enabled the total drops by a set amount every period, replayed over the case history
+pub async fn active_points(data: &Data, guild: GuildId, user: UserId) -> Result<i64, BotError> {
+    use crate::database::schema::cases::dsl::*;
+
+    let counting = counting_case_types(data, guild).await;
+    let decay = fetch_warn_decay(data, guild).await;
+
+    let history = data.db.run(|conn| {
+        cases
+            .filter(guild_id.eq(guild.get() as i64))
+            .filter(user_id.eq(user.get() as i64))
+            .filter(case_type.eq_any(counting))
+            .filter(end_date.is_null().or(end_date.gt(Utc::now())))
+            .filter(revoked_at.is_null())
+            .filter(points.is_not_null())
+            .order(created_at.asc())
+            .select((created_at, points))
+            .load::<(DateTime<Utc>, Option<i32>)>(conn)
+    }).await?;
+
+    let history: Vec<(DateTime<Utc>, i64)> = history.into_iter()
+        .map(|(at, case_points)| (at, case_points.unwrap_or(0) as i64))
+        .collect();
+
+    Ok(match decay {
+        Some((amount, period)) => decayed_points(&history, amount, period, Utc::now()),
+        None => history.iter().map(|(_, case_points)| case_points).sum(),
+    })
+}
+
+/// The guild's decay as (points, period in seconds), `None` when points only expire with their case
+async fn fetch_warn_decay(data: &Data, guild: GuildId) -> Option<(i64, i64)> {
+    use crate::database::schema::moderation_settings::dsl::*;
+
+    let settings = data.db.run(|conn| {
+        moderation_settings
+            .filter(guild_id.eq(guild.get() as i64))
+            .select(ModerationSettings::as_select())
+            .first::<ModerationSettings>(conn)
+    }).await.ok()?;
+
+    match (settings.warn_decay_points, settings.warn_decay_period) {
+        (amount, Some(period)) if amount > 0 && period > 0 => Some((amount as i64, period)),
+        _ => None,
+    }
+}
+
+/// Replays the history in order: the total loses `amount` per full `period` and never goes below zero.
+/// The decay clock starts when points are added to an empty total, so a clean record doesn't bank decay.
+fn decayed_points(history: &[(DateTime<Utc>, i64)], amount: i64, period: i64, now: DateTime<Utc>) -> i64 {
+    let decay_until = |total: i64, clock: DateTime<Utc>, until: DateTime<Utc>| {
+        let periods = (until - clock).num_seconds().max(0) / period;
+        ((total - periods * amount).max(0), clock + chrono::Duration::seconds(periods * period))
+    };
+
+    let mut total = 0;
+    let mut clock = None;
+
+    for &(at, case_points) in history {
+        if let Some(started) = clock {
+            let (decayed, advanced) = decay_until(total, started, at);
+            total = decayed;
+            clock = Some(advanced);
+        }
+
+        if total == 0 {
+            clock = Some(at);
+        }
+
+        total += case_points;
+    }
+
+    match clock {
+        Some(started) => decay_until(total, started, now).0,
+        None => total,
+    }
+}
+
+/// Keeps the case in the history but takes it out of effect: its points stop counting
+/// and it no longer has an end date for the expiry checks to act on
+pub async fn revoke_case(data: &Data, guild: GuildId, revoked_case_id: i32, reason: &str) -> Result<(), BotError> {
+    use crate::database::schema::cases::dsl::*;
+
+    let reason = reason.to_string();
+
+    data.db.run(|conn| {
+        diesel::update(cases
+            .filter(guild_id.eq(guild.get() as i64))
+            .filter(case_id.eq(revoked_case_id))
+        ).set((
+            revoked_at.eq(Some(Utc::now())),
+            revoke_reason.eq(Some(reason)),
+            end_date.eq(None::<DateTime<Utc>>),
+        )).execute(conn)
+    }).await?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone;
+    use super::*;
+
+    const DAY: i64 = 86400;
+
+    fn start() -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap()
+    }
+
+    fn after(seconds: i64) -> DateTime<Utc> {
+        start() + chrono::Duration::seconds(seconds)
+    }
+
+    #[test]
+    fn empty_history_has_no_points() {
+        assert_eq!(decayed_points(&[], 1, DAY, after(10 * DAY)), 0);
+    }
+
+    #[test]
+    fn partial_periods_do_not_decay() {
+        let history = [(start(), 3)];
+
+        assert_eq!(decayed_points(&history, 1, DAY, after(DAY - 1)), 3);
+        assert_eq!(decayed_points(&history, 1, DAY, after(DAY)), 2);
+        assert_eq!(decayed_points(&history, 1, DAY, after(2 * DAY + DAY / 2)), 1);
+    }
+
+    #[test]
+    fn partial_period_carries_over_a_new_warn() {
+        let history = [(start(), 3), (after(DAY + DAY / 2), 1)];
+
+        // One period passed before the second warn, the half day left over completes at 2 days
+        assert_eq!(decayed_points(&history, 1, DAY, after(2 * DAY - 1)), 3);
+        assert_eq!(decayed_points(&history, 1, DAY, after(2 * DAY)), 2);
+    }
+
+    #[test]
+    fn decay_stops_at_zero() {
+        let history = [(start(), 1)];
+
+        assert_eq!(decayed_points(&history, 2, DAY, after(10 * DAY)), 0);
+    }
+
+    #[test]
+    fn new_warn_after_reaching_zero_restarts_the_clock() {
+        let history = [(start(), 2), (after(5 * DAY + DAY / 2), 2)];
+
+        // The days spent at zero don't count against the new warn
+        assert_eq!(decayed_points(&history, 1, DAY, after(6 * DAY)), 2);
+        assert_eq!(decayed_points(&history, 1, DAY, after(6 * DAY + DAY / 2)), 1);
+        assert_eq!(decayed_points(&history, 1, DAY, after(8 * DAY)), 0);
+    }
+}
@@ -0,0 +1,181 @@
+use std::collections::HashMap;
+use chrono::Utc;
+use diesel::prelude::*;
+use diesel_async::RunQueryDsl;
+use lazy_static::lazy_static;
+use poise::serenity_prelude::{Attachment, CacheHttp, ChannelId, Context, CreateAllowedMentions, CreateAttachment, CreateEmbed, CreateMessage, GuildId, Message, MessageId, Timestamp};
+use regex::Regex;
+use crate::{BotError, Data};
+use crate::database::models::{CaseEvidence, ModerationSettings};
+use crate::util::color::BotColors;
+
+/// Leaves room for the author and channel header in the archived copy
+const MAX_ARCHIVED_CONTENT_LENGTH: usize = 1800;
+
+lazy_static! {
+    static ref MESSAGE_LINK_REGEX: Regex = Regex::new(r"^https://(?:(?:ptb|canary)\.)?discord(?:app)?\.com/channels/(\d+)/(\d+)/(\d+)$").unwrap();
+}
+
+/// Splits a message link into its guild, channel and message, `None` if it isn't one
+pub fn parse_message_link(link: &str) -> Option<(GuildId, ChannelId, MessageId)> {
+    let captures = MESSAGE_LINK_REGEX.captures(link.trim())?;
+
+    Some((
+        GuildId::new(captures[1].parse().ok()?),
+        ChannelId::new(captures[2].parse().ok()?),
+        MessageId::new(captures[3].parse().ok()?),
+    ))
+}
+
+/// Only messages from the guild itself can be archived
+pub fn is_guild_message_link(link: &str, guild: GuildId) -> bool {
+    parse_message_link(link).is_some_and(|(link_guild, _, _)| link_guild == guild)
+}
+
+async fn fetch_archive_channel(data: &Data, guild: GuildId) -> Option<ChannelId> {
+    use crate::database::schema::moderation_settings::dsl::*;
+
+    data.db.run(|conn| {
+        moderation_settings
+            .filter(guild_id.eq(guild.get() as i64))
+            .select(ModerationSettings::as_select())
+            .first::<ModerationSettings>(conn)
+    }).await.ok()?.evidence_channel.map(|id| ChannelId::new(id as u64))
+}
+
+fn archive_header(case_id: i32, kind: &str) -> CreateEmbed<'static> {
+    CreateEmbed::new()
+        .color(BotColors::Default.color())
+        .title(format!("Case #{} — {}", case_id, kind.to_lowercase()))
+        .timestamp(Timestamp::from(Utc::now()))
+}
+
+async fn archive_attachment(ctx: &Context, channel: ChannelId, case_id: i32, attachment: &Attachment) -> Result<Message, BotError> {
+    let bytes = attachment.download().await?;
+
+    let message = channel.send_message(ctx.http(), CreateMessage::new()
+        .embed(archive_header(case_id, "ATTACHMENT"))
+        .add_file(CreateAttachment::bytes(bytes, attachment.filename.to_string()))
+    ).await?;
+
+    Ok(message)
+}
+
+async fn archive_message(ctx: &Context, channel: ChannelId, case_id: i32, source_channel: ChannelId, source_message: MessageId) -> Result<Message, BotError> {
+    let original = source_channel.message(ctx.http(), source_message).await?;
+
+    let content: String = original.content.chars().take(MAX_ARCHIVED_CONTENT_LENGTH).collect();
+
+    let mut files = Vec::new();
+    for attachment in original.attachments.iter() {
+        if let Ok(bytes) = attachment.download().await {
+            files.push(CreateAttachment::bytes(bytes, attachment.filename.to_string()));
+        }
+    }
+
+    let message = channel.send_message(ctx.http(), CreateMessage::new()
+        .embed(archive_header(case_id, "LINK")
+            .description(format!(
+                "`Author:` <@{}> \n`Channel:` <#{}> \n`Sent:` <t:{}:f> \n\n{}",
+                original.author.id,
+                source_channel,
+                original.timestamp.unix_timestamp(),
+                content
+            )))
+        .add_files(files)
+        .allowed_mentions(CreateAllowedMentions::new())
+    ).await?;
+
+    Ok(message)
+}
+
+/// Re-hosts the evidence in the guild's evidence channel and records it on the case.
+/// Without an evidence channel, or if re-hosting fails, the original URL is kept.
+/// Returns the recorded URLs.
+pub async fn archive_evidence(ctx: &Context, data: &Data, guild: GuildId, case: i32, attachment: Option<&Attachment>, link: Option<&str>) -> Result<Vec<String>, BotError> {
+    use crate::database::schema::case_evidence::dsl::*;
+
+    let archive_channel = fetch_archive_channel(data, guild).await;
+    let mut evidence = Vec::new();
+
+    if let Some(attachment) = attachment {
+        let archived = match archive_channel {
+            Some(channel) => archive_attachment(ctx, channel, case, attachment).await.ok(),
+            None => None,
+        };
+
+        evidence.push(CaseEvidence {
+            guild_id: guild.get() as i64,
+            case_id: case,
+            kind: "ATTACHMENT".to_string(),
+            url: archived.map_or(attachment.url.to_string(), |message| message.link()),
+            source: Some(attachment.url.to_string()),
+            created_at: Utc::now(),
+        });
+    }
+
+    if let Some(link) = link {
+        let archived = match (archive_channel, parse_message_link(link)) {
+            (Some(channel), Some((_, source_channel, source_message))) => archive_message(ctx, channel, case, source_channel, source_message).await.ok(),
+            _ => None,
+        };
+
+        evidence.push(CaseEvidence {
+            guild_id: guild.get() as i64,
+            case_id: case,
+            kind: "LINK".to_string(),
+            url: archived.map_or(link.trim().to_string(), |message| message.link()),
+            source: Some(link.trim().to_string()),
+            created_at: Utc::now(),
+        });
+    }
+
+    if evidence.is_empty() {
+        return Ok(vec![]);
+    }
+
+    let urls = evidence.iter().map(|entry| entry.url.clone()).collect();
+
+    data.db.run(|conn| {
+        diesel::insert_into(case_evidence)
+            .values(&evidence)
+            .execute(conn)
+    }).await?;
+
+    Ok(urls)
+}
+
+/// Evidence recorded on the given cases, keyed by case ID
+pub async fn fetch_evidence(data: &Data, guild: GuildId, case_ids: Vec<i32>) -> HashMap<i32, Vec<CaseEvidence>> {
+    use crate::database::schema::case_evidence::dsl::*;
+
+    let entries = data.db.run(|conn| {
+        case_evidence
+            .filter(guild_id.eq(guild.get() as i64))
+            .filter(case_id.eq_any(case_ids))
+            .order(created_at.asc())
+            .select(CaseEvidence::as_select())
+            .load::<CaseEvidence>(conn)
+    }).await.unwrap_or_default();
+
+    let mut evidence: HashMap<i32, Vec<CaseEvidence>> = HashMap::new();
+    for entry in entries {
+        evidence.entry(entry.case_id).or_default().push(entry);
+    }
+
+    evidence
+}
+
+/// Removes the evidence of deleted cases
+pub async fn delete_evidence(data: &Data, guild: GuildId, case_ids: Vec<i32>) -> Result<(), BotError> {
+    use crate::database::schema::case_evidence::dsl::*;
+
+    data.db.run(|conn| {
+        diesel::delete(case_evidence
+            .filter(guild_id.eq(guild.get() as i64))
+            .filter(case_id.eq_any(case_ids))
+        ).execute(conn)
+    }).await?;
+
+    Ok(())
+}
@@ -0,0 +1,168 @@
+use chrono::{DateTime, Utc};
+use diesel::prelude::*;
+use diesel_async::{AsyncConnection, RunQueryDsl};
+use diesel_async::scoped_futures::ScopedFutureExt;
+use poise::serenity_prelude::{CacheHttp, ChannelId, ChannelType, Context, GuildId, PermissionOverwrite, PermissionOverwriteType, Permissions, UserId};
+use poise::serenity_prelude::nonmax::NonMaxU64;
+use crate::{BotError, Data};
+use crate::database::models::{LockdownOverwrites, Lockdowns, ModerationSettings};
+use crate::modules::moderation::locks::LOCKED_PERMISSIONS;
+
+pub async fn is_locked_down(data: &Data, guild: GuildId) -> bool {
+    use crate::database::schema::lockdowns::dsl::*;
+
+    data.db.run(|conn| {
+        lockdowns
+            .filter(guild_id.eq(guild.get() as i64))
+            .select(Lockdowns::as_select())
+            .first::<Lockdowns>(conn)
+    }).await.ok().is_some()
+}
+
+/// Snapshots the @everyone overwrite of every targeted channel, then denies sending messages in all of them.
+/// Channels that can't be edited are skipped. Returns the number of locked channels.
+pub async fn start_lockdown(
+    ctx: &Context,
+    data: &Data,
+    guild: GuildId,
+    moderator: UserId,
+    lockdown_reason: Option<String>,
+    lockdown_end: Option<DateTime<Utc>>,
+) -> Result<usize, BotError> {
+    use crate::database::schema::moderation_settings::dsl::{moderation_settings, guild_id as settings_guild_id};
+
+    let settings = data.db.run(|conn| {
+        moderation_settings
+            .filter(settings_guild_id.eq(guild.get() as i64))
+            .select(ModerationSettings::as_select())
+            .first::<ModerationSettings>(conn)
+    }).await?;
+
+    let everyone = guild.everyone_role();
+
+    let channels: Vec<_> = {
+        let guild = ctx.cache.guild(guild).ok_or("Guild not cached")?;
+        guild.channels.iter()
+            .filter(|c| c.kind == ChannelType::Text)
+            .filter(|c| settings.lockdown_channels.is_empty() || settings.lockdown_channels.contains(&(c.id.get() as i64)))
+            .map(|c| {
+                let overwrite = c.permission_overwrites.iter()
+                    .find(|p| p.kind == PermissionOverwriteType::Role(everyone))
+                    .cloned();
+                (c.id, overwrite)
+            })
+            .collect()
+    };
+
+    let snapshots: Vec<LockdownOverwrites> = channels.iter().map(|(channel, overwrite)| LockdownOverwrites {
+        guild_id: guild.get() as i64,
+        channel_id: channel.get() as i64,
+        allow: overwrite.as_ref().map(|o| o.allow.bits() as i64),
+        deny: overwrite.as_ref().map(|o| o.deny.bits() as i64),
+    }).collect();
+
+    let new_lockdown = Lockdowns {
+        guild_id: guild.get() as i64,
+        moderator_id: moderator.get() as i64,
+        reason: lockdown_reason.clone(),
+        created_at: Utc::now(),
+        end_date: lockdown_end,
+    };
+
+    // The snapshot is written before touching any channel, so a crash mid-lockdown can still be undone
+    let (new_snapshots, new_lockdown) = (&snapshots, &new_lockdown);
+    data.db.run(|conn| conn.transaction::<_, diesel::result::Error, _>(|conn| async move {
+        diesel::insert_into(crate::database::schema::lockdown_overwrites::table)
+            .values(new_snapshots)
+            .execute(conn)
+            .await?;
+
+        diesel::insert_into(crate::database::schema::lockdowns::table)
+            .values(new_lockdown)
+            .execute(conn)
+            .await?;
+
+        Ok(())
+    }.scope_boxed())).await?;
+
+    let mut failed_channels = Vec::new();
+
+    for (channel, overwrite) in &channels {
+        let (mut allow, mut deny) = overwrite.as_ref()
+            .map_or((Permissions::empty(), Permissions::empty()), |o| (o.allow, o.deny));
+
+        allow.remove(LOCKED_PERMISSIONS);
+        deny.insert(LOCKED_PERMISSIONS);
+
+        // One channel we can't edit shouldn't leave the rest unlocked
+        if let Err(why) = channel.create_permission(ctx.http(), PermissionOverwrite {
+            allow,
+            deny,
+            kind: PermissionOverwriteType::Role(everyone),
+        }, lockdown_reason.as_deref()).await {
+            eprintln!("Failed to lock channel {}: {:?}", channel, why);
+            failed_channels.push(channel.get() as i64);
+        }
+    }
+
+    // Restoring these later would overwrite whatever the channel has by then
+    if !failed_channels.is_empty() {
+        use crate::database::schema::lockdown_overwrites::dsl::*;
+
+        let failed = failed_channels.clone();
+        data.db.run(|conn| {
+            diesel::delete(lockdown_overwrites
+                .filter(guild_id.eq(guild.get() as i64))
+                .filter(channel_id.eq_any(failed))
+            ).execute(conn)
+        }).await?;
+    }
+
+    Ok(channels.len() - failed_channels.len())
+}
+
+/// Restores the @everyone overwrites saved by [`start_lockdown`] and clears the lockdown.
+/// Returns the number of restored channels.
+pub async fn end_lockdown(ctx: &Context, data: &Data, guild: GuildId) -> Result<usize, BotError> {
+    use crate::database::schema::lockdown_overwrites::dsl::{lockdown_overwrites, guild_id as overwrites_guild_id};
+    use crate::database::schema::lockdowns::dsl::{lockdowns, guild_id as lockdowns_guild_id};
+
+    let snapshots = data.db.run(|conn| {
+        lockdown_overwrites
+            .filter(overwrites_guild_id.eq(guild.get() as i64))
+            .select(LockdownOverwrites::as_select())
+            .load::<LockdownOverwrites>(conn)
+    }).await?;
+
+    let everyone = guild.everyone_role();
+    let mut restored = 0;
+
+    for snapshot in &snapshots {
+        let channel = ChannelId::new(u64::from(NonMaxU64::try_from(snapshot.channel_id as u64).unwrap()));
+
+        let result = match (snapshot.allow, snapshot.deny) {
+            (Some(allow), Some(deny)) => channel.create_permission(ctx.http(), PermissionOverwrite {
+                allow: Permissions::from_bits_truncate(allow as u64),
+                deny: Permissions::from_bits_truncate(deny as u64),
+                kind: PermissionOverwriteType::Role(everyone),
+            }, Some("Lockdown ended")).await,
+            _ => channel.delete_permission(ctx.http(), PermissionOverwriteType::Role(everyone), Some("Lockdown ended")).await,
+        };
+
+        // A channel deleted during the lockdown has nothing left to restore
+        match result {
+            Ok(()) => restored += 1,
+            Err(why) => eprintln!("Failed to restore overwrite for channel {}: {:?}", channel, why),
+        }
+    }
+
+    data.db.run(|conn| {
+        diesel::delete(lockdown_overwrites.filter(overwrites_guild_id.eq(guild.get() as i64))).execute(conn)
+    }).await?;
+
+    data.db.run(|conn| {
+        diesel::delete(lockdowns.filter(lockdowns_guild_id.eq(guild.get() as i64))).execute(conn)
+    }).await?;
+
+    Ok(restored)
+}
@@ -0,0 +1,201 @@
+use diesel::prelude::*;
+use diesel_async::RunQueryDsl;
+use poise::serenity_prelude::{CacheHttp, Context, GuildChannel, PermissionOverwrite, PermissionOverwriteType, Permissions, RoleId, SerenityError};
+use poise::serenity_prelude::nonmax::NonMaxU64;
+use crate::{BotError, Data};
+use crate::database::models::{ChannelLockOverwrites, ChannelLocks};
+
+pub(crate) const LOCKED_PERMISSIONS: Permissions = Permissions::SEND_MESSAGES.union(Permissions::SEND_MESSAGES_IN_THREADS);
+
+/// Denies sending messages for each role, saving the role's previous overwrite first.
+/// Roles that already can't send messages are skipped. If an overwrite can't be changed, the ones
+/// already changed are put back and the error is returned. Returns the roles that got locked.
+pub async fn lock_channel(ctx: &Context, data: &Data, channel: &GuildChannel, roles: &[RoleId], lock: ChannelLocks) -> Result<Vec<RoleId>, BotError> {
+    use crate::database::schema::channel_lock_overwrites::dsl::*;
+
+    let mut locked_roles = Vec::new();
+    let mut snapshots = Vec::new();
+
+    for role in roles {
+        let overwrite = channel.permission_overwrites.iter()
+            .find(|p| p.kind == PermissionOverwriteType::Role(*role))
+            .cloned();
+
+        if overwrite.as_ref().is_some_and(|o| o.deny.contains(Permissions::SEND_MESSAGES)) {
+            continue;
+        }
+
+        snapshots.push(ChannelLockOverwrites {
+            channel_id: channel.id.get() as i64,
+            role_id: role.get() as i64,
+            allow: overwrite.as_ref().map(|o| o.allow.bits() as i64),
+            deny: overwrite.as_ref().map(|o| o.deny.bits() as i64),
+        });
+        locked_roles.push((*role, overwrite));
+    }
+
+    if locked_roles.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    data.db.run(|conn| {
+        diesel::insert_into(channel_lock_overwrites)
+            .values(&snapshots)
+            .on_conflict((channel_id, role_id))
+            .do_nothing()
+            .execute(conn)
+    }).await?;
+
+    for (index, (role, overwrite)) in locked_roles.iter().enumerate() {
+        let (mut allow_bits, mut deny_bits) = overwrite.as_ref()
+            .map_or((Permissions::empty(), Permissions::empty()), |o| (o.allow, o.deny));
+
+        allow_bits.remove(LOCKED_PERMISSIONS);
+        deny_bits.insert(LOCKED_PERMISSIONS);
+
+        let result = channel.id.create_permission(ctx.http(), PermissionOverwrite {
+            allow: allow_bits,
+            deny: deny_bits,
+            kind: PermissionOverwriteType::Role(*role),
+        }, lock.reason.as_deref()).await;
+
+        // Put back what this lock already changed, so the channel isn't left half locked
+        if let Err(why) = result {
+            for snapshot in &snapshots[..index] {
+                if let Err(why) = restore_overwrite(ctx, channel, snapshot).await {
+                    eprintln!("Failed to roll back overwrite in channel {}: {:?}", channel.id, why);
+                }
+            }
+
+            let locked_channel = channel.id.get() as i64;
+            let new_roles: Vec<i64> = snapshots.iter().map(|snapshot| snapshot.role_id).collect();
+
+            data.db.run(|conn| {
+                diesel::delete(channel_lock_overwrites
+                    .filter(channel_id.eq(locked_channel))
+                    .filter(role_id.eq_any(new_roles))
+                ).execute(conn)
+            }).await?;
+
+            return Err(why.into());
+        }
+    }
+
+    save_channel_lock(data, lock.clone()).await?;
+
+    Ok(locked_roles.into_iter().map(|(role, _)| role).collect())
+}
+
+/// Discord answers 404 for overwrites of roles deleted since the lock
+fn is_unknown_overwrite(why: &SerenityError) -> bool {
+    matches!(why, SerenityError::Http(why) if why.status_code().is_some_and(|status| status.as_u16() == 404))
+}
+
+/// Puts a role's overwrite back the way the snapshot saved it, removing it if the lock created it
+async fn restore_overwrite(ctx: &Context, channel: &GuildChannel, snapshot: &ChannelLockOverwrites) -> Result<(), SerenityError> {
+    let role = RoleId::new(u64::from(NonMaxU64::try_from(snapshot.role_id as u64).unwrap()));
+
+    match (snapshot.allow, snapshot.deny) {
+        (Some(allow_bits), Some(deny_bits)) => channel.id.create_permission(ctx.http(), PermissionOverwrite {
+            allow: Permissions::from_bits_truncate(allow_bits as u64),
+            deny: Permissions::from_bits_truncate(deny_bits as u64),
+            kind: PermissionOverwriteType::Role(role),
+        }, None).await,
+        _ => channel.id.delete_permission(ctx.http(), PermissionOverwriteType::Role(role), None).await,
+    }
+}
+
+/// Puts back every overwrite saved by [`lock_channel`], removing the ones the lock created.
+/// Channels locked without a snapshot only get the lock's deny lifted from @everyone.
+/// Returns `false` if the channel wasn't locked.
+pub async fn unlock_channel(ctx: &Context, data: &Data, channel: &GuildChannel, everyone: RoleId) -> Result<bool, BotError> {
+    use crate::database::schema::channel_lock_overwrites::dsl::*;
+
+    let snapshots = data.db.run(|conn| {
+        channel_lock_overwrites
+            .filter(channel_id.eq(channel.id.get() as i64))
+            .select(ChannelLockOverwrites::as_select())
+            .load::<ChannelLockOverwrites>(conn)
+    }).await?;
+
+    if snapshots.is_empty() {
+        let Some(mut overwrite) = channel.permission_overwrites.iter()
+            .find(|p| p.kind == PermissionOverwriteType::Role(everyone))
+            .cloned() else {
+            return Ok(false);
+        };
+
+        if !overwrite.deny.contains(Permissions::SEND_MESSAGES) {
+            return Ok(false);
+        }
+
+        overwrite.deny.remove(LOCKED_PERMISSIONS);
+        channel.id.create_permission(ctx.http(), overwrite, None).await?;
+
+        return Ok(true);
+    }
+
+    for snapshot in &snapshots {
+        match restore_overwrite(ctx, channel, snapshot).await {
+            Ok(()) => {},
+            // The role was deleted since the lock, there's nothing left to restore
+            Err(why) if is_unknown_overwrite(&why) => {},
+            Err(why) => return Err(why.into()),
+        }
+    }
+
+    Ok(true)
+}
+
+/// Saves the lock. A channel locked again keeps the later of the two end dates so the
+/// second lock doesn't cut the first one short, and a lock without an end date always wins.
+pub async fn save_channel_lock(data: &Data, mut lock: ChannelLocks) -> Result<(), BotError> {
+    use crate::database::schema::channel_locks::dsl::*;
+
+    let locked_channel = lock.channel_id;
+    let existing = data.db.run(|conn| {
+        channel_locks
+            .filter(channel_id.eq(locked_channel))
+            .select(ChannelLocks::as_select())
+            .first::<ChannelLocks>(conn)
+    }).await.ok();
+
+    if let Some(existing) = existing {
+        lock.end_date = match (existing.end_date, lock.end_date) {
+            (Some(existing_end), Some(new_end)) => Some(existing_end.max(new_end)),
+            _ => None,
+        };
+    }
+
+    data.db.run(|conn| {
+        diesel::insert_into(channel_locks)
+            .values(&lock)
+            .on_conflict(channel_id)
+            .do_update()
+            .set((
+                moderator_id.eq(lock.moderator_id),
+                reason.eq(lock.reason.clone()),
+                created_at.eq(lock.created_at),
+                end_date.eq(lock.end_date),
+            ))
+            .execute(conn)
+    }).await?;
+
+    Ok(())
+}
+
+/// Forgets a channel lock and its saved overwrites, cancelling its scheduled unlock if there was one
+pub async fn remove_channel_lock(data: &Data, channel: i64) -> Result<(), BotError> {
+    use crate::database::schema::channel_locks::dsl::{channel_locks, channel_id as locks_channel_id};
+    use crate::database::schema::channel_lock_overwrites::dsl::{channel_lock_overwrites, channel_id as overwrites_channel_id};
+
+    data.db.run(|conn| {
+        diesel::delete(channel_lock_overwrites.filter(overwrites_channel_id.eq(channel))).execute(conn)
+    }).await?;
+
+    data.db.run(|conn| {
+        diesel::delete(channel_locks.filter(locks_channel_id.eq(channel))).execute(conn)
+    }).await?;
+
+    Ok(())
+}
@@ -1,14 +1,16 @@
 use std::ops::BitAnd;
 use std::str::FromStr;
-use chrono::Utc;
+use chrono::{DateTime, Utc};
 use diesel::{ExpressionMethods};
-use poise::serenity_prelude::{CacheHttp, ChannelId, Context, CreateEmbed, CreateEmbedAuthor, CreateEmbedFooter, CreateMessage, GuildId, Timestamp, UserId};
+use poise::serenity_prelude::{CacheHttp, ChannelId, Context, CreateAttachment, CreateEmbed, CreateEmbedAuthor, CreateEmbedFooter, CreateMessage, GuildId, Timestamp, UserId};
 use crate::{BotError, Data};
 use crate::database::models::*;
 use diesel::prelude::*;
 use diesel_async::RunQueryDsl;
 use crate::localization::manager::{Language, LocalizationManager};
 use crate::util::color::BotColors;
+use crate::util::time::{fetch_guild_timezone, format_datetime};
+use crate::util::timestamp::{Format, TimestampExt};
 use strum_macros::EnumIter;
 
 
@@ -28,6 +30,25 @@ pub enum LogType {
     Warn = 1 << 9,               // 00000010 00000000
     RemoveWarn = 1 << 10,        // 00000100 00000000
     RemoveMultipleWarns = 1 << 11, // 00001000 00000000
+    Slowmode = 1 << 12,          // 00010000 00000000
+    Lockdown = 1 << 13,          // 00100000 00000000
+    LockdownEnd = 1 << 14,       // 01000000 00000000
+    Nickname = 1 << 15,          // 10000000 00000000
+    RoleAdd = 1 << 16,           // 00000001 00000000 00000000
+    RoleRemove = 1 << 17,        // 00000010 00000000 00000000
+    VoiceMute = 1 << 18,         // 00000100 00000000 00000000
+    VoiceDeafen = 1 << 19,       // 00001000 00000000 00000000
+    VoiceKick = 1 << 20,         // 00010000 00000000 00000000
+    VoiceMove = 1 << 21,         // 00100000 00000000 00000000
+    VoiceUnmute = 1 << 22,       // 01000000 00000000 00000000
+    VoiceUndeafen = 1 << 23,     // 10000000 00000000 00000000
+    Quarantine = 1 << 24,        // 00000001 00000000 00000000 00000000
+    Release = 1 << 25,           // 00000010 00000000 00000000 00000000
+    Note = 1 << 26,              // 00000100 00000000 00000000 00000000
+    NoteRemove = 1 << 27,        // 00001000 00000000 00000000 00000000
+    CaseCreate = 1 << 28,        // 00010000 00000000 00000000 00000000
+    Undo = 1 << 29,              // 00100000 00000000 00000000 00000000
+    BanSync = 1 << 30,           // 01000000 00000000 00000000 00000000
 }
 
 impl LogType {
@@ -45,6 +66,25 @@ impl LogType {
             LogType::Warn => manager.get("commands.configuration.moderation.actions.warn", lang, &[]),
             LogType::RemoveWarn => manager.get("commands.configuration.moderation.actions.remove_warn", lang, &[]),
             LogType::RemoveMultipleWarns => manager.get("commands.configuration.moderation.actions.remove_multiple_warns", lang, &[]),
+            LogType::Slowmode => manager.get("commands.configuration.moderation.actions.slowmode", lang, &[]),
+            LogType::Lockdown => manager.get("commands.configuration.moderation.actions.lockdown", lang, &[]),
+            LogType::LockdownEnd => manager.get("commands.configuration.moderation.actions.lockdown_end", lang, &[]),
+            LogType::Nickname => manager.get("commands.configuration.moderation.actions.nickname", lang, &[]),
+            LogType::RoleAdd => manager.get("commands.configuration.moderation.actions.role_add", lang, &[]),
+            LogType::RoleRemove => manager.get("commands.configuration.moderation.actions.role_remove", lang, &[]),
+            LogType::VoiceMute => manager.get("commands.configuration.moderation.actions.voice_mute", lang, &[]),
+            LogType::VoiceDeafen => manager.get("commands.configuration.moderation.actions.voice_deafen", lang, &[]),
+            LogType::VoiceKick => manager.get("commands.configuration.moderation.actions.voice_kick", lang, &[]),
+            LogType::VoiceMove => manager.get("commands.configuration.moderation.actions.voice_move", lang, &[]),
+            LogType::VoiceUnmute => manager.get("commands.configuration.moderation.actions.voice_unmute", lang, &[]),
+            LogType::VoiceUndeafen => manager.get("commands.configuration.moderation.actions.voice_undeafen", lang, &[]),
+            LogType::Quarantine => manager.get("commands.configuration.moderation.actions.quarantine", lang, &[]),
+            LogType::Release => manager.get("commands.configuration.moderation.actions.release", lang, &[]),
+            LogType::Note => manager.get("commands.configuration.moderation.actions.note", lang, &[]),
+            LogType::NoteRemove => manager.get("commands.configuration.moderation.actions.note_remove", lang, &[]),
+            LogType::CaseCreate => manager.get("commands.configuration.moderation.actions.case_create", lang, &[]),
+            LogType::Undo => manager.get("commands.configuration.moderation.actions.undo", lang, &[]),
+            LogType::BanSync => manager.get("commands.configuration.moderation.actions.ban_sync", lang, &[]),
         }
     }
     pub fn as_bit(&self) -> u32 {
@@ -70,6 +110,25 @@ impl FromStr for LogType {
             "Warn" => Ok(LogType::Warn),
             "RemoveWarn" => Ok(LogType::RemoveWarn),
             "RemoveMultipleWarns" => Ok(LogType::RemoveMultipleWarns),
+            "Slowmode" => Ok(LogType::Slowmode),
+            "Lockdown" => Ok(LogType::Lockdown),
+            "LockdownEnd" => Ok(LogType::LockdownEnd),
+            "Nickname" => Ok(LogType::Nickname),
+            "RoleAdd" => Ok(LogType::RoleAdd),
+            "RoleRemove" => Ok(LogType::RoleRemove),
+            "VoiceMute" => Ok(LogType::VoiceMute),
+            "VoiceDeafen" => Ok(LogType::VoiceDeafen),
+            "VoiceKick" => Ok(LogType::VoiceKick),
+            "VoiceMove" => Ok(LogType::VoiceMove),
+            "VoiceUnmute" => Ok(LogType::VoiceUnmute),
+            "VoiceUndeafen" => Ok(LogType::VoiceUndeafen),
+            "Quarantine" => Ok(LogType::Quarantine),
+            "Release" => Ok(LogType::Release),
+            "Note" => Ok(LogType::Note),
+            "NoteRemove" => Ok(LogType::NoteRemove),
+            "CaseCreate" => Ok(LogType::CaseCreate),
+            "Undo" => Ok(LogType::Undo),
+            "BanSync" => Ok(LogType::BanSync),
             _ => Err(BotError::from("Invalid log type")),
         }
     }
@@ -91,6 +150,25 @@ pub fn get_active_log_types(mask: u32, manager: &LocalizationManager, lang: Lang
         LogType::Warn,
         LogType::RemoveWarn,
         LogType::RemoveMultipleWarns,
+        LogType::Slowmode,
+        LogType::Lockdown,
+        LogType::LockdownEnd,
+        LogType::Nickname,
+        LogType::RoleAdd,
+        LogType::RoleRemove,
+        LogType::VoiceMute,
+        LogType::VoiceDeafen,
+        LogType::VoiceKick,
+        LogType::VoiceMove,
+        LogType::VoiceUnmute,
+        LogType::VoiceUndeafen,
+        LogType::Quarantine,
+        LogType::Release,
+        LogType::Note,
+        LogType::NoteRemove,
+        LogType::CaseCreate,
+        LogType::Undo,
+        LogType::BanSync,
     ] {
         if mask & log_type.as_bit() != 0 {
             active_types.push(log_type.to_string(manager, lang));
@@ -104,7 +182,11 @@ pub fn string_to_log_type(s: &str, manager: &LocalizationManager, lang: Language
     LogType::from_str(s).ok().or_else(|| {
         [LogType::ClearMessages, LogType::ClearChannel, LogType::Mute, LogType::Unmute,
             LogType::Kick, LogType::Lock, LogType::Unlock, LogType::Ban, LogType::Unban,
-            LogType::Warn, LogType::RemoveWarn, LogType::RemoveMultipleWarns]
+            LogType::Warn, LogType::RemoveWarn, LogType::RemoveMultipleWarns, LogType::Slowmode,
+            LogType::Lockdown, LogType::LockdownEnd, LogType::Nickname, LogType::RoleAdd,
+            LogType::RoleRemove, LogType::VoiceMute, LogType::VoiceDeafen, LogType::VoiceKick,
+            LogType::VoiceMove, LogType::VoiceUnmute, LogType::VoiceUndeafen, LogType::Quarantine,
+            LogType::Release, LogType::Note, LogType::NoteRemove, LogType::CaseCreate, LogType::Undo, LogType::BanSync]
             .iter()
             .find(|&log_type| log_type.to_string(manager, lang) == s)
             .copied()
@@ -121,12 +203,22 @@ pub struct LogData<'a> {
     pub moderator_id: Option<UserId>,
     pub reason: Option<String>,
     pub duration: Option<String>,
+    pub ends_at: Option<DateTime<Utc>>,
+    pub evidence: Option<Vec<String>>,
+    pub delay: Option<String>,
     pub case_id: Option<i32>,
     pub points: Option<i32>,
     pub messages_deleted: Option<u32>,
+    pub roles: Option<Vec<u64>>,
+    pub channels_locked: Option<u32>,
     pub messages: Option<Vec<String>>,
+    pub transcript_id: Option<i32>,
+    pub not_preserved: Option<Vec<String>>,
+    pub attachments: Option<Vec<CreateAttachment<'static>>>,
     pub removed_warns: Option<Vec<(UserId, i32, i32)>>,
     pub old_message: Option<String>,
+    pub old_nickname: Option<String>,
+    pub nickname: Option<String>,
     pub message: Option<String>
 }
 
@@ -141,12 +233,22 @@ impl<'a> LogData<'a> {
             channel_id: None,
             reason: None,
             duration: None,
+            ends_at: None,
+            evidence: None,
+            delay: None,
             case_id: None,
             points: None,
             messages_deleted: None,
+            roles: None,
+            channels_locked: None,
             messages: None,
+            transcript_id: None,
+            not_preserved: None,
+            attachments: None,
             removed_warns: None,
             old_message: None,
+            old_nickname: None,
+            nickname: None,
             message: None,
         }
     }
@@ -162,7 +264,7 @@ impl BitAnd<LogType> for i32 {
     }
 }
 
-pub async fn log_action(log_type: LogType, log_data: LogData<'_>) -> Result<(), BotError> {
+pub async fn log_action(log_type: LogType, mut log_data: LogData<'_>) -> Result<(), BotError> {
     use crate::database::schema::moderation_settings::dsl::*;
     let data = log_data.data.unwrap();
     
@@ -185,8 +287,12 @@ pub async fn log_action(log_type: LogType, log_data: LogData<'_>) -> Result<(),
         return Ok(());
     }
 
+    let message = CreateMessage::new()
+        .embed(log_embed)
+        .add_files(log_data.attachments.take().unwrap_or_default());
+
     ChannelId::new(log.default_log_channel.unwrap() as u64)
-        .send_message(log_data.ctx.unwrap().http(), CreateMessage::new().embed(log_embed))
+        .send_message(log_data.ctx.unwrap().http(), message)
         .await?;
 
     Ok(())
@@ -197,14 +303,19 @@ async fn create_log_embed(log_type: &LogType, log_data: &LogData<'_>) -> CreateE
         LogType::ClearMessages => (
             format!("{} Messages Purged", log_data.messages_deleted.unwrap_or(0)),
             format!(
-                "`Channel:` <#{}> \n\n ```{}```",
+                "`Channel:` <#{}> \n`Transcript:` {} \n\n ```{}```",
                 log_data.channel_id.unwrap(),
+                log_data.transcript_id.map_or("N/A".to_string(), |id| format!("#{}", id)),
                 log_data.messages.as_ref().map_or("Could not log messages".to_string(), |m| m.join("\n"))
             ),
         ),
         LogType::ClearChannel => (
             "Channel nuked".to_string(),
-            format!("`Channel:` <#{}> \n\n ```All messages purged```", log_data.channel_id.unwrap()),
+            format!(
+                "`Channel:` <#{}> \n`Not preserved:` {} \n\n ```All messages purged```",
+                log_data.channel_id.unwrap(),
+                log_data.not_preserved.as_ref().map_or("N/A".to_string(), |items| items.join(", "))
+            ),
         ),
         LogType::Mute => (
             "User Muted".to_string(),
@@ -232,12 +343,190 @@ async fn create_log_embed(log_type: &LogType, log_data: &LogData<'_>) -> CreateE
         ),
         LogType::Lock => (
             "Channel Locked".to_string(),
-            format!("`Channel:` <#{}> \n`Reason:` **{}**", log_data.channel_id.unwrap(), log_data.reason.as_deref().unwrap_or("No reason provided")),
+            format!(
+                "`Channel:` <#{}> \n`Roles:` {} \n`Reason:` **{}** \n`Duration:` {}",
+                log_data.channel_id.unwrap(),
+                log_data.roles.as_ref().map_or("@everyone".to_string(), |r| r.iter().map(|id| format!("<@&{}>", id)).collect::<Vec<_>>().join(", ")),
+                log_data.reason.as_deref().unwrap_or("No reason provided"),
+                log_data.duration.as_deref().unwrap_or("N/A")
+            ),
         ),
         LogType::Unlock => (
             "Channel Unlocked".to_string(),
             format!("`Channel:` <#{}> \n`Reason:` **{}**", log_data.channel_id.unwrap(), log_data.reason.as_deref().unwrap_or("No reason provided")),
         ),
+        LogType::Slowmode => (
+            "Slowmode Changed".to_string(),
+            format!(
+                "`Channel:` <#{}> \n`Delay:` {} \n`Duration:` {} \n`Reason:` **{}**",
+                log_data.channel_id.unwrap(),
+                log_data.delay.as_deref().unwrap_or("N/A"),
+                log_data.duration.as_deref().unwrap_or("N/A"),
+                log_data.reason.as_deref().unwrap_or("No reason provided")
+            ),
+        ),
+        LogType::Lockdown => (
+            "Server Lockdown Started".to_string(),
+            format!(
+                "`Channels locked:` {} \n`Duration:` {} \n`Reason:` **{}**",
+                log_data.channels_locked.unwrap_or(0),
+                log_data.duration.as_deref().unwrap_or("N/A"),
+                log_data.reason.as_deref().unwrap_or("No reason provided")
+            ),
+        ),
+        LogType::LockdownEnd => (
+            "Server Lockdown Ended".to_string(),
+            format!(
+                "`Channels restored:` {} \n`Reason:` **{}**",
+                log_data.channels_locked.unwrap_or(0),
+                log_data.reason.as_deref().unwrap_or("No reason provided")
+            ),
+        ),
+        LogType::Nickname => (
+            "Nickname Changed".to_string(),
+            format!(
+                "`User:` <@{}> \n`Old nickname:` {} \n`New nickname:` {} \n`Reason:` **{}**",
+                log_data.user_id.unwrap(),
+                log_data.old_nickname.as_deref().unwrap_or("None"),
+                log_data.nickname.as_deref().unwrap_or("None"),
+                log_data.reason.as_deref().unwrap_or("No reason provided")
+            ),
+        ),
+        LogType::RoleAdd => (
+            "Role Added".to_string(),
+            format!(
+                "`User:` <@{}> \n`Role:` {} \n`Duration:` {} \n`Reason:` **{}**",
+                log_data.user_id.unwrap(),
+                log_data.roles.as_ref().map_or("N/A".to_string(), |r| r.iter().map(|id| format!("<@&{}>", id)).collect::<Vec<_>>().join(", ")),
+                log_data.duration.as_deref().unwrap_or("Permanent"),
+                log_data.reason.as_deref().unwrap_or("No reason provided")
+            ),
+        ),
+        LogType::RoleRemove => (
+            "Role Removed".to_string(),
+            format!(
+                "`User:` <@{}> \n`Role:` {} \n`Duration:` {} \n`Reason:` **{}**",
+                log_data.user_id.unwrap(),
+                log_data.roles.as_ref().map_or("N/A".to_string(), |r| r.iter().map(|id| format!("<@&{}>", id)).collect::<Vec<_>>().join(", ")),
+                log_data.duration.as_deref().unwrap_or("Permanent"),
+                log_data.reason.as_deref().unwrap_or("No reason provided")
+            ),
+        ),
+        LogType::VoiceMute => (
+            "User Voice Muted".to_string(),
+            format!(
+                "`User:` <@{}> \n`Reason:` {} \n`Duration:` {} \n`Case ID:` {}",
+                log_data.user_id.unwrap(),
+                log_data.reason.as_deref().unwrap_or("No reason provided"),
+                log_data.duration.as_deref().unwrap_or("Permanent"),
+                log_data.case_id.unwrap_or(0)
+            ),
+        ),
+        LogType::VoiceDeafen => (
+            "User Voice Deafened".to_string(),
+            format!(
+                "`User:` <@{}> \n`Reason:` {} \n`Duration:` {} \n`Case ID:` {}",
+                log_data.user_id.unwrap(),
+                log_data.reason.as_deref().unwrap_or("No reason provided"),
+                log_data.duration.as_deref().unwrap_or("Permanent"),
+                log_data.case_id.unwrap_or(0)
+            ),
+        ),
+        LogType::VoiceKick => (
+            "User Disconnected From Voice".to_string(),
+            format!(
+                "`User:` <@{}> \n`Reason:` {} \n`Case ID:` {}",
+                log_data.user_id.unwrap(),
+                log_data.reason.as_deref().unwrap_or("No reason provided"),
+                log_data.case_id.unwrap_or(0)
+            ),
+        ),
+        LogType::VoiceMove => (
+            "User Moved In Voice".to_string(),
+            format!(
+                "`User:` <@{}> \n`Channel:` <#{}> \n`Reason:` {} \n`Case ID:` {}",
+                log_data.user_id.unwrap(),
+                log_data.channel_id.unwrap(),
+                log_data.reason.as_deref().unwrap_or("No reason provided"),
+                log_data.case_id.unwrap_or(0)
+            ),
+        ),
+        LogType::VoiceUnmute => (
+            "User Voice Unmuted".to_string(),
+            format!("`User:` <@{}> \n`Reason:` {}", log_data.user_id.unwrap(), log_data.reason.as_deref().unwrap_or("No reason provided")),
+        ),
+        LogType::VoiceUndeafen => (
+            "User Voice Undeafened".to_string(),
+            format!("`User:` <@{}> \n`Reason:` {}", log_data.user_id.unwrap(), log_data.reason.as_deref().unwrap_or("No reason provided")),
+        ),
+        LogType::Quarantine => (
+            "User Quarantined".to_string(),
+            format!(
+                "`User:` <@{}> \n`Roles saved:` {} \n`Reason:` {} \n`Case ID:` {}",
+                log_data.user_id.unwrap(),
+                log_data.roles.as_ref().map_or("N/A".to_string(), |r| r.iter().map(|id| format!("<@&{}>", id)).collect::<Vec<_>>().join(", ")),
+                log_data.reason.as_deref().unwrap_or("No reason provided"),
+                log_data.case_id.unwrap_or(0)
+            ),
+        ),
+        LogType::Release => (
+            "User Released From Quarantine".to_string(),
+            format!(
+                "`User:` <@{}> \n`Roles restored:` {} \n`Reason:` {}",
+                log_data.user_id.unwrap(),
+                log_data.roles.as_ref().map_or("N/A".to_string(), |r| r.iter().map(|id| format!("<@&{}>", id)).collect::<Vec<_>>().join(", ")),
+                log_data.reason.as_deref().unwrap_or("No reason provided")
+            ),
+        ),
+        LogType::Note => (
+            "Note Added".to_string(),
+            format!(
+                "`User:` <@{}> \n`Note:` {} \n`Case ID:` {}",
+                log_data.user_id.unwrap(),
+                log_data.reason.as_deref().unwrap_or("N/A"),
+                log_data.case_id.unwrap_or(0)
+            ),
+        ),
+        LogType::NoteRemove => (
+            "Note Removed".to_string(),
+            format!(
+                "`User:` <@{}> \n`Note:` {} \n`Case ID:` {}",
+                log_data.user_id.unwrap(),
+                log_data.reason.as_deref().unwrap_or("N/A"),
+                log_data.case_id.unwrap_or(0)
+            ),
+        ),
+        LogType::CaseCreate => (
+            format!("Case Created: {}", log_data.message.as_deref().unwrap_or("N/A")),
+            format!(
+                "`User:` <@{}> \n`Reason:` {} \n`Points:` {} \n`Duration:` {} \n`Case ID:` {}",
+                log_data.user_id.unwrap(),
+                log_data.reason.as_deref().unwrap_or("No reason provided"),
+                log_data.points.unwrap_or(0),
+                log_data.duration.as_deref().unwrap_or("Permanent"),
+                log_data.case_id.unwrap_or(0)
+            ),
+        ),
+        LogType::Undo => (
+            format!("Action Undone: {}", log_data.message.as_deref().unwrap_or("N/A")),
+            match (log_data.user_id, log_data.channel_id) {
+                (Some(user_id), _) => format!(
+                    "`User:` <@{}> \n`Reason:` {} \n`Case ID:` {}",
+                    user_id,
+                    log_data.reason.as_deref().unwrap_or("No reason provided"),
+                    log_data.case_id.unwrap_or(0)
+                ),
+                (None, channel_id) => format!(
+                    "`Channel:` <#{}> \n`Reason:` {}",
+                    channel_id.unwrap_or(0),
+                    log_data.reason.as_deref().unwrap_or("No reason provided")
+                ),
+            },
+        ),
+        LogType::BanSync => (
+            "Bans Reconciled".to_string(),
+            log_data.message.clone().unwrap_or_default(),
+        ),
         LogType::Ban => (
             "User Banned".to_string(),
             format!(
@@ -311,12 +600,29 @@ async fn create_log_embed(log_type: &LogType, log_data: &LogData<'_>) -> CreateE
         // ),
     };
 
+    let guild_tz = fetch_guild_timezone(log_data.data.unwrap(), GuildId::new(log_data.guild_id.unwrap())).await;
+
+    let description = match log_data.ends_at {
+        Some(ends_at) => format!(
+            "{} \n`Ends:` {} ({})",
+            description,
+            Timestamp::from(ends_at).to_discord_timestamp(Format::LongDateShortTime),
+            format_datetime(ends_at, guild_tz)
+        ),
+        None => description,
+    };
+
+    let description = match log_data.evidence.as_deref() {
+        Some(evidence) if !evidence.is_empty() => format!("{} \n`Evidence:` {}", description, evidence.join(", ")),
+        _ => description,
+    };
+
     let author = log_data.moderator_id.unwrap().to_user(log_data.ctx.unwrap().http()).await.unwrap();
     let guild = GuildId::new(log_data.guild_id.unwrap()).to_partial_guild(log_data.ctx.unwrap().http()).await.unwrap();
     CreateEmbed::new()
         .color(BotColors::Default.color())
         .author(CreateEmbedAuthor::new(title).icon_url(guild.icon_url().unwrap_or_default())).url(guild.icon_url().unwrap_or_default())
         .description(description)
-        .footer(CreateEmbedFooter::new(format!("Action by: {} ({})", author.clone().global_name.unwrap(), author.clone().id)).icon_url(author.clone().avatar_url().unwrap()))
+        .footer(CreateEmbedFooter::new(format!("Action by: {} ({}) • {}", author.global_name.as_deref().unwrap_or(author.name.as_str()), author.id, format_datetime(Utc::now(), guild_tz))).icon_url(author.face()))
         .timestamp(Timestamp::from(Utc::now()))
 }
@@ -0,0 +1,98 @@
+use diesel::prelude::*;
+use diesel_async::RunQueryDsl;
+use lazy_static::lazy_static;
+use poise::serenity_prelude::{CacheHttp, Context, EditMember, Member};
+use regex::Regex;
+use crate::{BotError, Data};
+use crate::database::models::ModerationSettings;
+use crate::modules::moderation::logs::{log_action, LogData, LogType};
+
+pub const DEFAULT_NICKNAME_TEMPLATE: &str = "{username}";
+
+/// Used when the rendered template is still hoisted, e.g. a username made only of symbols
+const FALLBACK_NICKNAME: &str = "Dehoisted";
+
+lazy_static! {
+    static ref HOIST_REGEX: Regex = Regex::new(r"^[^\p{L}\p{N}]").unwrap();
+}
+
+pub fn is_hoisted(name: &str) -> bool {
+    HOIST_REGEX.is_match(name)
+}
+
+/// Patterns are matched case-insensitively. Invalid ones never match.
+pub fn matches_patterns(name: &str, patterns: &[String]) -> bool {
+    patterns.iter()
+        .filter_map(|pattern| Regex::new(&format!("(?i){}", pattern)).ok())
+        .any(|re| re.is_match(name))
+}
+
+/// Fills `{username}` and `{id}` in the template and strips any leading hoisting characters
+pub fn render_template(template: &str, member: &Member) -> String {
+    let rendered = template
+        .replace("{username}", &member.user.name)
+        .replace("{id}", &member.user.id.to_string());
+
+    let dehoisted = rendered.trim_start_matches(|c: char| !c.is_alphanumeric());
+
+    if dehoisted.is_empty() {
+        FALLBACK_NICKNAME.to_string()
+    } else {
+        dehoisted.chars().take(32).collect()
+    }
+}
+
+/// Renames the member if the guild has auto-dehoist enabled and their display name is hoisted or matches a pattern
+pub async fn enforce_nickname(ctx: &Context, data: &Data, member: &Member) -> Result<(), BotError> {
+    use crate::database::schema::moderation_settings::dsl::*;
+
+    if member.user.bot() {
+        return Ok(());
+    }
+
+    let settings = data.db.run(|conn| {
+        moderation_settings
+            .filter(guild_id.eq(member.guild_id.get() as i64))
+            .select(ModerationSettings::as_select())
+            .first::<ModerationSettings>(conn)
+    }).await?;
+
+    if !settings.auto_dehoist {
+        return Ok(());
+    }
+
+    let display_name = member.display_name().to_string();
+
+    if !is_hoisted(&display_name) && !matches_patterns(&display_name, &settings.nickname_patterns) {
+        return Ok(());
+    }
+
+    let new_nickname = render_template(&settings.nickname_template, member);
+
+    if new_nickname == display_name {
+        return Ok(());
+    }
+
+    member.guild_id.edit_member(ctx.http(), member.user.id, EditMember::new()
+        .nickname(new_nickname.clone())
+        .audit_log_reason("Automatic nickname moderation")
+    ).await?;
+
+    let bot_id = *data.client_id.read().unwrap();
+
+    let log_data = LogData {
+        ctx: Some(ctx),
+        data: Some(data),
+        guild_id: Some(member.guild_id.get()),
+        user_id: Some(member.user.id.get()),
+        moderator_id: Some(bot_id),
+        old_nickname: Some(display_name),
+        nickname: Some(new_nickname),
+        reason: Some("Automatic nickname moderation".into()),
+        ..LogData::default()
+    };
+
+    log_action(LogType::Nickname, log_data).await?;
+
+    Ok(())
+}
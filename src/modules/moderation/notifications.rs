@@ -3,14 +3,21 @@ use std::sync::Arc;
 use std::time::Duration;
 use diesel::dsl::now;
 use diesel::QueryDsl;
-use poise::serenity_prelude::{CacheHttp, Context, GuildId, Ready, UserId};
-use crate::Data;
+use poise::serenity_prelude::{CacheHttp, ChannelId, Context, EditChannel, GuildId, Ready, RoleId, UserId};
+use crate::{BotError, Data};
 
 use crate::database::models::*;
 use diesel::prelude::*;
 use diesel_async::{ RunQueryDsl};
 use poise::serenity_prelude::nonmax::NonMaxU64;
+use crate::modules::moderation::bans::is_unknown_ban;
+use crate::modules::moderation::evidence::delete_evidence;
+use crate::modules::moderation::lockdown::end_lockdown;
+use crate::modules::moderation::locks::{remove_channel_lock, unlock_channel};
 use crate::modules::moderation::logs::{log_action, LogData, LogType};
+use crate::modules::moderation::voice::expire_voice_punishment;
+use crate::localization::manager::Language;
+use crate::util::time::format_duration;
 
 async fn unban_check(data: Arc<Data>, ctx: Arc<Context>, data_about_bot: Ready) -> Result<(), Box<dyn error::Error>> {
     use crate::database::schema::cases::dsl::*;
@@ -27,7 +34,12 @@ async fn unban_check(data: Arc<Data>, ctx: Arc<Context>, data_about_bot: Ready)
        let guild = GuildId::new(u64::from(NonMaxU64::try_from(case.guild_id as u64).unwrap()));
         let user = UserId::new(u64::from(NonMaxU64::try_from(case.user_id as u64).unwrap()));
         
-        guild.unban(ctx.http(), user, "Ban expired".into()).await?;
+        match guild.unban(ctx.http(), user, "Ban expired".into()).await {
+            Ok(()) => {},
+            // Lifted outside the bot in the meantime, the case only needs removing
+            Err(why) if is_unknown_ban(&why) => {},
+            Err(why) => return Err(why.into()),
+        }
 
         let log_data = LogData {
             ctx: Some(&ctx),
@@ -40,11 +52,19 @@ async fn unban_check(data: Arc<Data>, ctx: Arc<Context>, data_about_bot: Ready)
             ..LogData::default()
         };
         
-        log_action(LogType::Unban, log_data).await.unwrap();
+        if let Err(why) = log_action(LogType::Unban, log_data).await {
+            eprintln!("Failed to send log: {:?}", why);
+        }
 
         let _ = data.db.run(|conn| {
-            diesel::delete(cases.filter(case_id.eq(case.case_id))).execute(conn)
+            diesel::delete(cases
+                .filter(guild_id.eq(case.guild_id))
+                .filter(case_id.eq(case.case_id))
+            ).execute(conn)
         }).await?;
+
+        // The case ID can be handed out again, its evidence mustn't carry over
+        delete_evidence(&data, GuildId::new(u64::from(NonMaxU64::try_from(case.guild_id as u64).unwrap())), vec![case.case_id]).await?;
     }
     
     Ok(())
@@ -79,13 +99,216 @@ async fn remove_warn_check(data: Arc<Data>, ctx: Arc<Context>, data_about_bot: R
             ..LogData::default()
         };
 
-        log_action(LogType::RemoveWarn, log_data).await.unwrap();
+        if let Err(why) = log_action(LogType::RemoveWarn, log_data).await {
+            eprintln!("Failed to send log: {:?}", why);
+        }
 
         
         let _ = data.db.run(|conn| {
-            diesel::delete(cases.filter(case_id.eq(case.case_id))).execute(conn)
+            diesel::delete(cases
+                .filter(guild_id.eq(case.guild_id))
+                .filter(case_id.eq(case.case_id))
+            ).execute(conn)
+        }).await?;
+
+        // The case ID can be handed out again, its evidence mustn't carry over
+        delete_evidence(&data, GuildId::new(u64::from(NonMaxU64::try_from(case.guild_id as u64).unwrap())), vec![case.case_id]).await?;
+    }
+    Ok(())
+}
+
+async fn slowmode_revert_check(data: Arc<Data>, ctx: Arc<Context>, data_about_bot: Ready) -> Result<(), Box<dyn error::Error>> {
+    use crate::database::schema::slowmodes::dsl::*;
+
+    let slowmodes_results = data.db.run(|conn| {
+        slowmodes
+            .filter(end_date.lt(now))
+            .select(Slowmodes::as_select())
+            .load::<Slowmodes>(conn)
+    }).await?;
+
+    for slowmode in slowmodes_results {
+        let guild = GuildId::new(u64::from(NonMaxU64::try_from(slowmode.guild_id as u64).unwrap()));
+        let channel = ChannelId::new(u64::from(NonMaxU64::try_from(slowmode.channel_id as u64).unwrap()));
+
+        // The channel may have been deleted while the slowmode ran, the row goes either way
+        // so one channel we can't edit doesn't hold back the others
+        if ctx.http().get_channel(channel).await.ok().and_then(|c| c.guild()).is_some() {
+            match channel.edit(ctx.http(), EditChannel::new().rate_limit_per_user(slowmode.previous_delay as u16)).await {
+                Ok(_) => {
+                    let guild_lang = data.localization_manager
+                        .get_guild_language(data.db.clone(), guild).await.unwrap_or(Language::English);
+
+                    let log_data = LogData {
+                        ctx: Some(&ctx),
+                        data: Some(&data),
+                        guild_id: Some(guild.get()),
+                        channel_id: Some(channel.get()),
+                        moderator_id: Some(data_about_bot.user.id),
+                        reason: Some("Slowmode expired".into()),
+                        delay: Some(format_duration(slowmode.previous_delay as u64, guild_lang)),
+                        ..LogData::default()
+                    };
+
+                    if let Err(why) = log_action(LogType::Slowmode, log_data).await {
+                        eprintln!("Failed to send log: {:?}", why);
+                    }
+                },
+                Err(why) => eprintln!("Failed to revert slowmode in {}: {:?}", channel, why),
+            }
+        }
+
+        let _ = data.db.run(|conn| {
+            diesel::delete(slowmodes.filter(channel_id.eq(slowmode.channel_id))).execute(conn)
+        }).await?;
+    }
+
+    Ok(())
+}
+
+async fn lockdown_end_check(data: Arc<Data>, ctx: Arc<Context>, data_about_bot: Ready) -> Result<(), BotError> {
+    use crate::database::schema::lockdowns::dsl::*;
+
+    let lockdowns_results = data.db.run(|conn| {
+        lockdowns
+            .filter(end_date.lt(now))
+            .select(Lockdowns::as_select())
+            .load::<Lockdowns>(conn)
+    }).await?;
+
+    for lockdown in lockdowns_results {
+        let guild = GuildId::new(u64::from(NonMaxU64::try_from(lockdown.guild_id as u64).unwrap()));
+
+        let restored = end_lockdown(&ctx, &data, guild).await?;
+
+        let log_data = LogData {
+            ctx: Some(&ctx),
+            data: Some(&data),
+            guild_id: Some(guild.get()),
+            moderator_id: Some(data_about_bot.user.id),
+            reason: Some("Lockdown expired".into()),
+            channels_locked: Some(restored as u32),
+            ..LogData::default()
+        };
+
+        if let Err(why) = log_action(LogType::LockdownEnd, log_data).await {
+            eprintln!("Failed to send log: {:?}", why);
+        }
+    }
+
+    Ok(())
+}
+
+async fn unlock_check(data: Arc<Data>, ctx: Arc<Context>, data_about_bot: Ready) -> Result<(), BotError> {
+    use crate::database::schema::channel_locks::dsl::*;
+
+    let locks_results = data.db.run(|conn| {
+        channel_locks
+            .filter(end_date.lt(now))
+            .select(ChannelLocks::as_select())
+            .load::<ChannelLocks>(conn)
+    }).await?;
+
+    for lock in locks_results {
+        let guild = GuildId::new(u64::from(NonMaxU64::try_from(lock.guild_id as u64).unwrap()));
+        let channel = ChannelId::new(u64::from(NonMaxU64::try_from(lock.channel_id as u64).unwrap()));
+
+        // The channel may have been deleted while it was locked
+        if let Some(channel) = ctx.http().get_channel(channel).await.ok().and_then(|c| c.guild()) {
+            if unlock_channel(&ctx, &data, &channel, guild.everyone_role()).await? {
+                let log_data = LogData {
+                    ctx: Some(&ctx),
+                    data: Some(&data),
+                    guild_id: Some(guild.get()),
+                    channel_id: Some(channel.id.get()),
+                    moderator_id: Some(data_about_bot.user.id),
+                    reason: Some("Lock expired".into()),
+                    ..LogData::default()
+                };
+
+                if let Err(why) = log_action(LogType::Unlock, log_data).await {
+                    eprintln!("Failed to send log: {:?}", why);
+                }
+            }
+        }
+
+        remove_channel_lock(&data, lock.channel_id).await?;
+    }
+
+    Ok(())
+}
+
+async fn temp_role_check(data: Arc<Data>, ctx: Arc<Context>, data_about_bot: Ready) -> Result<(), BotError> {
+    use crate::database::schema::temp_roles::dsl::*;
+
+    let temp_roles_results = data.db.run(|conn| {
+        temp_roles
+            .filter(end_date.lt(now))
+            .select(TempRoles::as_select())
+            .load::<TempRoles>(conn)
+    }).await?;
+
+    for temp_role in temp_roles_results {
+        let guild = GuildId::new(u64::from(NonMaxU64::try_from(temp_role.guild_id as u64).unwrap()));
+        let user = UserId::new(u64::from(NonMaxU64::try_from(temp_role.user_id as u64).unwrap()));
+        let role = RoleId::new(u64::from(NonMaxU64::try_from(temp_role.role_id as u64).unwrap()));
+
+        // The member may have left or the role may have been deleted in the meantime
+        let reverted = if temp_role.action == "ADD" {
+            ctx.http().remove_member_role(guild, user, role, Some("Temporary role expired")).await.is_ok()
+        } else {
+            ctx.http().add_member_role(guild, user, role, Some("Temporary role removal expired")).await.is_ok()
+        };
+
+        if reverted {
+            let log_data = LogData {
+                ctx: Some(&ctx),
+                data: Some(&data),
+                guild_id: Some(guild.get()),
+                user_id: Some(user.get()),
+                moderator_id: Some(data_about_bot.user.id),
+                roles: Some(vec![role.get()]),
+                reason: Some("Temporary role expired".into()),
+                ..LogData::default()
+            };
+
+            let log_type = if temp_role.action == "ADD" { LogType::RoleRemove } else { LogType::RoleAdd };
+
+            if let Err(why) = log_action(log_type, log_data).await {
+                eprintln!("Failed to send log: {:?}", why);
+            }
+        }
+
+        let _ = data.db.run(|conn| {
+            diesel::delete(temp_roles
+                .filter(guild_id.eq(temp_role.guild_id))
+                .filter(user_id.eq(temp_role.user_id))
+                .filter(role_id.eq(temp_role.role_id))
+            ).execute(conn)
         }).await?;
     }
+
+    Ok(())
+}
+
+async fn voice_restore_check(data: Arc<Data>, ctx: Arc<Context>) -> Result<(), BotError> {
+    use crate::database::schema::voice_punishments::dsl::*;
+
+    let punishments_results = data.db.run(|conn| {
+        voice_punishments
+            .filter(pending.eq(false))
+            .filter(end_date.lt(now))
+            .select(VoicePunishments::as_select())
+            .load::<VoicePunishments>(conn)
+    }).await?;
+
+    for punishment in punishments_results {
+        // One member we can't edit shouldn't hold back the others
+        if let Err(why) = expire_voice_punishment(&ctx, &data, &punishment).await {
+            eprintln!("Failed to expire voice case #{}: {:?}", punishment.case_id, why);
+        }
+    }
+
     Ok(())
 }
 
@@ -100,6 +323,26 @@ pub async fn notification_loop(data: Arc<Data>, ctx: Arc<Context>, data_about_bo
                 eprintln!("Error checking for unbans: {:?}", why);
             }
 
+            if let Err(why) = slowmode_revert_check(data.clone(), ctx.clone(), data_about_bot.clone()).await {
+                eprintln!("Error checking for expired slowmodes: {:?}", why);
+            }
+
+            if let Err(why) = lockdown_end_check(data.clone(), ctx.clone(), data_about_bot.clone()).await {
+                eprintln!("Error checking for expired lockdowns: {:?}", why);
+            }
+
+            if let Err(why) = unlock_check(data.clone(), ctx.clone(), data_about_bot.clone()).await {
+                eprintln!("Error checking for expired channel locks: {:?}", why);
+            }
+
+            if let Err(why) = temp_role_check(data.clone(), ctx.clone(), data_about_bot.clone()).await {
+                eprintln!("Error checking for expired temporary roles: {:?}", why);
+            }
+
+            if let Err(why) = voice_restore_check(data.clone(), ctx.clone()).await {
+                eprintln!("Error checking for expired voice punishments: {:?}", why);
+            }
+
             tokio::time::sleep(Duration::from_secs(15)).await;
         }
     });
@@ -0,0 +1,48 @@
+use diesel::prelude::*;
+use diesel_async::RunQueryDsl;
+use poise::serenity_prelude::{AutocompleteChoice, CreateAutocompleteResponse, GuildId};
+use crate::{Context, Data};
+use crate::database::models::ReasonPresets;
+
+/// Discord shows at most this many autocomplete choices
+const MAX_CHOICES: usize = 25;
+
+/// Autocomplete values can't be longer than this
+pub const MAX_PRESET_LENGTH: usize = 100;
+
+pub async fn fetch_presets(data: &Data, guild: GuildId) -> Vec<ReasonPresets> {
+    use crate::database::schema::reason_presets::dsl::*;
+
+    data.db.run(|conn| {
+        reason_presets
+            .filter(guild_id.eq(guild.get() as i64))
+            .order(id.asc())
+            .select(ReasonPresets::as_select())
+            .load::<ReasonPresets>(conn)
+    }).await.unwrap_or_default()
+}
+
+/// Looks up the preset a reason was picked from, so its defaults can fill in missing options
+pub async fn find_preset(data: &Data, guild: GuildId, preset_reason: Option<&str>) -> Option<ReasonPresets> {
+    let preset_reason = preset_reason?;
+
+    fetch_presets(data, guild).await.into_iter().find(|preset| preset.reason == preset_reason)
+}
+
+/// Autocompletes the `reason` option of moderation commands from the guild's presets
+pub async fn autocomplete_reason<'a>(ctx: Context<'_>, partial: &'a str) -> CreateAutocompleteResponse<'a> {
+    let Some(guild) = ctx.guild_id() else {
+        return CreateAutocompleteResponse::new();
+    };
+
+    let partial = partial.to_lowercase();
+
+    let choices: Vec<AutocompleteChoice> = fetch_presets(&ctx.data(), guild).await
+        .into_iter()
+        .filter(|preset| preset.reason.to_lowercase().contains(&partial))
+        .take(MAX_CHOICES)
+        .map(|preset| AutocompleteChoice::new(preset.reason.clone(), preset.reason))
+        .collect();
+
+    CreateAutocompleteResponse::new().set_choices(choices)
+}
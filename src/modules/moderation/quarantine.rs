@@ -0,0 +1,258 @@
+use chrono::Utc;
+use diesel::prelude::*;
+use diesel_async::RunQueryDsl;
+use poise::serenity_prelude::{CacheHttp, ChannelId, Context, EditMember, GuildId, Member, PermissionOverwrite, PermissionOverwriteType, Permissions, RoleId, UserId};
+use poise::serenity_prelude::nonmax::NonMaxU64;
+use crate::{BotError, Data};
+use crate::database::models::{Cases, ModerationSettings, Quarantines};
+use crate::modules::moderation::cases::{create_case, revoke_case};
+use crate::modules::moderation::logs::{log_action, LogData, LogType};
+
+/// Roles with `managed` set belong to integrations or boosting and can't be removed, so they're left alone
+fn managed_roles(ctx: &Context, guild: GuildId, roles: &[RoleId]) -> Vec<RoleId> {
+    let Some(guild) = ctx.cache.guild(guild) else {
+        return Vec::new();
+    };
+
+    roles.iter()
+        .filter(|role| guild.roles.get(*role).is_some_and(|r| r.managed))
+        .copied()
+        .collect()
+}
+
+pub async fn fetch_quarantine(data: &Data, guild: GuildId, user: UserId) -> Option<Quarantines> {
+    use crate::database::schema::quarantines::dsl::*;
+
+    data.db.run(|conn| {
+        quarantines
+            .filter(guild_id.eq(guild.get() as i64))
+            .filter(user_id.eq(user.get() as i64))
+            .select(Quarantines::as_select())
+            .first::<Quarantines>(conn)
+    }).await.ok()
+}
+
+/// Saves the member's roles, swaps them for the quarantine role and opens a case.
+/// Returns the case ID, or `None` if the member was already quarantined.
+pub async fn quarantine_member(
+    ctx: &Context,
+    data: &Data,
+    member: &Member,
+    quarantine_role: RoleId,
+    moderator: UserId,
+    reason: Option<String>
+) -> Result<Option<i32>, BotError> {
+    use crate::database::schema::quarantines::dsl::*;
+
+    let managed = managed_roles(ctx, member.guild_id, &member.roles);
+
+    let saved_roles: Vec<i64> = member.roles.iter()
+        .filter(|role| !managed.contains(role) && **role != quarantine_role)
+        .map(|role| role.get() as i64)
+        .collect();
+
+    let quarantine = Quarantines {
+        guild_id: member.guild_id.get() as i64,
+        user_id: member.user.id.get() as i64,
+        moderator_id: moderator.get() as i64,
+        roles: saved_roles.clone(),
+        reason: reason.clone(),
+        created_at: Utc::now(),
+    };
+
+    // Saving first makes concurrent triggers (e.g. several automod actions for one message) quarantine only once
+    let inserted = data.db.run(|conn| {
+        diesel::insert_into(quarantines)
+            .values(&quarantine)
+            .on_conflict((guild_id, user_id))
+            .do_nothing()
+            .execute(conn)
+    }).await?;
+
+    if inserted == 0 {
+        return Ok(None);
+    }
+
+    let mut new_roles = managed;
+    new_roles.push(quarantine_role);
+
+    let edited = member.guild_id.edit_member(ctx.http(), member.user.id, EditMember::new()
+        .roles(new_roles)
+        .audit_log_reason(reason.as_deref().unwrap_or("Quarantined"))
+    ).await;
+
+    // The member kept their roles, so the saved row would only block a retry
+    if let Err(why) = edited {
+        data.db.run(|conn| {
+            diesel::delete(quarantines
+                .filter(guild_id.eq(member.guild_id.get() as i64))
+                .filter(user_id.eq(member.user.id.get() as i64))
+            ).execute(conn)
+        }).await?;
+
+        return Err(why.into());
+    }
+
+    let new_case_id = create_case(data, Cases {
+        guild_id: member.guild_id.get() as i64,
+        user_id: member.user.id.get() as i64,
+        moderator_id: moderator.get() as i64,
+        case_id: 0,
+        case_type: "QUARANTINE".to_string(),
+        reason: reason.clone(),
+        created_at: Utc::now(),
+        end_date: None,
+        points: None,
+        revoked_at: None,
+        revoke_reason: None
+    }).await?;
+
+    let log_data = LogData {
+        ctx: Some(ctx),
+        data: Some(data),
+        guild_id: Some(member.guild_id.get()),
+        user_id: Some(member.user.id.get()),
+        moderator_id: Some(moderator),
+        roles: Some(saved_roles.iter().map(|role| *role as u64).collect()),
+        reason,
+        case_id: Some(new_case_id),
+        ..LogData::default()
+    };
+
+    log_action(LogType::Quarantine, log_data).await?;
+
+    Ok(Some(new_case_id))
+}
+
+/// Gives back exactly the saved roles that still exist and forgets the quarantine.
+/// Members who left only have the quarantine forgotten. Returns `false` if the member wasn't quarantined.
+pub async fn release_member(
+    ctx: &Context,
+    data: &Data,
+    guild: GuildId,
+    user: UserId,
+    moderator: UserId,
+    reason: Option<String>
+) -> Result<bool, BotError> {
+    use crate::database::schema::quarantines::dsl::*;
+
+    let Some(quarantine) = fetch_quarantine(data, guild, user).await else {
+        return Ok(false);
+    };
+
+    let saved_roles: Vec<RoleId> = quarantine.roles.iter()
+        .map(|role| RoleId::new(u64::from(NonMaxU64::try_from(*role as u64).unwrap())))
+        .collect();
+
+    let restored_roles: Vec<RoleId> = match ctx.cache.guild(guild) {
+        Some(cached) => saved_roles.into_iter().filter(|role| cached.roles.get(role).is_some()).collect(),
+        None => saved_roles,
+    };
+
+    if let Ok(member) = guild.member(ctx.http(), user).await {
+        let mut new_roles = managed_roles(ctx, guild, &member.roles);
+        new_roles.extend(restored_roles.iter().copied());
+
+        guild.edit_member(ctx.http(), user, EditMember::new()
+            .roles(new_roles)
+            .audit_log_reason(reason.as_deref().unwrap_or("Released from quarantine"))
+        ).await?;
+    }
+
+    data.db.run(|conn| {
+        diesel::delete(quarantines
+            .filter(guild_id.eq(quarantine.guild_id))
+            .filter(user_id.eq(quarantine.user_id))
+        ).execute(conn)
+    }).await?;
+
+    close_quarantine_cases(data, guild, user, reason.as_deref().unwrap_or("Released from quarantine")).await?;
+
+    let log_data = LogData {
+        ctx: Some(ctx),
+        data: Some(data),
+        guild_id: Some(guild.get()),
+        user_id: Some(user.get()),
+        moderator_id: Some(moderator),
+        roles: Some(restored_roles.iter().map(|role| role.get()).collect()),
+        reason,
+        ..LogData::default()
+    };
+
+    log_action(LogType::Release, log_data).await?;
+
+    Ok(true)
+}
+
+/// Revokes the member's open `QUARANTINE` cases so they don't stay active after the release
+async fn close_quarantine_cases(data: &Data, guild: GuildId, user: UserId, release_reason: &str) -> Result<(), BotError> {
+    use crate::database::schema::cases::dsl::*;
+
+    let open_cases = data.db.run(|conn| {
+        cases
+            .filter(guild_id.eq(guild.get() as i64))
+            .filter(user_id.eq(user.get() as i64))
+            .filter(case_type.eq("QUARANTINE"))
+            .filter(revoked_at.is_null())
+            .select(case_id)
+            .load::<i32>(conn)
+    }).await?;
+
+    for open_case in open_cases {
+        revoke_case(data, guild, open_case, release_reason).await?;
+    }
+
+    Ok(())
+}
+
+/// Puts quarantined members who left and rejoined straight back into quarantine
+pub async fn reapply_quarantine(ctx: &Context, data: &Data, member: &Member) -> Result<(), BotError> {
+    if fetch_quarantine(data, member.guild_id, member.user.id).await.is_none() {
+        return Ok(());
+    }
+
+    let Some(role) = fetch_quarantine_role(data, member.guild_id).await? else {
+        return Ok(());
+    };
+
+    member.add_role(ctx.http(), role, Some("Rejoined while quarantined")).await?;
+
+    Ok(())
+}
+
+pub async fn fetch_quarantine_role(data: &Data, guild: GuildId) -> Result<Option<RoleId>, BotError> {
+    use crate::database::schema::moderation_settings::dsl::*;
+
+    let settings = data.db.run(|conn| {
+        moderation_settings
+            .filter(guild_id.eq(guild.get() as i64))
+            .select(ModerationSettings::as_select())
+            .first::<ModerationSettings>(conn)
+    }).await?;
+
+    Ok(settings.quarantine_role.map(|role| RoleId::new(u64::from(NonMaxU64::try_from(role as u64).unwrap()))))
+}
+
+/// Hides every channel from the quarantine role except the quarantine channel
+pub async fn apply_quarantine_overwrites(ctx: &Context, guild: GuildId, role: RoleId, quarantine_channel: ChannelId) -> Result<(), BotError> {
+    let channels: Vec<ChannelId> = {
+        let guild = ctx.cache.guild(guild).ok_or("Guild not cached")?;
+        guild.channels.iter().map(|c| c.id).collect()
+    };
+
+    for channel in channels {
+        let (allow, deny) = if channel == quarantine_channel {
+            (Permissions::VIEW_CHANNEL | Permissions::SEND_MESSAGES | Permissions::READ_MESSAGE_HISTORY, Permissions::empty())
+        } else {
+            (Permissions::empty(), Permissions::VIEW_CHANNEL)
+        };
+
+        channel.create_permission(ctx.http(), PermissionOverwrite {
+            allow,
+            deny,
+            kind: PermissionOverwriteType::Role(role),
+        }, Some("Quarantine setup")).await?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,41 @@
+use diesel::prelude::*;
+use diesel_async::RunQueryDsl;
+use crate::{BotError, Data};
+use crate::database::models::TempRoles;
+
+/// Schedules the reversal of a role change, replacing any pending one for the same member and role
+pub async fn save_temp_role(data: &Data, temp_role: TempRoles) -> Result<(), BotError> {
+    use crate::database::schema::temp_roles::dsl::*;
+
+    data.db.run(|conn| {
+        diesel::insert_into(temp_roles)
+            .values(&temp_role)
+            .on_conflict((guild_id, user_id, role_id))
+            .do_update()
+            .set((
+                moderator_id.eq(temp_role.moderator_id),
+                action.eq(temp_role.action.clone()),
+                reason.eq(temp_role.reason.clone()),
+                created_at.eq(temp_role.created_at),
+                end_date.eq(temp_role.end_date),
+            ))
+            .execute(conn)
+    }).await?;
+
+    Ok(())
+}
+
+/// Cancels a scheduled reversal, e.g. when a moderator changes the role again by hand
+pub async fn remove_temp_role(data: &Data, guild: i64, user: i64, role: i64) -> Result<(), BotError> {
+    use crate::database::schema::temp_roles::dsl::*;
+
+    data.db.run(|conn| {
+        diesel::delete(temp_roles
+            .filter(guild_id.eq(guild))
+            .filter(user_id.eq(user))
+            .filter(role_id.eq(role))
+        ).execute(conn)
+    }).await?;
+
+    Ok(())
+}
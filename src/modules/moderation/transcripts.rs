@@ -0,0 +1,107 @@
+use chrono::DateTime;
+use diesel::prelude::*;
+use diesel_async::RunQueryDsl;
+use poise::serenity_prelude::{CreateAttachment, Message};
+use crate::{BotError, Data};
+use crate::database::models::PurgeTranscripts;
+
+fn format_time(message: &Message) -> String {
+    DateTime::from_timestamp(message.timestamp.unix_timestamp(), 0)
+        .map(|date| date.format("%Y-%m-%d %H:%M:%S UTC").to_string())
+        .unwrap_or_default()
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Renders purged messages oldest first, one block per message
+pub fn render_text(messages: &[Message]) -> String {
+    let mut transcript = String::new();
+
+    for message in messages.iter().rev() {
+        transcript += &format!("[{}] {} ({}): {}\n", format_time(message), message.author.name, message.author.id, message.content);
+
+        for attachment in message.attachments.iter() {
+            transcript += &format!("    Attachment: {} ({})\n", attachment.filename, attachment.url);
+        }
+
+        for embed in message.embeds.iter() {
+            transcript += &format!(
+                "    Embed: {} | {} | {}\n",
+                embed.title.as_deref().unwrap_or("-"),
+                embed.description.as_deref().unwrap_or("-"),
+                embed.url.as_deref().unwrap_or("-")
+            );
+        }
+    }
+
+    transcript
+}
+
+pub fn render_html(messages: &[Message]) -> String {
+    let mut body = String::new();
+
+    for message in messages.iter().rev() {
+        body += &format!(
+            "<div class=\"message\"><div class=\"meta\"><span class=\"author\">{}</span> <span class=\"id\">({})</span> <time>{}</time></div><div class=\"content\">{}</div>",
+            escape_html(&message.author.name),
+            message.author.id,
+            format_time(message),
+            escape_html(&message.content).replace('\n', "<br>")
+        );
+
+        for attachment in message.attachments.iter() {
+            body += &format!(
+                "<div class=\"attachment\"><a href=\"{}\">{}</a></div>",
+                escape_html(&attachment.url),
+                escape_html(&attachment.filename)
+            );
+        }
+
+        for embed in message.embeds.iter() {
+            body += &format!(
+                "<div class=\"embed\"><strong>{}</strong><p>{}</p>{}</div>",
+                escape_html(embed.title.as_deref().unwrap_or_default()),
+                escape_html(embed.description.as_deref().unwrap_or_default()).replace('\n', "<br>"),
+                embed.url.as_deref().map_or(String::new(), |url| format!("<a href=\"{0}\">{0}</a>", escape_html(url)))
+            );
+        }
+
+        body += "</div>";
+    }
+
+    format!(
+        "<!DOCTYPE html><html><head><meta charset=\"utf-8\"><title>Transcript</title><style>\
+        body{{font-family:sans-serif;background:#313338;color:#dbdee1}}\
+        .message{{padding:6px 0;border-bottom:1px solid #3f4147}}\
+        .author{{font-weight:bold;color:#fff}}.id,time{{color:#949ba4;font-size:12px}}\
+        .embed{{border-left:4px solid #5848CE;padding:4px 8px;margin:4px 0;background:#2b2d31}}\
+        a{{color:#00a8fc}}</style></head><body>{}</body></html>",
+        body
+    )
+}
+
+pub fn transcript_attachments(transcript: &PurgeTranscripts, transcript_id: i32) -> Vec<CreateAttachment<'static>> {
+    vec![
+        CreateAttachment::bytes(transcript.transcript.clone().into_bytes(), format!("transcript-{}.txt", transcript_id)),
+        CreateAttachment::bytes(transcript.transcript_html.clone().into_bytes(), format!("transcript-{}.html", transcript_id)),
+    ]
+}
+
+/// Stores the transcript and returns its ID
+pub async fn save_transcript(data: &Data, transcript: &PurgeTranscripts) -> Result<i32, BotError> {
+    use crate::database::schema::purge_transcripts::dsl::*;
+
+    let transcript_id = data.db.run(|conn| {
+        diesel::insert_into(purge_transcripts)
+            .values(transcript)
+            .returning(id)
+            .get_result::<i32>(conn)
+    }).await?;
+
+    Ok(transcript_id)
+}
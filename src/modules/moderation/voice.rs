@@ -0,0 +1,124 @@
+use diesel::prelude::*;
+use diesel_async::RunQueryDsl;
+use poise::serenity_prelude::{CacheHttp, Context, EditMember, GuildId, UserId};
+use poise::serenity_prelude::nonmax::NonMaxU64;
+use crate::{BotError, Data};
+use crate::database::models::VoicePunishments;
+use crate::modules::moderation::logs::{log_action, LogData, LogType};
+
+/// Schedules the end of a server mute or deafen, replacing any earlier one of the same kind
+/// so an older punishment can't lift a newer one
+pub async fn save_voice_punishment(data: &Data, punishment: VoicePunishments) -> Result<(), BotError> {
+    use crate::database::schema::voice_punishments::dsl::*;
+
+    data.db.run(|conn| {
+        diesel::insert_into(voice_punishments)
+            .values(&punishment)
+            .on_conflict((guild_id, user_id, kind))
+            .do_update()
+            .set((
+                case_id.eq(punishment.case_id),
+                end_date.eq(punishment.end_date),
+                pending.eq(false),
+            ))
+            .execute(conn)
+    }).await?;
+
+    Ok(())
+}
+
+/// Cancels a scheduled lift, e.g. when the member gets a permanent punishment of the same kind
+pub async fn remove_voice_punishment(data: &Data, guild: i64, user: i64, punishment_kind: &str) -> Result<(), BotError> {
+    use crate::database::schema::voice_punishments::dsl::*;
+
+    let punishment_kind = punishment_kind.to_string();
+
+    data.db.run(|conn| {
+        diesel::delete(voice_punishments
+            .filter(guild_id.eq(guild))
+            .filter(user_id.eq(user))
+            .filter(kind.eq(punishment_kind))
+        ).execute(conn)
+    }).await?;
+
+    Ok(())
+}
+
+async fn lift_voice_punishment(ctx: &Context, data: &Data, punishment: &VoicePunishments) -> Result<(), BotError> {
+    let guild = GuildId::new(u64::from(NonMaxU64::try_from(punishment.guild_id as u64).unwrap()));
+    let user = UserId::new(u64::from(NonMaxU64::try_from(punishment.user_id as u64).unwrap()));
+
+    let (builder, log_type) = if punishment.kind == "VOICE_MUTE" {
+        (EditMember::new().mute(false), LogType::VoiceUnmute)
+    } else {
+        (EditMember::new().deafen(false), LogType::VoiceUndeafen)
+    };
+
+    guild.edit_member(ctx.http(), user, builder.audit_log_reason("Voice punishment expired")).await?;
+    remove_voice_punishment(data, punishment.guild_id, punishment.user_id, &punishment.kind).await?;
+
+    let log_data = LogData {
+        ctx: Some(ctx),
+        data: Some(data),
+        guild_id: Some(guild.get()),
+        user_id: Some(user.get()),
+        moderator_id: Some(ctx.cache.current_user().id),
+        reason: Some("Voice punishment expired".into()),
+        case_id: Some(punishment.case_id),
+        ..LogData::default()
+    };
+
+    if let Err(why) = log_action(log_type, log_data).await {
+        eprintln!("Failed to send log: {:?}", why);
+    }
+
+    Ok(())
+}
+
+/// Lifts an expired punishment. Discord only accepts voice edits for connected members,
+/// for anyone else it's marked pending and lifted by `restore_pending_voice`.
+pub async fn expire_voice_punishment(ctx: &Context, data: &Data, punishment: &VoicePunishments) -> Result<(), BotError> {
+    use crate::database::schema::voice_punishments::dsl::*;
+
+    let guild = GuildId::new(u64::from(NonMaxU64::try_from(punishment.guild_id as u64).unwrap()));
+    let user = UserId::new(u64::from(NonMaxU64::try_from(punishment.user_id as u64).unwrap()));
+
+    let is_connected = ctx.cache.guild(guild)
+        .is_some_and(|g| g.voice_states.get(&user).and_then(|state| state.channel_id).is_some());
+
+    if is_connected {
+        return lift_voice_punishment(ctx, data, punishment).await;
+    }
+
+    let (pending_guild, pending_user, pending_kind) = (punishment.guild_id, punishment.user_id, punishment.kind.clone());
+
+    data.db.run(|conn| {
+        diesel::update(voice_punishments
+            .filter(guild_id.eq(pending_guild))
+            .filter(user_id.eq(pending_user))
+            .filter(kind.eq(pending_kind))
+        ).set(pending.eq(true)).execute(conn)
+    }).await?;
+
+    Ok(())
+}
+
+/// Lifts punishments that expired while the member was out of voice, called when they connect
+pub async fn restore_pending_voice(ctx: &Context, data: &Data, guild: GuildId, user: UserId) -> Result<(), BotError> {
+    use crate::database::schema::voice_punishments::dsl::*;
+
+    let pending_punishments = data.db.run(|conn| {
+        voice_punishments
+            .filter(guild_id.eq(guild.get() as i64))
+            .filter(user_id.eq(user.get() as i64))
+            .filter(pending.eq(true))
+            .select(VoicePunishments::as_select())
+            .load::<VoicePunishments>(conn)
+    }).await?;
+
+    for punishment in pending_punishments {
+        lift_voice_punishment(ctx, data, &punishment).await?;
+    }
+
+    Ok(())
+}
@@ -1,10 +1,23 @@
 use std::time::Duration;
-use poise::serenity_prelude::{ActionRowComponent, ComponentInteraction, ComponentInteractionDataKind, CreateActionRow, CreateSelectMenu, CreateSelectMenuKind, CreateSelectMenuOption, InputText, Message, ModalInteraction};
+use poise::CreateReply;
+use poise::serenity_prelude::{ActionRowComponent, ButtonStyle, ComponentInteraction, ComponentInteractionDataKind, CreateActionRow, CreateButton, CreateEmbed, CreateInputText, CreateInteractionResponse, CreateInteractionResponseMessage, CreateModal, CreateSelectMenu, CreateSelectMenuKind, CreateSelectMenuOption, InputText, InputTextStyle, Message, ModalInteraction};
 use poise::serenity_prelude::small_fixed_array::FixedArray;
 use crate::{BotError, Context};
+use crate::localization::manager::TranslationParam;
 
 pub(crate) const INTERACTION_TIMEOUT: Duration = Duration::from_secs(60);
 
+/// How long a paginated message keeps reacting to its buttons
+pub(crate) const PAGINATOR_TIMEOUT: Duration = Duration::from_secs(300);
+
+pub const DEFAULT_PAGE_SIZE: usize = 10;
+
+/// Discord rejects select menus with more options than this
+pub const MAX_SELECT_OPTIONS: usize = 25;
+
+/// Discord rejects embed descriptions longer than this
+const MAX_DESCRIPTION_LENGTH: usize = 4096;
+
 
 pub fn create_select_menu<'a, K>(
     custom_id: &'a str,
@@ -114,6 +127,7 @@ pub fn get_selected_value(interaction: &ComponentInteraction) -> Result<String,
     match &interaction.data.kind {
         ComponentInteractionDataKind::StringSelect { values } => Ok(values[0].clone()),
         ComponentInteractionDataKind::ChannelSelect { values } => Ok(values[0].get().to_string().clone()),
+        ComponentInteractionDataKind::RoleSelect { values } => Ok(values[0].get().to_string()),
         _ => Err(BotError::from("Invalid interaction data kind"))
     }
 }
@@ -126,9 +140,154 @@ pub fn get_selected_values(interaction: &ComponentInteraction) -> Result<Vec<Str
     }
 }
 
+/// Values of every input in the modal, in order. Empty inputs give an empty string.
+pub fn get_modal_values(interaction: &ModalInteraction) -> Result<Vec<String>, BotError> {
+    interaction.data.components.iter()
+        .map(|row| match &row.components[0] {
+            ActionRowComponent::InputText { 0: InputText { value, .. } } => Ok(value.as_deref().unwrap_or_default().to_string()),
+            _ => Err(Box::from("Invalid component")),
+        })
+        .collect()
+}
+
 pub fn get_modal_value(interaction: &ModalInteraction) -> Result<String, BotError> {
     match &interaction.data.components[0].components[0] {
         ActionRowComponent::InputText { 0: InputText { value, .. } } => Ok(value.clone().unwrap().to_string()),
         _ => Err(Box::from("Invalid component")),
     }
-}
\ No newline at end of file
+}
+
+/// Splits entries into page descriptions of at most `page_size` entries each.
+/// A page is cut early if it would go over Discord's description limit.
+/// Entries with a group get the group as a bold header whenever the group changes or a new page starts.
+pub fn paginate_entries(entries: &[(Option<String>, String)], page_size: usize) -> Vec<String> {
+    let mut pages = Vec::new();
+    let mut page = String::new();
+    let mut page_entries = 0;
+    let mut current_group: Option<&String> = None;
+
+    let header = |group: &Option<String>| group.as_ref().map_or(String::new(), |g| format!("**{}**\n", g));
+
+    for (group, entry) in entries {
+        let mut text = if group.as_ref() != current_group { header(group) } else { String::new() };
+        text += entry;
+
+        if page_entries > 0 && (page_entries >= page_size || page.chars().count() + text.chars().count() > MAX_DESCRIPTION_LENGTH) {
+            pages.push(std::mem::take(&mut page));
+            page_entries = 0;
+            text = header(group) + entry;
+        }
+
+        page += &text;
+        page_entries += 1;
+        current_group = group.as_ref();
+    }
+
+    if !page.is_empty() {
+        pages.push(page);
+    }
+
+    // A single entry over the limit still has to fit
+    pages.into_iter().map(|page| page.chars().take(MAX_DESCRIPTION_LENGTH).collect()).collect()
+}
+
+fn create_page_buttons(page: usize, pages: usize) -> Vec<CreateActionRow<'static>> {
+    if pages <= 1 {
+        return vec![];
+    }
+
+    vec![CreateActionRow::Buttons(vec![
+        CreateButton::new("page_first").style(ButtonStyle::Secondary).emoji('⏮').disabled(page == 0),
+        CreateButton::new("page_prev").style(ButtonStyle::Primary).emoji('⬅').disabled(page == 0),
+        CreateButton::new("page_jump").style(ButtonStyle::Secondary).label(format!("{}/{}", page + 1, pages)),
+        CreateButton::new("page_next").style(ButtonStyle::Primary).emoji('➡').disabled(page + 1 >= pages),
+        CreateButton::new("page_last").style(ButtonStyle::Secondary).emoji('⏭').disabled(page + 1 >= pages),
+    ].into())]
+}
+
+/// Sends the pages with navigation buttons and a jump-to-page modal, only the command author can use them.
+/// `build_embed` gets the page description, the page index and the page count. Guild commands only,
+/// the modal and the error reply use the guild's language.
+pub async fn send_paginated<F>(ctx: Context<'_>, pages: Vec<String>, build_embed: F) -> Result<(), BotError>
+where
+    F: Fn(&str, usize, usize) -> CreateEmbed<'static>,
+{
+    let db = ctx.data().db.clone();
+    let locales = ctx.data().localization_manager.clone();
+    let guild_lang = locales
+        .get_guild_language(db, ctx.guild_id().unwrap()).await.unwrap();
+
+    let total = pages.len().max(1);
+    let mut current_page = 0;
+
+    let render = |page: usize| build_embed(pages.get(page).map_or("", |p| p.as_str()), page, total);
+
+    let reply = ctx.send(CreateReply::new()
+        .embed(render(current_page))
+        .components(create_page_buttons(current_page, total))
+    ).await?;
+
+    if total <= 1 {
+        return Ok(());
+    }
+
+    let message = reply.message().await?.into_owned();
+
+    while let Some(interaction) = message.await_component_interaction(ctx.serenity_context().shard.clone())
+        .timeout(PAGINATOR_TIMEOUT)
+        .await
+    {
+        if interaction.user.id != ctx.author().id {
+            interaction.create_response(ctx.http(), CreateInteractionResponse::Message(
+                CreateInteractionResponseMessage::new().content(locales.get("util.interaction.error_not_author", guild_lang, &[])).ephemeral(true)
+            )).await?;
+            continue;
+        }
+
+        current_page = match interaction.data.custom_id.as_str() {
+            "page_first" => 0,
+            "page_prev" => current_page.saturating_sub(1),
+            "page_next" => (current_page + 1).min(total - 1),
+            "page_last" => total - 1,
+            "page_jump" => {
+                let input = CreateInputText::new(InputTextStyle::Short, locales.get("util.interaction.jump_label", guild_lang, &[
+                    TranslationParam::from(total.to_string())
+                ]), "page")
+                    .placeholder((current_page + 1).to_string())
+                    .required(true);
+
+                interaction.create_response(ctx.http(), CreateInteractionResponse::Modal(
+                    CreateModal::new("page_jump_modal", locales.get("util.interaction.jump_title", guild_lang, &[])).components(vec![CreateActionRow::InputText(input)])
+                )).await?;
+
+                let Some(modal) = await_modal_interaction(&ctx, &message, "page_jump_modal").await else {
+                    continue;
+                };
+
+                current_page = get_modal_value(&modal)?.trim().parse::<usize>()
+                    .map_or(current_page, |page| page.clamp(1, total) - 1);
+
+                modal.create_response(ctx.http(), CreateInteractionResponse::UpdateMessage(
+                    CreateInteractionResponseMessage::new()
+                        .embed(render(current_page))
+                        .components(create_page_buttons(current_page, total))
+                )).await?;
+                continue;
+            },
+            _ => continue,
+        };
+
+        interaction.create_response(ctx.http(), CreateInteractionResponse::UpdateMessage(
+            CreateInteractionResponseMessage::new()
+                .embed(render(current_page))
+                .components(create_page_buttons(current_page, total))
+        )).await?;
+    }
+
+    reply.edit(ctx, CreateReply::new()
+        .embed(render(current_page))
+        .components(vec![])
+    ).await?;
+
+    Ok(())
+}
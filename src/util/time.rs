@@ -1,25 +1,521 @@
-use poise::serenity_prelude::Timestamp;
-use regex::Regex;
+use chrono::{DateTime, Datelike, Days, NaiveDate, NaiveTime, TimeZone, Utc, Weekday};
+use chrono_tz::Tz;
+use diesel::prelude::*;
+use diesel_async::RunQueryDsl;
+use poise::serenity_prelude::{GuildId, Timestamp};
+use crate::Data;
+use crate::localization::manager::{Language, LocalizationManager, TranslationParam};
 
+/// Upper bound for any parsed duration, keeps `chrono::Duration` and Discord timestamps in range
+const MAX_DURATION: u64 = 100 * 365 * 86400;
 
-pub(crate) fn parse_to_time(time_str: String) -> Option<u64> {
-    let re = Regex::new(r"^(\d+)([dhms])$").unwrap();
-    let captures = re.captures(&time_str)?;
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum TimeUnit {
+    Year,
+    Month,
+    Week,
+    Day,
+    Hour,
+    Minute,
+    Second,
+}
+
+impl TimeUnit {
+    /// Largest first, the formatter relies on this order
+    const ALL: [TimeUnit; 7] = [
+        TimeUnit::Year,
+        TimeUnit::Month,
+        TimeUnit::Week,
+        TimeUnit::Day,
+        TimeUnit::Hour,
+        TimeUnit::Minute,
+        TimeUnit::Second,
+    ];
+
+    /// Months and years are fixed at 30 and 365 days
+    fn seconds(self) -> u64 {
+        match self {
+            TimeUnit::Year => 365 * 86400,
+            TimeUnit::Month => 30 * 86400,
+            TimeUnit::Week => 7 * 86400,
+            TimeUnit::Day => 86400,
+            TimeUnit::Hour => 3600,
+            TimeUnit::Minute => 60,
+            TimeUnit::Second => 1,
+        }
+    }
+
+    /// Words accepted by the parser for this unit, lowercase
+    fn words(self, lang: Language) -> &'static [&'static str] {
+        match (lang, self) {
+            (Language::English, TimeUnit::Year) => &["y", "yr", "yrs", "year", "years"],
+            (Language::English, TimeUnit::Month) => &["mo", "mon", "month", "months"],
+            (Language::English, TimeUnit::Week) => &["w", "wk", "wks", "week", "weeks"],
+            (Language::English, TimeUnit::Day) => &["d", "day", "days"],
+            (Language::English, TimeUnit::Hour) => &["h", "hr", "hrs", "hour", "hours"],
+            (Language::English, TimeUnit::Minute) => &["m", "min", "mins", "minute", "minutes"],
+            (Language::English, TimeUnit::Second) => &["s", "sec", "secs", "second", "seconds"],
+            (Language::Polish, TimeUnit::Year) => &["r", "rok", "roku", "lata", "lat"],
+            (Language::Polish, TimeUnit::Month) => &["mies", "miesiąc", "miesiące", "miesięcy", "miesiac", "miesiace", "miesiecy"],
+            (Language::Polish, TimeUnit::Week) => &["t", "tydz", "tydzień", "tydzien", "tygodnie", "tygodni"],
+            (Language::Polish, TimeUnit::Day) => &["d", "dz", "dzień", "dzien", "dni", "dnia"],
+            (Language::Polish, TimeUnit::Hour) => &["g", "godz", "godzina", "godzinę", "godzine", "godziny", "godzin"],
+            (Language::Polish, TimeUnit::Minute) => &["m", "min", "minuta", "minutę", "minute", "minuty", "minut"],
+            (Language::Polish, TimeUnit::Second) => &["s", "sek", "sekunda", "sekundę", "sekunde", "sekundy", "sekund"],
+        }
+    }
+
+    /// Name of the unit for `count`, with the plural form the language needs
+    fn name(self, lang: Language, count: u64) -> &'static str {
+        match lang {
+            Language::English => {
+                let (one, other) = match self {
+                    TimeUnit::Year => ("year", "years"),
+                    TimeUnit::Month => ("month", "months"),
+                    TimeUnit::Week => ("week", "weeks"),
+                    TimeUnit::Day => ("day", "days"),
+                    TimeUnit::Hour => ("hour", "hours"),
+                    TimeUnit::Minute => ("minute", "minutes"),
+                    TimeUnit::Second => ("second", "seconds"),
+                };
+
+                if count == 1 { one } else { other }
+            },
+            Language::Polish => {
+                let (one, few, many) = match self {
+                    TimeUnit::Year => ("rok", "lata", "lat"),
+                    TimeUnit::Month => ("miesiąc", "miesiące", "miesięcy"),
+                    TimeUnit::Week => ("tydzień", "tygodnie", "tygodni"),
+                    TimeUnit::Day => ("dzień", "dni", "dni"),
+                    TimeUnit::Hour => ("godzina", "godziny", "godzin"),
+                    TimeUnit::Minute => ("minuta", "minuty", "minut"),
+                    TimeUnit::Second => ("sekunda", "sekundy", "sekund"),
+                };
 
-    let time_value = captures[1].parse::<u64>().ok()?;
-    let time_unit = &captures[2];
+                if count == 1 {
+                    one
+                } else if (2..=4).contains(&(count % 10)) && !(12..=14).contains(&(count % 100)) {
+                    few
+                } else {
+                    many
+                }
+            },
+        }
+    }
+
+    /// English words are always understood, so "13d" keeps working in every guild
+    fn from_word(word: &str, lang: Language) -> Option<TimeUnit> {
+        TimeUnit::ALL.into_iter().find(|unit| {
+            unit.words(lang).contains(&word) || unit.words(Language::English).contains(&word)
+        })
+    }
+}
+
+fn permanent_words(lang: Language) -> &'static [&'static str] {
+    match lang {
+        Language::English => &["perm", "permanent", "never", "forever"],
+        Language::Polish => &["perm", "permanentnie", "nigdy", "na zawsze", "zawsze"],
+    }
+}
 
-    match time_unit {
-        "d" => Some(time_value * 86400),
-        "h" => Some(time_value * 3600),
-        "m" => Some(time_value * 60),
-        "s" => Some(time_value),
+fn weekday_from_word(word: &str) -> Option<Weekday> {
+    match word {
+        "mon" | "monday" | "pon" | "poniedziałek" | "poniedzialek" => Some(Weekday::Mon),
+        "tue" | "tuesday" | "wt" | "wtorek" => Some(Weekday::Tue),
+        "wed" | "wednesday" | "śr" | "sr" | "środa" | "środę" | "sroda" | "srode" => Some(Weekday::Wed),
+        "thu" | "thursday" | "czw" | "czwartek" => Some(Weekday::Thu),
+        "fri" | "friday" | "pt" | "piątek" | "piatek" => Some(Weekday::Fri),
+        "sat" | "saturday" | "sob" | "sobota" | "sobotę" | "sobote" => Some(Weekday::Sat),
+        "sun" | "sunday" | "nd" | "niedziela" | "niedzielę" | "niedziele" => Some(Weekday::Sun),
         _ => None,
     }
 }
 
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum DurationError {
+    Empty,
+    /// A number with no unit after it, e.g. the `12` in "1d12"
+    MissingUnit(u64),
+    /// A unit that was not recognised, e.g. "3x"
+    UnknownUnit(String),
+    /// Text where a number was expected
+    UnexpectedText(String),
+    Zero,
+    TooLong,
+    /// Shorter than the action allows, with the minimum in seconds
+    TooShort(u64),
+    /// Looked like a date or time but could not be read as one
+    InvalidDate(String),
+    /// An absolute date that has already passed
+    InPast,
+}
+
+impl DurationError {
+    /// Message telling the user what exactly was wrong with their input
+    pub(crate) fn localize(&self, lang: Language, locales: &LocalizationManager) -> String {
+        match self {
+            DurationError::Empty => locales.get("util.time.error_empty", lang, &[]),
+            DurationError::MissingUnit(number) => locales.get("util.time.error_missing_unit", lang, &[
+                TranslationParam::from(number.to_string())
+            ]),
+            DurationError::UnknownUnit(unit) => locales.get("util.time.error_unknown_unit", lang, &[
+                TranslationParam::from(unit.clone())
+            ]),
+            DurationError::UnexpectedText(text) => locales.get("util.time.error_unexpected_text", lang, &[
+                TranslationParam::from(text.clone())
+            ]),
+            DurationError::Zero => locales.get("util.time.error_zero", lang, &[]),
+            DurationError::TooLong => locales.get("util.time.error_too_long", lang, &[]),
+            DurationError::TooShort(min_seconds) => locales.get("util.time.error_too_short", lang, &[
+                TranslationParam::from(format_duration(*min_seconds, lang))
+            ]),
+            DurationError::InvalidDate(date) => locales.get("util.time.error_invalid_date", lang, &[
+                TranslationParam::from(date.clone())
+            ]),
+            DurationError::InPast => locales.get("util.time.error_in_past", lang, &[]),
+        }
+    }
+}
+
+/// Parses a duration like "1d12h", "2 weeks" or "3dni" into seconds.
+///
+/// Units can be combined and separated by spaces or commas, and the unit words of `lang`
+/// are accepted alongside the English ones. Returns `Ok(None)` for "perm"/"permanent".
+pub(crate) fn parse_duration(input: &str, lang: Language) -> Result<Option<u64>, DurationError> {
+    let input = input.trim().to_lowercase();
+
+    if input.is_empty() {
+        return Err(DurationError::Empty);
+    }
+
+    if permanent_words(lang).contains(&input.as_str()) || permanent_words(Language::English).contains(&input.as_str()) {
+        return Ok(None);
+    }
+
+    let mut chars = input.chars().peekable();
+    let mut total: u64 = 0;
+
+    loop {
+        while chars.next_if(|c| c.is_whitespace() || *c == ',').is_some() {}
+
+        if chars.peek().is_none() {
+            break;
+        }
+
+        let mut number = String::new();
+        while let Some(c) = chars.next_if(char::is_ascii_digit) {
+            number.push(c);
+        }
+
+        if number.is_empty() {
+            let text: String = std::iter::from_fn(|| chars.next_if(|c| !c.is_ascii_digit() && !c.is_whitespace())).collect();
+            return Err(DurationError::UnexpectedText(text));
+        }
+
+        let value = number.parse::<u64>().map_err(|_| DurationError::TooLong)?;
+
+        while chars.next_if(|c| c.is_whitespace()).is_some() {}
+
+        let word: String = std::iter::from_fn(|| chars.next_if(|c| c.is_alphabetic())).collect();
+
+        if word.is_empty() {
+            return Err(DurationError::MissingUnit(value));
+        }
+
+        let unit = TimeUnit::from_word(&word, lang).ok_or(DurationError::UnknownUnit(word))?;
+
+        total = value.checked_mul(unit.seconds())
+            .and_then(|seconds| total.checked_add(seconds))
+            .filter(|total| *total <= MAX_DURATION)
+            .ok_or(DurationError::TooLong)?;
+    }
+
+    if total == 0 {
+        return Err(DurationError::Zero);
+    }
+
+    Ok(Some(total))
+}
+
+/// Parses an absolute date and/or time in the guild's timezone, e.g. "2024-11-01 18:00",
+/// "01.11.2024", "friday 18:00", "tomorrow" or just "18:00".
+///
+/// A bare time that already passed today means tomorrow, a bare weekday always means the next one.
+pub(crate) fn parse_datetime(input: &str, tz: Tz) -> Option<DateTime<Utc>> {
+    let input = input.trim().to_lowercase();
+    let parse_time = |text: &str| NaiveTime::parse_from_str(text, "%H:%M").ok();
+
+    let (date_part, time) = match input.rsplit_once(' ') {
+        Some((date_part, time_part)) if parse_time(time_part).is_some() => (date_part.trim(), parse_time(time_part)),
+        _ if parse_time(&input).is_some() => ("", parse_time(&input)),
+        _ => (input.as_str(), None),
+    };
+
+    let now = Utc::now().with_timezone(&tz);
+    let today = now.date_naive();
+
+    let date = match date_part {
+        "" | "today" | "dziś" | "dzis" | "dzisiaj" => today,
+        "tomorrow" | "jutro" => today.checked_add_days(Days::new(1))?,
+        word => match weekday_from_word(word) {
+            Some(weekday) => {
+                let days_ahead = (weekday.num_days_from_monday() + 7 - today.weekday().num_days_from_monday()) % 7;
+                today.checked_add_days(Days::new(if days_ahead == 0 { 7 } else { days_ahead.into() }))?
+            },
+            None => ["%Y-%m-%d", "%d.%m.%Y", "%d/%m/%Y"].iter()
+                .find_map(|format| NaiveDate::parse_from_str(word, format).ok())?,
+        },
+    };
+
+    let resolve = |date: NaiveDate| tz.from_local_datetime(&date.and_time(time.unwrap_or_default())).earliest();
+
+    let mut result = resolve(date)?;
+
+    if date_part.is_empty() && result <= now {
+        result = resolve(date.checked_add_days(Days::new(1))?)?;
+    }
+
+    Some(result.with_timezone(&Utc))
+}
+
+/// Like [`parse_duration`], but also accepts an absolute date or time (see [`parse_datetime`])
+/// and returns the seconds left until it
+pub(crate) fn parse_duration_or_date(input: &str, lang: Language, tz: Tz) -> Result<Option<u64>, DurationError> {
+    let why = match parse_duration(input, lang) {
+        Err(why @ (DurationError::MissingUnit(_) | DurationError::UnknownUnit(_) | DurationError::UnexpectedText(_))) => why,
+        result => return result,
+    };
+
+    let Some(date) = parse_datetime(input, tz) else {
+        if input.contains([':', '-', '.', '/']) {
+            return Err(DurationError::InvalidDate(input.trim().to_string()));
+        }
+        return Err(why);
+    };
+
+    let seconds = (date - Utc::now()).num_seconds();
+
+    if seconds <= 0 {
+        return Err(DurationError::InPast);
+    }
+
+    if seconds as u64 > MAX_DURATION {
+        return Err(DurationError::TooLong);
+    }
+
+    Ok(Some(seconds as u64))
+}
+
+/// Parses the optional duration of a timed action into its end date and humanized form.
+/// No input or a permanent word gives `(None, None)`, durations under `min_seconds` are rejected.
+pub(crate) fn parse_expiry(input: Option<&str>, lang: Language, tz: Tz, min_seconds: u64) -> Result<(Option<DateTime<Utc>>, Option<String>), DurationError> {
+    let Some(input) = input else {
+        return Ok((None, None));
+    };
+
+    match parse_duration_or_date(input, lang, tz)? {
+        Some(seconds) if seconds < min_seconds => Err(DurationError::TooShort(min_seconds)),
+        Some(seconds) => Ok((
+            Some(Utc::now() + chrono::Duration::seconds(seconds as i64)),
+            Some(format_duration(seconds, lang))
+        )),
+        None => Ok((None, None)),
+    }
+}
+
+/// The timezone set in `/config`, UTC if none is set
+pub(crate) async fn fetch_guild_timezone(data: &Data, guild: GuildId) -> Tz {
+    use crate::database::schema::guild_settings::dsl::*;
+
+    data.db.run(|conn| {
+        guild_settings
+            .filter(guild_id.eq(guild.get() as i64))
+            .select(timezone)
+            .first::<String>(conn)
+    }).await.ok()
+        .and_then(|name| name.parse::<Tz>().ok())
+        .unwrap_or(Tz::UTC)
+}
+
+/// Date and time in the guild's timezone, e.g. "2024-11-01 18:00 CET"
+pub(crate) fn format_datetime(date: DateTime<Utc>, tz: Tz) -> String {
+    date.with_timezone(&tz).format("%Y-%m-%d %H:%M %Z").to_string()
+}
+
+/// Human readable form of a duration in seconds, e.g. "1 day and 12 hours" or "1 dzień i 12 godzin"
+pub(crate) fn format_duration(seconds: u64, lang: Language) -> String {
+    let mut remaining = seconds;
+    let mut parts = Vec::new();
+
+    for unit in TimeUnit::ALL {
+        let count = remaining / unit.seconds();
+        if count > 0 {
+            parts.push(format!("{} {}", count, unit.name(lang, count)));
+            remaining %= unit.seconds();
+        }
+    }
+
+    let conjunction = match lang {
+        Language::English => "and",
+        Language::Polish => "i",
+    };
+
+    match parts.split_last() {
+        None => format!("0 {}", TimeUnit::Second.name(lang, 0)),
+        Some((last, [])) => last.clone(),
+        Some((last, rest)) => format!("{} {} {}", rest.join(", "), conjunction, last),
+    }
+}
+
 pub(crate) fn date_after(time: u64) -> Timestamp {
     let now = chrono::Utc::now();
     let future = now + chrono::Duration::seconds(time as i64);
     future.into()
-}
\ No newline at end of file
+}
+
+/// Parses a `YYYY-MM-DD` date as the start of that day in the guild's timezone
+pub(crate) fn parse_date(date_str: &str, tz: Tz) -> Option<DateTime<Utc>> {
+    NaiveDate::parse_from_str(date_str.trim(), "%Y-%m-%d").ok()
+        .and_then(|date| tz.from_local_datetime(&date.and_time(NaiveTime::MIN)).earliest())
+        .map(|date| date.with_timezone(&Utc))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_combined_units() {
+        assert_eq!(parse_duration("1d12h", Language::English), Ok(Some(86400 + 12 * 3600)));
+        assert_eq!(parse_duration("2 weeks, 3 days", Language::English), Ok(Some(17 * 86400)));
+        assert_eq!(parse_duration("1h 30m 15s", Language::English), Ok(Some(5415)));
+        assert_eq!(parse_duration("1mo1y", Language::English), Ok(Some(395 * 86400)));
+        assert_eq!(parse_duration("3dni 2godz", Language::Polish), Ok(Some(3 * 86400 + 2 * 3600)));
+        assert_eq!(parse_duration("13d", Language::Polish), Ok(Some(13 * 86400)));
+    }
+
+    #[test]
+    fn rejects_malformed_input() {
+        assert_eq!(parse_duration("", Language::English), Err(DurationError::Empty));
+        assert_eq!(parse_duration("1d12", Language::English), Err(DurationError::MissingUnit(12)));
+        assert_eq!(parse_duration("3x", Language::English), Err(DurationError::UnknownUnit("x".to_string())));
+        assert_eq!(parse_duration("abc", Language::English), Err(DurationError::UnexpectedText("abc".to_string())));
+        assert_eq!(parse_duration("0s", Language::English), Err(DurationError::Zero));
+    }
+
+    #[test]
+    fn permanent_words_mean_no_duration() {
+        for word in ["perm", "permanent", "never", "forever", "Never", " forever "] {
+            assert_eq!(parse_duration(word, Language::English), Ok(None), "{}", word);
+        }
+
+        for word in ["nigdy", "na zawsze", "zawsze", "permanentnie", "never"] {
+            assert_eq!(parse_duration(word, Language::Polish), Ok(None), "{}", word);
+        }
+
+        assert!(parse_duration("nigdy", Language::English).is_err());
+    }
+
+    #[test]
+    fn caps_durations_at_max() {
+        assert_eq!(parse_duration("100y", Language::English), Ok(Some(MAX_DURATION)));
+        assert_eq!(parse_duration("100y1s", Language::English), Err(DurationError::TooLong));
+        assert_eq!(parse_duration("18446744073709551615y", Language::English), Err(DurationError::TooLong));
+        assert_eq!(parse_duration("99999999999999999999999s", Language::English), Err(DurationError::TooLong));
+    }
+
+    #[test]
+    fn formats_english_plurals() {
+        assert_eq!(format_duration(1, Language::English), "1 second");
+        assert_eq!(format_duration(86400 + 12 * 3600, Language::English), "1 day and 12 hours");
+        assert_eq!(format_duration(7 * 86400 + 3661, Language::English), "1 week, 1 hour, 1 minute and 1 second");
+        assert_eq!(format_duration(0, Language::English), "0 seconds");
+    }
+
+    #[test]
+    fn formats_polish_plurals() {
+        assert_eq!(format_duration(60, Language::Polish), "1 minuta");
+        assert_eq!(format_duration(2 * 60, Language::Polish), "2 minuty");
+        assert_eq!(format_duration(5 * 60, Language::Polish), "5 minut");
+        assert_eq!(format_duration(12 * 3600, Language::Polish), "12 godzin");
+        assert_eq!(format_duration(22 * 3600, Language::Polish), "22 godziny");
+        assert_eq!(format_duration(86400 + 12 * 3600, Language::Polish), "1 dzień i 12 godzin");
+        assert_eq!(format_duration(2 * 365 * 86400, Language::Polish), "2 lata");
+        assert_eq!(format_duration(0, Language::Polish), "0 sekund");
+    }
+
+    #[test]
+    fn rejects_expiry_below_minimum() {
+        assert_eq!(parse_expiry(None, Language::English, Tz::UTC, 60), Ok((None, None)));
+        assert_eq!(parse_expiry(Some("never"), Language::English, Tz::UTC, 60), Ok((None, None)));
+        assert_eq!(parse_expiry(Some("30s"), Language::English, Tz::UTC, 60), Err(DurationError::TooShort(60)));
+        assert!(parse_expiry(Some("1h"), Language::English, Tz::UTC, 60).is_ok_and(|(ends_at, _)| ends_at.is_some()));
+    }
+
+    #[test]
+    fn weekday_means_the_next_one() {
+        let today = Utc::now().date_naive();
+
+        let monday = parse_datetime("monday 12:00", Tz::UTC).unwrap().date_naive();
+        assert_eq!(monday.weekday(), Weekday::Mon);
+        assert!((1..=7).contains(&(monday - today).num_days()));
+
+        // Today's weekday rolls over to next week rather than meaning today
+        let same_day = match today.weekday() {
+            Weekday::Mon => "mon",
+            Weekday::Tue => "tue",
+            Weekday::Wed => "wed",
+            Weekday::Thu => "thu",
+            Weekday::Fri => "fri",
+            Weekday::Sat => "sat",
+            Weekday::Sun => "sun",
+        };
+        assert_eq!((parse_datetime(same_day, Tz::UTC).unwrap().date_naive() - today).num_days(), 7);
+    }
+
+    #[test]
+    fn bare_time_already_past_means_tomorrow() {
+        let now = Utc::now();
+        let past = (now - chrono::Duration::minutes(1)).format("%H:%M").to_string();
+
+        let until = parse_datetime(&past, Tz::UTC).unwrap() - now;
+        assert!(until > chrono::Duration::hours(23) && until <= chrono::Duration::hours(24), "{}", until);
+    }
+
+    #[test]
+    fn tomorrow_is_the_next_day() {
+        let today = Utc::now().date_naive();
+
+        let date = parse_datetime("tomorrow 12:00", Tz::UTC).unwrap();
+        assert_eq!(date.date_naive(), today.succ_opt().unwrap());
+        assert_eq!(date.time(), NaiveTime::from_hms_opt(12, 0, 0).unwrap());
+    }
+
+    #[test]
+    fn time_in_a_dst_gap_is_rejected() {
+        // Clocks in Warsaw jumped from 02:00 to 03:00 that night
+        assert_eq!(parse_datetime("2024-03-31 02:30", Tz::Europe__Warsaw), None);
+        assert_eq!(
+            parse_duration_or_date("2024-03-31 02:30", Language::English, Tz::Europe__Warsaw),
+            Err(DurationError::InvalidDate("2024-03-31 02:30".to_string()))
+        );
+    }
+
+    #[test]
+    fn rejects_past_and_invalid_dates() {
+        assert_eq!(parse_duration_or_date("2020-01-01", Language::English, Tz::UTC), Err(DurationError::InPast));
+        assert_eq!(parse_duration_or_date("01.01.2020 18:00", Language::English, Tz::UTC), Err(DurationError::InPast));
+        assert_eq!(
+            parse_duration_or_date("2024-13-45", Language::English, Tz::UTC),
+            Err(DurationError::InvalidDate("2024-13-45".to_string()))
+        );
+        assert_eq!(parse_duration_or_date("3x", Language::English, Tz::UTC), Err(DurationError::UnknownUnit("x".to_string())));
+    }
+
+    #[test]
+    fn accepts_future_dates_and_durations() {
+        assert!(parse_duration_or_date("2100-01-01", Language::English, Tz::UTC).is_ok_and(|seconds| seconds.is_some()));
+        assert_eq!(parse_duration_or_date("1d", Language::English, Tz::UTC), Ok(Some(86400)));
+        assert_eq!(parse_duration_or_date("never", Language::English, Tz::UTC), Ok(None));
+    }
+}